@@ -4,6 +4,7 @@ use std::pin::Pin;
 
 use anyhow::{Result, ensure};
 use bili_sync_entity::rule::Rule;
+use bili_sync_entity::trigger::Trigger;
 use bili_sync_entity::*;
 use futures::Stream;
 use sea_orm::ActiveValue::Set;
@@ -47,6 +48,18 @@ impl VideoSource for bangumi::Model {
         &self.rule
     }
 
+    fn danmaku_snapshot(&self) -> bool {
+        self.danmaku_snapshot
+    }
+
+    fn source_key(&self) -> (&'static str, i32) {
+        ("bangumi", self.id)
+    }
+
+    fn trigger(&self) -> &Option<Trigger> {
+        &self.trigger
+    }
+
     async fn refresh<'a>(
         self,
         bili_client: &'a BiliClient,
@@ -23,6 +23,7 @@ use bili_sync_entity::collection::Model as Collection;
 use bili_sync_entity::favorite::Model as Favorite;
 use bili_sync_entity::rule::Rule;
 use bili_sync_entity::submission::Model as Submission;
+use bili_sync_entity::trigger::Trigger;
 use bili_sync_entity::watch_later::Model as WatchLater;
 
 use crate::bilibili::{BiliClient, Credential, VideoInfo};
@@ -80,6 +81,15 @@ pub trait VideoSource {
 
     fn rule(&self) -> &Option<Rule>;
 
+    /// 是否在下载后定期重新抓取弹幕，合并为弹幕快照
+    fn danmaku_snapshot(&self) -> bool;
+
+    /// 视频源的类型与 id，类型与接口路径中的 {type} 一致，用于唯一标识一个视频源
+    fn source_key(&self) -> (&'static str, i32);
+
+    /// 视频源单独设置的触发条件，为空时跟随全局的下载任务执行
+    fn trigger(&self) -> &Option<Trigger>;
+
     fn log_refresh_video_start(&self) {
         info!("开始扫描{}..", self.display_name());
     }
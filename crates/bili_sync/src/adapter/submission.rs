@@ -3,6 +3,7 @@ use std::pin::Pin;
 
 use anyhow::{Result, ensure};
 use bili_sync_entity::rule::Rule;
+use bili_sync_entity::trigger::Trigger;
 use bili_sync_entity::*;
 use futures::Stream;
 use sea_orm::ActiveValue::Set;
@@ -82,6 +83,18 @@ impl VideoSource for submission::Model {
         &self.rule
     }
 
+    fn danmaku_snapshot(&self) -> bool {
+        self.danmaku_snapshot
+    }
+
+    fn source_key(&self) -> (&'static str, i32) {
+        ("submissions", self.id)
+    }
+
+    fn trigger(&self) -> &Option<Trigger> {
+        &self.trigger
+    }
+
     async fn refresh<'a>(
         self,
         bili_client: &'a BiliClient,
@@ -1,9 +1,15 @@
 use std::borrow::Borrow;
+use std::collections::HashSet;
 
+use bili_sync_entity::*;
 use itertools::Itertools;
-use sea_orm::{ConnectionTrait, DatabaseTransaction};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DatabaseTransaction, EntityTrait, QueryFilter, Select,
+    TransactionTrait,
+};
 
-use crate::api::response::{PageInfo, SimplePageInfo, SimpleVideoInfo, VideoInfo};
+use crate::api::response::{PageInfo, ResetFilteredVideosResponse, SimplePageInfo, SimpleVideoInfo, VideoInfo};
+use crate::utils::status::{PageStatus, VideoStatus};
 
 pub trait VideoRecord {
     fn as_id_status_tuple(&self) -> (i32, u32);
@@ -79,6 +85,67 @@ where
     Ok(())
 }
 
+/// 重置筛选出的视频及其分页中失败的子任务，force 时额外修正仍有子任务未执行却被标记为完成的视频
+pub async fn reset_videos_status(
+    query: Select<video::Entity>,
+    force: bool,
+    db: &DatabaseConnection,
+) -> Result<ResetFilteredVideosResponse, sea_orm::DbErr> {
+    let all_videos = query.into_partial_model::<SimpleVideoInfo>().all(db).await?;
+    let all_pages = page::Entity::find()
+        .filter(page::Column::VideoId.is_in(all_videos.iter().map(|v| v.id)))
+        .into_partial_model::<SimplePageInfo>()
+        .all(db)
+        .await?;
+    let resetted_pages_info = all_pages
+        .into_iter()
+        .filter_map(|mut page_info| {
+            let mut page_status = PageStatus::from(page_info.download_status);
+            if (force && page_status.force_reset_failed()) || page_status.reset_failed() {
+                page_info.download_status = page_status.into();
+                Some(page_info)
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    let video_ids_with_resetted_pages: HashSet<i32> = resetted_pages_info.iter().map(|page| page.video_id).collect();
+    let resetted_videos_info = all_videos
+        .into_iter()
+        .filter_map(|mut video_info| {
+            let mut video_status = VideoStatus::from(video_info.download_status);
+            let mut video_resetted = (force && video_status.force_reset_failed()) || video_status.reset_failed();
+            if video_ids_with_resetted_pages.contains(&video_info.id) {
+                video_status.set(4, 0); // 将"分页下载"重置为 0
+                video_resetted = true;
+            }
+            if video_resetted {
+                video_info.download_status = video_status.into();
+                Some(video_info)
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    let has_video_updates = !resetted_videos_info.is_empty();
+    let has_page_updates = !resetted_pages_info.is_empty();
+    if has_video_updates || has_page_updates {
+        let txn = db.begin().await?;
+        if has_video_updates {
+            update_video_download_status(&txn, &resetted_videos_info, Some(500)).await?;
+        }
+        if has_page_updates {
+            update_page_download_status(&txn, &resetted_pages_info, Some(500)).await?;
+        }
+        txn.commit().await?;
+    }
+    Ok(ResetFilteredVideosResponse {
+        resetted: has_video_updates || has_page_updates,
+        resetted_videos_count: resetted_videos_info.len(),
+        resetted_pages_count: resetted_pages_info.len(),
+    })
+}
+
 async fn execute_video_update_batch(
     txn: &DatabaseTransaction,
     videos: impl Iterator<Item = (i32, u32)>,
@@ -5,4 +5,5 @@ mod response;
 mod routes;
 mod wrapper;
 
+pub use helper::reset_videos_status;
 pub use routes::{LogHelper, MAX_HISTORY_LOGS, router};
@@ -1,8 +1,10 @@
 use bili_sync_entity::rule::Rule;
+use bili_sync_entity::trigger::Trigger;
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
 use crate::bilibili::CollectionType;
+use crate::config::BundleFormat;
 
 #[derive(Deserialize)]
 pub struct VideosRequest {
@@ -36,6 +38,14 @@ pub struct ResetFilteredVideoStatusRequest {
     pub force: bool,
 }
 
+#[derive(Deserialize, Validate)]
+pub struct VideoStatusUpdate {
+    #[validate(range(min = 0, max = 5))]
+    pub status_index: usize,
+    #[validate(custom(function = "crate::utils::validation::validate_status_value"))]
+    pub status_value: u32,
+}
+
 #[derive(Deserialize, Validate)]
 pub struct StatusUpdate {
     #[validate(range(min = 0, max = 4))]
@@ -55,7 +65,7 @@ pub struct PageStatusUpdate {
 pub struct UpdateVideoStatusRequest {
     #[serde(default)]
     #[validate(nested)]
-    pub video_updates: Vec<StatusUpdate>,
+    pub video_updates: Vec<VideoStatusUpdate>,
     #[serde(default)]
     #[validate(nested)]
     pub page_updates: Vec<PageStatusUpdate>,
@@ -72,7 +82,7 @@ pub struct UpdateFilteredVideoStatusRequest {
     pub query: Option<String>,
     #[serde(default)]
     #[validate(nested)]
-    pub video_updates: Vec<StatusUpdate>,
+    pub video_updates: Vec<VideoStatusUpdate>,
     #[serde(default)]
     #[validate(nested)]
     pub page_updates: Vec<StatusUpdate>,
@@ -139,6 +149,24 @@ pub struct UpdateVideoSourceRequest {
     pub enabled: bool,
     pub rule: Option<Rule>,
     pub use_dynamic_api: Option<bool>,
+    pub danmaku_snapshot: Option<bool>,
+    /// 视频源单独的触发条件，为空时跟随全局设置
+    #[serde(default)]
+    #[validate(custom(function = "crate::utils::validation::validate_trigger"))]
+    pub trigger: Option<Trigger>,
+}
+
+#[derive(Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleDryRunRequest {
+    pub rule: Option<Rule>,
+    /// 仅列出应用规则后下载状态发生变化的视频
+    #[serde(default)]
+    pub changed_only: bool,
+    #[validate(range(max = 100_000))]
+    pub page: Option<u64>,
+    #[validate(range(min = 1, max = 100))]
+    pub page_size: Option<u64>,
 }
 
 #[derive(Deserialize)]
@@ -146,7 +174,54 @@ pub struct BangumiSectionsRequest {
     pub season_id: i64,
 }
 
+/// 未提供的模板使用当前配置中的值
+#[derive(Deserialize)]
+pub struct TemplatePreviewRequest {
+    pub video_name: Option<String>,
+    pub page_name: Option<String>,
+    pub bangumi_name: Option<String>,
+    pub limit: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct ExportConfigRequest {
+    #[serde(default)]
+    pub format: BundleFormat,
+    /// 不导出鉴权令牌、登录凭据与通知器
+    #[serde(default)]
+    pub exclude_secrets: bool,
+}
+
+/// dry_run 时仅返回与当前配置的差异，不实际导入
+#[derive(Deserialize)]
+pub struct ImportConfigRequest {
+    pub content: String,
+    #[serde(default)]
+    pub format: BundleFormat,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Deserialize)]
+pub struct RelayoutRequest {
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct DefaultPathRequest {
     pub name: String,
 }
+
+#[derive(Deserialize, Validate)]
+pub struct UpdateQueuePriorityRequest {
+    #[validate(range(min = -100, max = 100))]
+    pub priority: i32,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct ReorderQueueRequest {
+    /// 需要重新排列的任务 id，按照期望的下载顺序排列
+    #[validate(length(min = 1))]
+    pub ids: Vec<i32>,
+}
@@ -1,8 +1,14 @@
+use bili_sync_entity::download_queue::QueueStatus;
 use bili_sync_entity::rule::Rule;
+use bili_sync_entity::trigger::Trigger;
 use bili_sync_entity::*;
 use sea_orm::{DerivePartialModel, FromQueryResult};
 use serde::Serialize;
 
+use crate::config::ConfigChange;
+use crate::utils::queue::QueueStats;
+use crate::utils::risk_control::RiskControlState;
+use crate::utils::rule::RuleExplanation;
 use crate::utils::status::{PageStatus, VideoStatus};
 
 #[derive(Serialize)]
@@ -110,7 +116,7 @@ fn serde_video_download_status<S>(status: &u32, serializer: S) -> Result<S::Ok,
 where
     S: serde::Serializer,
 {
-    let status: [u32; 5] = VideoStatus::from(*status).into();
+    let status: [u32; 6] = VideoStatus::from(*status).into();
     status.serialize(serializer)
 }
 
@@ -208,6 +214,7 @@ pub struct DashBoardResponse {
     pub enable_watch_later: bool,
     pub enabled_bangumi: u64,
     pub videos_by_day: Vec<DayCountPair>,
+    pub risk_control: RiskControlState,
 }
 
 #[derive(Serialize, Clone, Copy)]
@@ -231,14 +238,104 @@ pub struct VideoSourceDetail {
     #[serde(default)]
     pub rule_display: Option<String>,
     #[serde(default)]
+    pub rule_dsl: Option<String>,
+    #[serde(default)]
     pub use_dynamic_api: Option<bool>,
     pub enabled: bool,
+    pub danmaku_snapshot: bool,
+    pub trigger: Option<Trigger>,
     #[serde(default)]
     pub season_type: Option<u16>,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleDryRunResponse {
+    pub total: usize,
+    /// 满足规则的视频数量
+    pub matched: usize,
+    /// 当前不下载、应用规则后会下载的视频数量
+    pub newly_included: usize,
+    /// 当前会下载、应用规则后不再下载的视频数量
+    pub newly_excluded: usize,
+    /// 符合列出条件的视频数量，用于分页
+    pub total_count: usize,
+    pub videos: Vec<RuleDryRunVideo>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleDryRunVideo {
+    pub id: i32,
+    pub bvid: String,
+    pub name: String,
+    pub should_download: bool,
+    /// 综合全局规则与视频源规则后是否下载，命中全局放行规则时即使不满足视频源规则也为 true
+    pub matched: bool,
+    /// 视频源规则的评估过程
+    pub explanation: Option<RuleExplanation>,
+    /// 全局排除规则的评估过程，命中时一律不下载
+    pub global_deny: Option<RuleExplanation>,
+    /// 全局放行规则的评估过程，未命中排除规则时命中该规则会覆盖视频源规则
+    pub global_allow: Option<RuleExplanation>,
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdateVideoSourceResponse {
     pub rule_display: Option<String>,
+    pub rule_dsl: Option<String>,
+    /// 修改已经保存，但重新注册视频源下载任务失败时的错误信息
+    pub reschedule_error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ExportConfigResponse {
+    pub content: String,
+}
+
+#[derive(Serialize)]
+pub struct ImportConfigResponse {
+    pub changes: Vec<ConfigChange>,
+    pub applied: bool,
+    /// 导入已经生效，但重新注册视频源下载任务失败时的错误信息
+    pub reschedule_error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct TemplatePreviewResponse {
+    /// 模板自身的语法错误
+    pub errors: Vec<String>,
+    pub samples: Vec<TemplatePreviewSample>,
+}
+
+#[derive(Serialize)]
+pub struct TemplatePreviewSample {
+    pub video_id: i32,
+    pub bvid: String,
+    pub name: String,
+    /// 相对于视频源目录的视频文件路径
+    pub paths: Vec<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct QueueResponse {
+    pub items: Vec<QueueItem>,
+    pub stats: QueueStats,
+}
+
+/// 下载队列中的任务，按照下载顺序排列
+#[derive(Serialize)]
+pub struct QueueItem {
+    pub id: i32,
+    pub video_id: i32,
+    pub bvid: String,
+    pub name: String,
+    pub upper_name: String,
+    pub source_type: String,
+    pub source_id: i32,
+    pub priority: i32,
+    pub position: i64,
+    pub status: QueueStatus,
 }
@@ -1,20 +1,37 @@
+use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
 
 use anyhow::Result;
-use axum::extract::Extension;
+use axum::extract::{Extension, Query};
 use axum::routing::{get, post};
 use axum::{Json, Router};
-use sea_orm::DatabaseConnection;
+use bili_sync_entity::{page, video};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use tokio_util::sync::CancellationToken;
 
+use crate::api::request::{ExportConfigRequest, ImportConfigRequest, TemplatePreviewRequest};
+use crate::api::response::{
+    ExportConfigResponse, ImportConfigResponse, TemplatePreviewResponse, TemplatePreviewSample,
+};
 use crate::api::wrapper::{ApiError, ApiResponse, ValidatedJson};
 use crate::bilibili::BiliClient;
-use crate::config::{Config, VersionedConfig};
-use crate::notifier::Notifier;
+use crate::config::{Config, ConfigBundle, PathSafeTemplate, VersionedConfig, register_helpers};
+use crate::notifier::{NotificationEvent, Notifier};
+use crate::task::DownloadTaskManager;
+use crate::utils::layout::compute_layout;
+use crate::workflow::get_cached_season_title;
+
+/// 每个视频最多预览的分页数量
+const PREVIEW_PAGE_LIMIT: usize = 5;
 
 pub(super) fn router() -> Router {
     Router::new()
         .route("/config", get(get_config).put(update_config))
+        .route("/config/export", get(export_config))
+        .route("/config/import", post(import_config))
         .route("/config/notifiers/ping", post(ping_notifiers))
+        .route("/config/template/preview", post(preview_template))
 }
 
 /// 获取全局配置
@@ -32,6 +49,38 @@ pub async fn update_config(
     Ok(ApiResponse::ok(new_config))
 }
 
+/// 导出全局配置与全部视频源
+pub async fn export_config(
+    Extension(db): Extension<DatabaseConnection>,
+    Query(params): Query<ExportConfigRequest>,
+) -> Result<ApiResponse<ExportConfigResponse>, ApiError> {
+    let bundle = ConfigBundle::export(&db, params.exclude_secrets).await?;
+    Ok(ApiResponse::ok(ExportConfigResponse {
+        content: bundle.dump(params.format)?,
+    }))
+}
+
+/// 导入全局配置与视频源，返回与导入前相比的差异
+pub async fn import_config(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(bili_client): Extension<Arc<BiliClient>>,
+    Json(request): Json<ImportConfigRequest>,
+) -> Result<ApiResponse<ImportConfigResponse>, ApiError> {
+    let (bundle, changes) = ConfigBundle::prepare_import(&request.content, request.format, &bili_client, &db).await?;
+    let applied = !request.dry_run && !changes.is_empty();
+    let mut reschedule_error = None;
+    if applied {
+        bundle.apply(&db).await?;
+        // 导入已经提交，重新注册下载任务失败时不再返回错误，而是告知调用方
+        reschedule_error = DownloadTaskManager::get().reload_source_tasks().await;
+    }
+    Ok(ApiResponse::ok(ImportConfigResponse {
+        changes,
+        applied,
+        reschedule_error,
+    }))
+}
+
 pub async fn ping_notifiers(
     Extension(bili_client): Extension<Arc<BiliClient>>,
     Json(mut notifier): Json<Notifier>,
@@ -41,7 +90,91 @@ pub async fn ping_notifiers(
         *ignore_cache = Some(());
     }
     notifier
-        .notify(bili_client.inner_client(), "This is a test notification from BiliSync.")
+        .notify(
+            bili_client.inner_client(),
+            &NotificationEvent::Test {
+                message: "This is a test notification from BiliSync.".to_owned(),
+            },
+        )
         .await?;
     Ok(ApiResponse::ok(()))
 }
+
+/// 使用候选的文件名模板渲染数据库中最近的视频，预览生成的路径，不会修改配置
+pub async fn preview_template(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(bili_client): Extension<Arc<BiliClient>>,
+    Json(request): Json<TemplatePreviewRequest>,
+) -> Result<ApiResponse<TemplatePreviewResponse>, ApiError> {
+    let config = VersionedConfig::get().snapshot();
+    let mut template = handlebars::Handlebars::new();
+    register_helpers(&mut template);
+    let mut errors = Vec::new();
+    for (name, candidate, current) in [
+        ("video", request.video_name, &config.video_name),
+        ("page", request.page_name, &config.page_name),
+        ("bangumi", request.bangumi_name, &config.bangumi_name),
+    ] {
+        if let Err(e) = template.path_safe_register(name, candidate.unwrap_or_else(|| current.clone())) {
+            errors.push(format!("{} 模板无效：{:#}", name, e));
+        }
+    }
+    let videos = video::Entity::find()
+        .filter(
+            video::Column::Valid
+                .eq(true)
+                .and(video::Column::SinglePage.is_not_null()),
+        )
+        .order_by_desc(video::Column::Id)
+        .limit(request.limit.unwrap_or(10).clamp(1, 50))
+        .all(&db)
+        .await?;
+    let mut pages = HashMap::<i32, Vec<page::Model>>::new();
+    for page_model in page::Entity::find()
+        .filter(page::Column::VideoId.is_in(videos.iter().map(|v| v.id)))
+        .order_by_asc(page::Column::Pid)
+        .all(&db)
+        .await?
+    {
+        pages.entry(page_model.video_id).or_default().push(page_model);
+    }
+    let mut samples = Vec::with_capacity(videos.len());
+    for video_model in videos {
+        let page_models = pages.remove(&video_model.id).unwrap_or_default();
+        // 番剧与下载时一致，使用接口中的季度标题渲染
+        let api_title = match &video_model.season_id {
+            Some(season_id) if video_model.bangumi_id.is_some() => {
+                get_cached_season_title(&bili_client, season_id, CancellationToken::new()).await
+            }
+            _ => None,
+        };
+        let layout = compute_layout(
+            &template,
+            Path::new(""),
+            &video_model,
+            &page_models,
+            &config.time_format,
+            api_title.as_deref(),
+        );
+        let (paths, error) = match layout {
+            Ok(layout) => (
+                layout
+                    .pages
+                    .into_iter()
+                    .take(PREVIEW_PAGE_LIMIT)
+                    .map(|(_, path)| path.to_string_lossy().into_owned())
+                    .collect(),
+                None,
+            ),
+            Err(e) => (Vec::new(), Some(format!("{:#}", e))),
+        };
+        samples.push(TemplatePreviewSample {
+            video_id: video_model.id,
+            bvid: video_model.bvid,
+            name: video_model.name,
+            paths,
+            error,
+        });
+    }
+    Ok(ApiResponse::ok(TemplatePreviewResponse { errors, samples }))
+}
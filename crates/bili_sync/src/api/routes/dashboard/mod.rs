@@ -6,6 +6,7 @@ use sea_orm::{FromQueryResult, Statement};
 
 use crate::api::response::{DashBoardResponse, DayCountPair};
 use crate::api::wrapper::{ApiError, ApiResponse};
+use crate::utils::risk_control;
 
 pub(super) fn router() -> Router {
     Router::new().route("/dashboard", get(get_dashboard))
@@ -65,5 +66,6 @@ ORDER BY
         enable_watch_later: enabled_watch_later > 0,
         enabled_bangumi,
         videos_by_day,
+        risk_control: risk_control::state(),
     }))
 }
@@ -13,6 +13,7 @@ use crate::config::VersionedConfig;
 mod config;
 mod dashboard;
 mod me;
+mod queue;
 mod task;
 mod video_sources;
 mod videos;
@@ -30,6 +31,7 @@ pub fn router() -> Router {
             .merge(dashboard::router())
             .merge(ws::router())
             .merge(task::router())
+            .merge(queue::router())
             .layer(middleware::from_fn(auth)),
     )
 }
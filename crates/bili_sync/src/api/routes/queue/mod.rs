@@ -0,0 +1,150 @@
+use anyhow::Result;
+use axum::Router;
+use axum::extract::{Extension, Path};
+use axum::routing::{get, post, put};
+use bili_sync_entity::download_queue::{self, QueueStatus};
+use bili_sync_entity::*;
+use sea_orm::ActiveValue::Set;
+use sea_orm::entity::prelude::*;
+use sea_orm::{DatabaseConnection, QueryOrder};
+
+use crate::api::error::InnerApiError;
+use crate::api::request::{ReorderQueueRequest, UpdateQueuePriorityRequest};
+use crate::api::response::{QueueItem, QueueResponse};
+use crate::api::wrapper::{ApiError, ApiResponse, ValidatedJson};
+use crate::utils::queue::{mark_queue_reordered, refresh_queue_stats, reorder_items, subscribe_queue_stats};
+
+pub(super) fn router() -> Router {
+    Router::new()
+        .route("/queue", get(get_queue))
+        .route("/queue/reorder", post(reorder_queue))
+        .route("/queue/{id}/priority", put(update_queue_priority))
+        .route("/queue/{id}/pause", post(pause_queue_item))
+        .route("/queue/{id}/resume", post(resume_queue_item))
+        .route("/queue/{id}/cancel", post(cancel_queue_item))
+}
+
+/// 列出下载队列中的全部任务，按照下载顺序排列
+pub async fn get_queue(Extension(db): Extension<DatabaseConnection>) -> Result<ApiResponse<QueueResponse>, ApiError> {
+    let items = download_queue::Entity::find()
+        .find_also_related(video::Entity)
+        .order_by_desc(download_queue::Column::Priority)
+        .order_by_asc(download_queue::Column::Position)
+        .order_by_asc(download_queue::Column::Id)
+        .all(&db)
+        .await?
+        .into_iter()
+        .map(|(item, video)| {
+            let (bvid, name, upper_name) = video
+                .map(|video| (video.bvid, video.name, video.upper_name))
+                .unwrap_or_default();
+            QueueItem {
+                id: item.id,
+                video_id: item.video_id,
+                bvid,
+                name,
+                upper_name,
+                source_type: item.source_type,
+                source_id: item.source_id,
+                priority: item.priority,
+                position: item.position,
+                status: item.status,
+            }
+        })
+        .collect();
+    refresh_queue_stats(&db).await?;
+    Ok(ApiResponse::ok(QueueResponse {
+        items,
+        stats: *subscribe_queue_stats().borrow(),
+    }))
+}
+
+/// 调整任务的优先级，优先级越高越先下载
+pub async fn update_queue_priority(
+    Path(id): Path<i32>,
+    Extension(db): Extension<DatabaseConnection>,
+    ValidatedJson(request): ValidatedJson<UpdateQueuePriorityRequest>,
+) -> Result<ApiResponse<bool>, ApiError> {
+    let Some(item) = download_queue::Entity::find_by_id(id).one(&db).await? else {
+        return Err(InnerApiError::NotFound(id).into());
+    };
+    let mut item: download_queue::ActiveModel = item.into();
+    item.priority = Set(request.priority);
+    item.save(&db).await?;
+    mark_queue_reordered();
+    Ok(ApiResponse::ok(true))
+}
+
+/// 按照请求中的顺序重新排列这些任务，不影响其它任务的位置
+pub async fn reorder_queue(
+    Extension(db): Extension<DatabaseConnection>,
+    ValidatedJson(request): ValidatedJson<ReorderQueueRequest>,
+) -> Result<ApiResponse<bool>, ApiError> {
+    reorder_items(&request.ids, &db).await?;
+    mark_queue_reordered();
+    Ok(ApiResponse::ok(true))
+}
+
+/// 暂停任务，正在下载的任务会在本次下载结束后保持暂停
+pub async fn pause_queue_item(
+    Path(id): Path<i32>,
+    Extension(db): Extension<DatabaseConnection>,
+) -> Result<ApiResponse<bool>, ApiError> {
+    update_status(
+        id,
+        &[QueueStatus::Pending, QueueStatus::Running],
+        QueueStatus::Paused,
+        &db,
+    )
+    .await
+}
+
+/// 恢复暂停或已取消的任务
+pub async fn resume_queue_item(
+    Path(id): Path<i32>,
+    Extension(db): Extension<DatabaseConnection>,
+) -> Result<ApiResponse<bool>, ApiError> {
+    update_status(
+        id,
+        &[QueueStatus::Paused, QueueStatus::Cancelled],
+        QueueStatus::Pending,
+        &db,
+    )
+    .await
+}
+
+/// 取消任务，已取消的任务保留在队列中，不会被重新加入，也不会再被下载，直到手动恢复
+pub async fn cancel_queue_item(
+    Path(id): Path<i32>,
+    Extension(db): Extension<DatabaseConnection>,
+) -> Result<ApiResponse<bool>, ApiError> {
+    update_status(
+        id,
+        &[QueueStatus::Pending, QueueStatus::Running, QueueStatus::Paused],
+        QueueStatus::Cancelled,
+        &db,
+    )
+    .await
+}
+
+async fn update_status(
+    id: i32,
+    from: &[QueueStatus],
+    to: QueueStatus,
+    db: &DatabaseConnection,
+) -> Result<ApiResponse<bool>, ApiError> {
+    let Some(item) = download_queue::Entity::find_by_id(id).one(db).await? else {
+        return Err(InnerApiError::NotFound(id).into());
+    };
+    if !from.contains(&item.status) {
+        return Err(InnerApiError::BadRequest(format!("任务当前的状态为 {:?}，无法执行该操作", item.status)).into());
+    }
+    let res = download_queue::Entity::update_many()
+        .col_expr(download_queue::Column::Status, Expr::value(to))
+        .filter(download_queue::Column::Id.eq(id))
+        .filter(download_queue::Column::Status.eq(item.status))
+        .exec(db)
+        .await?;
+    refresh_queue_stats(db).await?;
+    Ok(ApiResponse::ok(res.rows_affected == 1))
+}
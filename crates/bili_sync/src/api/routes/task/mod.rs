@@ -1,15 +1,43 @@
 use anyhow::Result;
-use axum::Router;
 use axum::routing::post;
+use axum::{Json, Router};
 
+use crate::api::request::RelayoutRequest;
 use crate::api::wrapper::{ApiError, ApiResponse};
-use crate::task::DownloadTaskManager;
+use crate::task::{DownloadTaskManager, RelayoutReport};
 
 pub(super) fn router() -> Router {
-    Router::new().route("/task/download", post(new_download_task))
+    Router::new()
+        .route("/task/download", post(new_download_task))
+        .route("/task/pause", post(pause_download_task))
+        .route("/task/resume", post(resume_download_task))
+        .route("/task/cancel", post(cancel_download_task))
+        .route("/task/relayout", post(relayout_files))
 }
 
 pub async fn new_download_task() -> Result<ApiResponse<bool>, ApiError> {
     DownloadTaskManager::get().download_once().await?;
     Ok(ApiResponse::ok(true))
 }
+
+/// 暂停正在运行的下载任务，返回值表示状态是否发生了变化
+pub async fn pause_download_task() -> Result<ApiResponse<bool>, ApiError> {
+    Ok(ApiResponse::ok(DownloadTaskManager::get().pause()?))
+}
+
+/// 恢复已暂停的下载任务
+pub async fn resume_download_task() -> Result<ApiResponse<bool>, ApiError> {
+    Ok(ApiResponse::ok(DownloadTaskManager::get().resume()?))
+}
+
+/// 取消正在运行的下载任务，已完成的内容会被保留
+pub async fn cancel_download_task() -> Result<ApiResponse<bool>, ApiError> {
+    Ok(ApiResponse::ok(DownloadTaskManager::get().cancel()?))
+}
+
+/// 按照当前模板重新整理已下载的文件，dry_run 时仅返回计划而不做任何修改
+pub async fn relayout_files(Json(request): Json<RelayoutRequest>) -> Result<ApiResponse<RelayoutReport>, ApiError> {
+    Ok(ApiResponse::ok(
+        DownloadTaskManager::get().relayout(request.dry_run).await?,
+    ))
+}
@@ -9,23 +9,27 @@ use bili_sync_entity::*;
 use bili_sync_migration::Expr;
 use sea_orm::ActiveValue::Set;
 use sea_orm::entity::prelude::*;
-use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QuerySelect, QueryTrait, TransactionTrait};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryOrder, QuerySelect, TransactionTrait};
 
 use crate::adapter::{_ActiveModel, VideoSource as _, VideoSourceEnum};
 use crate::api::error::InnerApiError;
 use crate::api::request::{
     BangumiSectionsRequest, DefaultPathRequest, InsertBangumiRequest, InsertCollectionRequest, InsertFavoriteRequest,
-    InsertSubmissionRequest, UpdateVideoSourceRequest,
+    InsertSubmissionRequest, RuleDryRunRequest, UpdateVideoSourceRequest,
 };
 use crate::api::response::{
-    UpdateVideoSourceResponse, VideoSource, VideoSourceDetail, VideoSourceWithSeasonType, VideoSourcesDetailsResponse,
-    VideoSourcesResponse,
+    RuleDryRunResponse, RuleDryRunVideo, UpdateVideoSourceResponse, VideoSource, VideoSourceDetail,
+    VideoSourceWithSeasonType, VideoSourcesDetailsResponse, VideoSourcesResponse,
 };
 use crate::api::wrapper::{ApiError, ApiResponse, ValidatedJson};
 use crate::bilibili::bangumi_list::SectionInfo;
-use crate::bilibili::{BangumiList, BiliClient, Collection, CollectionItem, FavoriteList, Submission};
+use crate::bilibili::{BangumiList, BiliClient, CollectionItem};
 use crate::config::{PathSafeTemplate, TEMPLATE, VersionedConfig};
-use crate::utils::rule::FieldEvaluatable;
+use crate::task::DownloadTaskManager;
+use crate::utils::model::{
+    NewVideoSource, delete_video_source, get_all_video_sources, get_enabled_video_sources, insert_video_source,
+};
+use crate::utils::rule::explain_rule;
 
 pub(super) fn router() -> Router {
     Router::new()
@@ -40,6 +44,8 @@ pub(super) fn router() -> Router {
             put(update_video_source).delete(remove_video_source),
         )
         .route("/video-sources/{type}/{id}/evaluate", post(evaluate_video_source))
+        .route("/video-sources/{type}/{id}/dry-run", post(dry_run_video_source_rule))
+        .route("/video-sources/{type}/{id}/sync", post(sync_video_source))
         .route("/video-sources/favorites", post(insert_favorite))
         .route("/video-sources/collections", post(insert_collection))
         .route("/video-sources/submissions", post(insert_submission))
@@ -134,7 +140,9 @@ pub async fn get_video_sources_details(
                 collection::Column::Name,
                 collection::Column::Path,
                 collection::Column::Rule,
-                collection::Column::Enabled
+                collection::Column::Enabled,
+                collection::Column::DanmakuSnapshot,
+                collection::Column::Trigger
             ])
             .into_model::<VideoSourceDetail>()
             .all(&db),
@@ -145,7 +153,9 @@ pub async fn get_video_sources_details(
                 favorite::Column::Name,
                 favorite::Column::Path,
                 favorite::Column::Rule,
-                favorite::Column::Enabled
+                favorite::Column::Enabled,
+                favorite::Column::DanmakuSnapshot,
+                favorite::Column::Trigger
             ])
             .into_model::<VideoSourceDetail>()
             .all(&db),
@@ -156,6 +166,8 @@ pub async fn get_video_sources_details(
                 submission::Column::Id,
                 submission::Column::Path,
                 submission::Column::Enabled,
+                submission::Column::DanmakuSnapshot,
+                submission::Column::Trigger,
                 submission::Column::Rule,
                 submission::Column::UseDynamicApi
             ])
@@ -168,6 +180,8 @@ pub async fn get_video_sources_details(
                 watch_later::Column::Id,
                 watch_later::Column::Path,
                 watch_later::Column::Enabled,
+                watch_later::Column::DanmakuSnapshot,
+                watch_later::Column::Trigger,
                 watch_later::Column::Rule
             ])
             .into_model::<VideoSourceDetail>()
@@ -179,6 +193,8 @@ pub async fn get_video_sources_details(
                 bangumi::Column::Id,
                 bangumi::Column::Path,
                 bangumi::Column::Enabled,
+                bangumi::Column::DanmakuSnapshot,
+                bangumi::Column::Trigger,
                 bangumi::Column::Rule,
                 bangumi::Column::SeasonType
             ])
@@ -192,8 +208,11 @@ pub async fn get_video_sources_details(
             path: String::new(),
             rule: None,
             rule_display: None,
+            rule_dsl: None,
             use_dynamic_api: None,
             enabled: false,
+            danmaku_snapshot: false,
+            trigger: None,
             season_type: None,
         })
     }
@@ -224,6 +243,7 @@ pub async fn get_video_sources_details(
         sources.iter_mut().for_each(|item| {
             if let Some(rule) = &item.rule {
                 item.rule_display = Some(rule.to_string());
+                item.rule_dsl = Some(rule.to_dsl());
             }
         });
     }
@@ -261,12 +281,17 @@ pub async fn update_video_source(
     ValidatedJson(request): ValidatedJson<UpdateVideoSourceRequest>,
 ) -> Result<ApiResponse<UpdateVideoSourceResponse>, ApiError> {
     let rule_display = request.rule.as_ref().map(|rule| rule.to_string());
+    let rule_dsl = request.rule.as_ref().map(|rule| rule.to_dsl());
     let active_model = match source_type.as_str() {
         "collections" => collection::Entity::find_by_id(id).one(&db).await?.map(|model| {
             let mut active_model: collection::ActiveModel = model.into();
             active_model.path = Set(request.path);
             active_model.enabled = Set(request.enabled);
             active_model.rule = Set(request.rule);
+            active_model.trigger = Set(request.trigger);
+            if let Some(danmaku_snapshot) = request.danmaku_snapshot {
+                active_model.danmaku_snapshot = Set(danmaku_snapshot);
+            }
             _ActiveModel::Collection(active_model)
         }),
         "favorites" => favorite::Entity::find_by_id(id).one(&db).await?.map(|model| {
@@ -274,6 +299,10 @@ pub async fn update_video_source(
             active_model.path = Set(request.path);
             active_model.enabled = Set(request.enabled);
             active_model.rule = Set(request.rule);
+            active_model.trigger = Set(request.trigger);
+            if let Some(danmaku_snapshot) = request.danmaku_snapshot {
+                active_model.danmaku_snapshot = Set(danmaku_snapshot);
+            }
             _ActiveModel::Favorite(active_model)
         }),
         "submissions" => submission::Entity::find_by_id(id).one(&db).await?.map(|model| {
@@ -281,6 +310,10 @@ pub async fn update_video_source(
             active_model.path = Set(request.path);
             active_model.enabled = Set(request.enabled);
             active_model.rule = Set(request.rule);
+            active_model.trigger = Set(request.trigger);
+            if let Some(danmaku_snapshot) = request.danmaku_snapshot {
+                active_model.danmaku_snapshot = Set(danmaku_snapshot);
+            }
             if let Some(use_dynamic_api) = request.use_dynamic_api {
                 active_model.use_dynamic_api = Set(use_dynamic_api);
             }
@@ -295,6 +328,10 @@ pub async fn update_video_source(
                 active_model.path = Set(request.path);
                 active_model.enabled = Set(request.enabled);
                 active_model.rule = Set(request.rule);
+                active_model.trigger = Set(request.trigger);
+                if let Some(danmaku_snapshot) = request.danmaku_snapshot {
+                    active_model.danmaku_snapshot = Set(danmaku_snapshot);
+                }
                 Some(_ActiveModel::WatchLater(active_model))
             }
             None => {
@@ -306,6 +343,8 @@ pub async fn update_video_source(
                         path: Set(request.path),
                         enabled: Set(request.enabled),
                         rule: Set(request.rule),
+                        trigger: Set(request.trigger),
+                        danmaku_snapshot: Set(request.danmaku_snapshot.unwrap_or_default()),
                         ..Default::default()
                     }))
                 }
@@ -316,6 +355,10 @@ pub async fn update_video_source(
             active_model.path = Set(request.path);
             active_model.enabled = Set(request.enabled);
             active_model.rule = Set(request.rule);
+            active_model.trigger = Set(request.trigger);
+            if let Some(danmaku_snapshot) = request.danmaku_snapshot {
+                active_model.danmaku_snapshot = Set(danmaku_snapshot);
+            }
             _ActiveModel::Bangumi(active_model)
         }),
         _ => return Err(InnerApiError::BadRequest("Invalid video source type".to_string()).into()),
@@ -324,7 +367,12 @@ pub async fn update_video_source(
         return Err(InnerApiError::NotFound(id).into());
     };
     active_model.save(&db).await?;
-    Ok(ApiResponse::ok(UpdateVideoSourceResponse { rule_display }))
+    let reschedule_error = DownloadTaskManager::get().reload_source_tasks().await;
+    Ok(ApiResponse::ok(UpdateVideoSourceResponse {
+        rule_display,
+        rule_dsl,
+        reschedule_error,
+    }))
 }
 
 pub async fn remove_video_source(
@@ -342,26 +390,9 @@ pub async fn remove_video_source(
     let Some(video_source) = video_source else {
         return Err(InnerApiError::NotFound(id).into());
     };
-    let txn = db.begin().await?;
-    page::Entity::delete_many()
-        .filter(
-            page::Column::VideoId.in_subquery(
-                video::Entity::find()
-                    .filter(video_source.filter_expr())
-                    .select_only()
-                    .column(video::Column::Id)
-                    .as_query()
-                    .to_owned(),
-            ),
-        )
-        .exec(&txn)
-        .await?;
-    video::Entity::delete_many()
-        .filter(video_source.filter_expr())
-        .exec(&txn)
-        .await?;
-    video_source.delete_from_db(&txn).await?;
-    txn.commit().await?;
+    delete_video_source(video_source, &db).await?;
+    // 视频源已经删除，重新注册任务失败时只会留下一个找不到视频源的空任务，不影响删除的结果
+    DownloadTaskManager::get().reload_source_tasks().await;
     Ok(ApiResponse::ok(true))
 }
 
@@ -428,9 +459,10 @@ pub async fn evaluate_video_source(
         .find_with_related(page::Entity)
         .all(&db)
         .await?;
+    let global_rule = &VersionedConfig::get().read().global_rule;
     let video_should_download_pairs = videos
         .into_iter()
-        .map(|(video, pages)| (video.id, rule.evaluate_model(&video, &pages)))
+        .map(|(video, pages)| (video.id, global_rule.evaluate_model(&rule, &video, &pages)))
         .collect::<Vec<(i32, bool)>>();
     let txn = db.begin().await?;
     for chunk in video_should_download_pairs.chunks(500) {
@@ -452,24 +484,104 @@ pub async fn evaluate_video_source(
     Ok(ApiResponse::ok(true))
 }
 
+/// 使用草稿规则评估视频源下的视频，仅返回评估结果，不写入数据库
+pub async fn dry_run_video_source_rule(
+    Path((source_type, id)): Path<(String, i32)>,
+    Extension(db): Extension<DatabaseConnection>,
+    ValidatedJson(request): ValidatedJson<RuleDryRunRequest>,
+) -> Result<ApiResponse<RuleDryRunResponse>, ApiError> {
+    let filter_condition = match source_type.as_str() {
+        "collections" => video::Column::CollectionId.eq(id),
+        "favorites" => video::Column::FavoriteId.eq(id),
+        "submissions" => video::Column::SubmissionId.eq(id),
+        "watch_later" => video::Column::WatchLaterId.eq(id),
+        "bangumi" => video::Column::BangumiId.eq(id),
+        _ => return Err(InnerApiError::BadRequest("Invalid video source type".to_string()).into()),
+    };
+    let videos: Vec<(video::Model, Vec<page::Model>)> = video::Entity::find()
+        .filter(filter_condition)
+        .order_by_desc(video::Column::Id)
+        .find_with_related(page::Entity)
+        .all(&db)
+        .await?;
+    let global_rule = &VersionedConfig::get().read().global_rule;
+    let videos = videos
+        .into_iter()
+        .map(|(video, pages)| RuleDryRunVideo {
+            matched: global_rule.evaluate_model(&request.rule, &video, &pages),
+            explanation: request.rule.as_ref().map(|rule| explain_rule(rule, &video, &pages)),
+            global_deny: global_rule.deny.as_ref().map(|rule| explain_rule(rule, &video, &pages)),
+            global_allow: global_rule
+                .allow
+                .as_ref()
+                .map(|rule| explain_rule(rule, &video, &pages)),
+            id: video.id,
+            bvid: video.bvid,
+            name: video.name,
+            should_download: video.should_download,
+        })
+        .collect::<Vec<_>>();
+    let (total, matched, newly_included, newly_excluded) = (
+        videos.len(),
+        videos.iter().filter(|v| v.matched).count(),
+        videos.iter().filter(|v| v.matched && !v.should_download).count(),
+        videos.iter().filter(|v| !v.matched && v.should_download).count(),
+    );
+    let videos = videos
+        .into_iter()
+        .filter(|v| !request.changed_only || v.matched != v.should_download)
+        .collect::<Vec<_>>();
+    let (page, page_size) = if let (Some(page), Some(page_size)) = (request.page, request.page_size) {
+        (page as usize, page_size as usize)
+    } else {
+        (0, 10)
+    };
+    Ok(ApiResponse::ok(RuleDryRunResponse {
+        total,
+        matched,
+        newly_included,
+        newly_excluded,
+        total_count: videos.len(),
+        videos: videos
+            .into_iter()
+            .skip(page.saturating_mul(page_size))
+            .take(page_size)
+            .collect(),
+    }))
+}
+
+/// 立即同步单个视频源，执行结果通过任务状态推送
+pub async fn sync_video_source(
+    Path((source_type, id)): Path<(String, i32)>,
+    Extension(db): Extension<DatabaseConnection>,
+) -> Result<ApiResponse<bool>, ApiError> {
+    let key = (source_type.as_str(), id);
+    let Some(video_source) = get_enabled_video_sources(&db)
+        .await?
+        .into_iter()
+        .find(|video_source| video_source.source_key() == key)
+    else {
+        if get_all_video_sources(&db)
+            .await?
+            .iter()
+            .any(|video_source| video_source.source_key() == key)
+        {
+            return Err(InnerApiError::BadRequest("视频源未启用，无法同步".to_owned()).into());
+        }
+        return Err(InnerApiError::NotFound(id).into());
+    };
+    DownloadTaskManager::get().sync_source(&video_source).await?;
+    Ok(ApiResponse::ok(true))
+}
+
 /// 新增收藏夹订阅
 pub async fn insert_favorite(
     Extension(db): Extension<DatabaseConnection>,
     Extension(bili_client): Extension<Arc<BiliClient>>,
     ValidatedJson(request): ValidatedJson<InsertFavoriteRequest>,
 ) -> Result<ApiResponse<bool>, ApiError> {
-    let credential = &VersionedConfig::get().read().credential;
-    let favorite = FavoriteList::new(bili_client.as_ref(), request.fid.to_string(), credential);
-    let favorite_info = favorite.get_info().await?;
-    favorite::Entity::insert(favorite::ActiveModel {
-        f_id: Set(favorite_info.id),
-        name: Set(favorite_info.title.clone()),
-        path: Set(request.path),
-        enabled: Set(false),
-        ..Default::default()
-    })
-    .exec(&db)
-    .await?;
+    let new_source = NewVideoSource::Favorite { fid: request.fid };
+    insert_video_source(new_source, request.path, false, &bili_client, &db).await?;
     Ok(ApiResponse::ok(true))
 }
 
@@ -479,29 +591,12 @@ pub async fn insert_collection(
     Extension(bili_client): Extension<Arc<BiliClient>>,
     ValidatedJson(request): ValidatedJson<InsertCollectionRequest>,
 ) -> Result<ApiResponse<bool>, ApiError> {
-    let credential = &VersionedConfig::get().read().credential;
-    let collection = Collection::new(
-        bili_client.as_ref(),
-        CollectionItem {
-            sid: request.sid.to_string(),
-            mid: request.mid.to_string(),
-            collection_type: request.collection_type,
-        },
-        credential,
-    );
-    let collection_info = collection.get_info().await?;
-    collection::Entity::insert(collection::ActiveModel {
-        s_id: Set(collection_info.sid),
-        m_id: Set(collection_info.mid),
-        r#type: Set(collection_info.collection_type.into()),
-        name: Set(collection_info.name.clone()),
-        path: Set(request.path),
-        enabled: Set(false),
-        ..Default::default()
-    })
-    .exec(&db)
-    .await?;
-
+    let new_source = NewVideoSource::Collection(CollectionItem {
+        sid: request.sid.to_string(),
+        mid: request.mid.to_string(),
+        collection_type: request.collection_type,
+    });
+    insert_video_source(new_source, request.path, false, &bili_client, &db).await?;
     Ok(ApiResponse::ok(true))
 }
 
@@ -511,18 +606,10 @@ pub async fn insert_submission(
     Extension(bili_client): Extension<Arc<BiliClient>>,
     ValidatedJson(request): ValidatedJson<InsertSubmissionRequest>,
 ) -> Result<ApiResponse<bool>, ApiError> {
-    let credential = &VersionedConfig::get().read().credential;
-    let submission = Submission::new(bili_client.as_ref(), request.upper_id.to_string(), credential);
-    let upper = submission.get_info().await?;
-    submission::Entity::insert(submission::ActiveModel {
-        upper_id: Set(upper.mid.parse()?),
-        upper_name: Set(upper.name),
-        path: Set(request.path),
-        enabled: Set(false),
-        ..Default::default()
-    })
-    .exec(&db)
-    .await?;
+    let new_source = NewVideoSource::Submission {
+        upper_id: request.upper_id,
+    };
+    insert_video_source(new_source, request.path, false, &bili_client, &db).await?;
     Ok(ApiResponse::ok(true))
 }
 
@@ -532,26 +619,12 @@ pub async fn insert_bangumi(
     Extension(bili_client): Extension<Arc<BiliClient>>,
     ValidatedJson(request): ValidatedJson<InsertBangumiRequest>,
 ) -> Result<ApiResponse<bool>, ApiError> {
-    let credential = &VersionedConfig::get().read().credential;
-    let bangumi = BangumiList::new(bili_client.as_ref(), request.season_id, credential);
-    let bangumi_info = bangumi.get_info().await?;
-
-    bangumi::Entity::insert(bangumi::ActiveModel {
-        season_id: Set(bangumi_info.season_id),
-        media_id: Set(bangumi_info.media_id),
-        title: Set(bangumi_info.title.clone()),
-        cover: Set(bangumi_info.cover),
-        evaluate: Set(bangumi_info.evaluate),
-        total: Set(bangumi_info.total),
-        is_finish: Set(bangumi_info.is_finish),
-        season_type: Set(bangumi_info.season_type),
-        selected_section_ids: Set(request.selected_section_ids),
-        path: Set(request.path),
-        enabled: Set(true), // 订阅后自动启用
-        ..Default::default()
-    })
-    .exec(&db)
-    .await?;
+    let new_source = NewVideoSource::Bangumi {
+        season_id: request.season_id,
+        selected_section_ids: request.selected_section_ids,
+    };
+    // 订阅后自动启用
+    insert_video_source(new_source, request.path, true, &bili_client, &db).await?;
     Ok(ApiResponse::ok(true))
 }
 
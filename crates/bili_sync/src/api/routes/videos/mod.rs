@@ -1,5 +1,3 @@
-use std::collections::HashSet;
-
 use anyhow::Result;
 use axum::extract::{Extension, Path, Query};
 use axum::routing::{get, post};
@@ -10,7 +8,7 @@ use sea_orm::{
 };
 
 use crate::api::error::InnerApiError;
-use crate::api::helper::{update_page_download_status, update_video_download_status};
+use crate::api::helper::{reset_videos_status, update_page_download_status, update_video_download_status};
 use crate::api::request::{
     ResetFilteredVideoStatusRequest, ResetVideoStatusRequest, UpdateFilteredVideoStatusRequest,
     UpdateVideoStatusRequest, VideosRequest,
@@ -178,60 +176,7 @@ pub async fn reset_filtered_video_status(
                 .or(video::Column::Bvid.contains(query_word)),
         );
     }
-    let all_videos = query.into_partial_model::<SimpleVideoInfo>().all(&db).await?;
-    let all_pages = page::Entity::find()
-        .filter(page::Column::VideoId.is_in(all_videos.iter().map(|v| v.id)))
-        .into_partial_model::<SimplePageInfo>()
-        .all(&db)
-        .await?;
-    let resetted_pages_info = all_pages
-        .into_iter()
-        .filter_map(|mut page_info| {
-            let mut page_status = PageStatus::from(page_info.download_status);
-            if (request.force && page_status.force_reset_failed()) || page_status.reset_failed() {
-                page_info.download_status = page_status.into();
-                Some(page_info)
-            } else {
-                None
-            }
-        })
-        .collect::<Vec<_>>();
-    let video_ids_with_resetted_pages: HashSet<i32> = resetted_pages_info.iter().map(|page| page.video_id).collect();
-    let resetted_videos_info = all_videos
-        .into_iter()
-        .filter_map(|mut video_info| {
-            let mut video_status = VideoStatus::from(video_info.download_status);
-            let mut video_resetted =
-                (request.force && video_status.force_reset_failed()) || video_status.reset_failed();
-            if video_ids_with_resetted_pages.contains(&video_info.id) {
-                video_status.set(4, 0); // 将"分页下载"重置为 0
-                video_resetted = true;
-            }
-            if video_resetted {
-                video_info.download_status = video_status.into();
-                Some(video_info)
-            } else {
-                None
-            }
-        })
-        .collect::<Vec<_>>();
-    let has_video_updates = !resetted_videos_info.is_empty();
-    let has_page_updates = !resetted_pages_info.is_empty();
-    if has_video_updates || has_page_updates {
-        let txn = db.begin().await?;
-        if has_video_updates {
-            update_video_download_status(&txn, &resetted_videos_info, Some(500)).await?;
-        }
-        if has_page_updates {
-            update_page_download_status(&txn, &resetted_pages_info, Some(500)).await?;
-        }
-        txn.commit().await?;
-    }
-    Ok(ApiResponse::ok(ResetFilteredVideosResponse {
-        resetted: has_video_updates || has_page_updates,
-        resetted_videos_count: resetted_videos_info.len(),
-        resetted_pages_count: resetted_pages_info.len(),
-    }))
+    Ok(ApiResponse::ok(reset_videos_status(query, request.force, &db).await?))
 }
 
 pub async fn update_video_status(
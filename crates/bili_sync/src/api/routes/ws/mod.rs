@@ -20,6 +20,7 @@ use sysinfo::{
     get_current_pid,
 };
 use tokio::sync::mpsc;
+use tokio::time::MissedTickBehavior;
 use tokio::{pin, select};
 use tokio_stream::wrappers::{BroadcastStream, WatchStream};
 use tokio_util::future::FutureExt;
@@ -28,6 +29,7 @@ use uuid::Uuid;
 
 use crate::api::response::SysInfo;
 use crate::task::{DownloadTaskManager, TaskStatus};
+use crate::utils::progress::{self, DownloadProgress};
 
 static WEBSOCKET_HANDLER: LazyLock<WebSocketHandler> = LazyLock::new(WebSocketHandler::new);
 
@@ -46,6 +48,7 @@ enum EventType {
     Logs,
     Tasks,
     SysInfo,
+    Progress,
 }
 
 #[derive(Deserialize)]
@@ -61,6 +64,7 @@ enum ServerEvent {
     Logs(String),
     Tasks(TaskStatus),
     SysInfo(SysInfo),
+    Progress(Vec<DownloadProgress>),
 }
 
 struct WebSocketHandler {
@@ -104,7 +108,7 @@ impl WebSocketHandler {
         // 日志和任务状态的处理本身就是由 stream 驱动的，可以直接为每个 ws 连接维护独立的任务处理器
         // 系统信息是服务端轮询然后推送的，如果单独维护会导致每个连接都独立轮询系统信息，造成不必要的浪费
         // 因此采用了全局的订阅者管理，所有连接共享同一个系统信息轮询任务
-        let (mut log_cancel, mut task_cancel) = (None, None);
+        let (mut log_cancel, mut task_cancel, mut progress_cancel) = (None, None, None);
         while let Some(Ok(msg)) = receiver.next().await {
             let Message::Text(text) = msg else {
                 continue;
@@ -137,6 +141,16 @@ impl WebSocketHandler {
                         cancel.cancel();
                     }
                 }
+                ClientEvent::Subscribe(EventType::Progress) => {
+                    if progress_cancel.is_none() {
+                        progress_cancel = Some(self.new_progress_handler(tx.clone()));
+                    }
+                }
+                ClientEvent::Unsubscribe(EventType::Progress) => {
+                    if let Some(cancel) = progress_cancel.take() {
+                        cancel.cancel();
+                    }
+                }
                 ClientEvent::Subscribe(EventType::SysInfo) => {
                     self.add_sysinfo_subscriber(uuid, tx.clone());
                 }
@@ -152,6 +166,9 @@ impl WebSocketHandler {
         if let Some(cancel) = task_cancel {
             cancel.cancel();
         }
+        if let Some(cancel) = progress_cancel {
+            cancel.cancel();
+        }
         self.remove_sysinfo_subscriber(uuid);
     }
 
@@ -223,6 +240,34 @@ impl WebSocketHandler {
         cancel_token
     }
 
+    /// 创建异步下载进度推送任务，返回任务的取消令牌
+    ///
+    /// 下载进度变化非常频繁，因此按固定间隔采样，仅在进度发生变化时推送
+    fn new_progress_handler(&self, tx: mpsc::Sender<ServerEvent>) -> CancellationToken {
+        let cancel_token = CancellationToken::new();
+        tokio::spawn(
+            async move {
+                let mut interval = tokio::time::interval(Duration::from_secs(1));
+                interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+                let mut last = None;
+                loop {
+                    interval.tick().await;
+                    let current = progress::snapshot();
+                    if last.as_ref() == Some(&current) {
+                        continue;
+                    }
+                    last = Some(current.clone());
+                    if let Err(e) = tx.send(ServerEvent::Progress(current)).await {
+                        error!("Failed to send download progress: {:?}", e);
+                        break;
+                    }
+                }
+            }
+            .with_cancellation_token_owned(cancel_token.clone()),
+        );
+        cancel_token
+    }
+
     /// 创建异步系统信息推送任务，返回任务的取消令牌
     fn new_sysinfo_handler(
         &self,
@@ -0,0 +1,143 @@
+use std::fmt::Write;
+
+use quick_xml::escape::escape;
+
+/// 播放器接口 view_points 中的视频看点（分段章节）
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct Chapter {
+    /// 章节开始时间，单位为秒
+    pub from: f64,
+    /// 章节结束时间，单位为秒
+    pub to: f64,
+    pub content: String,
+}
+
+impl Chapter {
+    #[inline]
+    fn start_ms(&self) -> u64 {
+        (self.from * 1e3) as u64
+    }
+
+    #[inline]
+    fn end_ms(&self) -> u64 {
+        (self.to * 1e3) as u64
+    }
+
+    /// 形如 HH:MM:SS.mmm 的开始时间
+    pub fn start_timestamp(&self) -> String {
+        format_timestamp(self.start_ms())
+    }
+
+    /// 形如 HH:MM:SS.mmm 的结束时间
+    pub fn end_timestamp(&self) -> String {
+        format_timestamp(self.end_ms())
+    }
+}
+
+/// 整理接口返回的看点：去掉无效条目并按开始时间排序
+pub fn normalize_chapters(mut chapters: Vec<Chapter>) -> Vec<Chapter> {
+    chapters.retain(|c| !c.content.trim().is_empty() && c.to > c.from);
+    chapters.sort_by(|a, b| a.from.total_cmp(&b.from));
+    chapters
+}
+
+/// 生成 ffmpeg 可以读取的 ffmetadata，用于将章节封装进视频文件
+pub fn chapters_ffmetadata(chapters: &[Chapter]) -> String {
+    let mut metadata = String::from(";FFMETADATA1\n");
+    for chapter in chapters {
+        let _ = write!(
+            metadata,
+            "\n[CHAPTER]\nTIMEBASE=1/1000\nSTART={}\nEND={}\ntitle={}\n",
+            chapter.start_ms(),
+            chapter.end_ms(),
+            escape_ffmetadata(chapter.content.trim())
+        );
+    }
+    metadata
+}
+
+/// 生成 Matroska 格式的 chapters.xml
+pub fn chapters_xml(chapters: &[Chapter]) -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<!DOCTYPE Chapters SYSTEM \"matroskachapters.dtd\">\n<Chapters>\n    <EditionEntry>\n",
+    );
+    for chapter in chapters {
+        let _ = write!(
+            xml,
+            "        <ChapterAtom>\n            <ChapterTimeStart>{}</ChapterTimeStart>\n            <ChapterTimeEnd>{}</ChapterTimeEnd>\n            <ChapterDisplay>\n                <ChapterString>{}</ChapterString>\n                <ChapterLanguage>chi</ChapterLanguage>\n            </ChapterDisplay>\n        </ChapterAtom>\n",
+            chapter.start_timestamp(),
+            chapter.end_timestamp(),
+            escape(chapter.content.trim())
+        );
+    }
+    xml.push_str("    </EditionEntry>\n</Chapters>\n");
+    xml
+}
+
+/// 将毫秒格式化为 HH:MM:SS.mmm
+fn format_timestamp(ms: u64) -> String {
+    let (second, millisecond) = (ms / 1000, ms % 1000);
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        second / 3600,
+        second % 3600 / 60,
+        second % 60,
+        millisecond
+    )
+}
+
+fn escape_ffmetadata(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '=' | ';' | '#' | '\\' | '\n' => {
+                escaped.push('\\');
+                escaped.push(ch);
+            }
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chapters() -> Vec<Chapter> {
+        serde_json::from_value(serde_json::json!([
+            { "type": 2, "from": 95, "to": 3725, "content": "第二章 = 正文", "imgUrl": "" },
+            { "type": 2, "from": 0, "to": 95, "content": "开场 & <介绍>", "imgUrl": "" },
+            { "type": 2, "from": 3725, "to": 3725, "content": "无效", "imgUrl": "" }
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_chapters_format() {
+        let chapters = normalize_chapters(chapters());
+        assert_eq!(chapters.len(), 2);
+        assert_eq!(chapters[0].content, "开场 & <介绍>");
+        assert_eq!(
+            chapters_ffmetadata(&chapters),
+            ";FFMETADATA1
+
+[CHAPTER]
+TIMEBASE=1/1000
+START=0
+END=95000
+title=开场 & <介绍>
+
+[CHAPTER]
+TIMEBASE=1/1000
+START=95000
+END=3725000
+title=第二章 \\= 正文
+"
+        );
+        let xml = chapters_xml(&chapters);
+        assert!(xml.contains("<ChapterTimeStart>00:01:35.000</ChapterTimeStart>"));
+        assert!(xml.contains("<ChapterTimeEnd>01:02:05.000</ChapterTimeEnd>"));
+        assert!(xml.contains("<ChapterString>开场 &amp; &lt;介绍&gt;</ChapterString>"));
+    }
+}
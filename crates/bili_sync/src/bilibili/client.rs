@@ -8,7 +8,8 @@ use ua_generator::ua;
 
 use crate::bilibili::Credential;
 use crate::bilibili::credential::WbiImg;
-use crate::config::{RateLimit, VersionedCache};
+use crate::config::{RateLimit, VersionedCache, VersionedConfig};
+use crate::utils::risk_control;
 
 // 一个对 reqwest::Client 的简单封装，用于 Bilibili 请求
 #[derive(Clone)]
@@ -65,6 +66,15 @@ enum Limiter {
     Snapshot(Arc<Option<RateLimiter>>),
 }
 
+fn new_rate_limiter(RateLimit { limit, duration }: &RateLimit) -> RateLimiter {
+    RateLimiter::builder()
+        .initial(*limit)
+        .refill(*limit)
+        .max(*limit)
+        .interval(Duration::from_millis(*duration))
+        .build()
+}
+
 pub struct BiliClient {
     pub client: Client,
     limiter: Limiter,
@@ -74,34 +84,35 @@ impl BiliClient {
     pub fn new() -> Self {
         let client = Client::new();
         let limiter = Limiter::Latest(
-            VersionedCache::new(|config| {
-                Ok(config
-                    .concurrent_limit
-                    .rate_limit
-                    .as_ref()
-                    .map(|RateLimit { limit, duration }| {
-                        RateLimiter::builder()
-                            .initial(*limit)
-                            .refill(*limit)
-                            .max(*limit)
-                            .interval(Duration::from_millis(*duration))
-                            .build()
-                    }))
-            })
-            .expect("failed to create rate limiter"),
+            VersionedCache::new(|config| Ok(config.concurrent_limit.rate_limit.as_ref().map(new_rate_limiter)))
+                .expect("failed to create rate limiter"),
         );
         Self { client, limiter }
     }
 
     /// 获取当前 BiliClient 的快照，快照中的限流器固定不变
+    ///
+    /// 近期触发过风控时，快照会使用按照退避等级放慢后的限流器
     pub fn snapshot(&self) -> Result<Self> {
         let Limiter::Latest(inner) = &self.limiter else {
             // 语法上没问题，但语义上不允许对快照进行快照
             bail!("cannot snapshot a snapshot BiliClient");
         };
+        let slowed =
+            risk_control::slowed_rate_limit(VersionedConfig::get().read().concurrent_limit.rate_limit.as_ref());
+        let limiter = match slowed {
+            Some(rate_limit) => {
+                warn!(
+                    "近期触发过风控，本轮请求频率降低为每 {}ms {} 次",
+                    rate_limit.duration, rate_limit.limit
+                );
+                Arc::new(Some(new_rate_limiter(&rate_limit)))
+            }
+            None => inner.snapshot(),
+        };
         Ok(Self {
             client: self.client.clone(),
-            limiter: Limiter::Snapshot(inner.snapshot()),
+            limiter: Limiter::Snapshot(limiter),
         })
     }
 
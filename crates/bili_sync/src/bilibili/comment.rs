@@ -0,0 +1,195 @@
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommentOption {
+    /// 是否下载评论
+    pub enabled: bool,
+    /// 最多拉取的一级评论页数，每页 20 条
+    pub max_pages: usize,
+    /// 每条一级评论最多保留的楼中楼回复数
+    pub max_sub_replies: usize,
+}
+
+impl Default for CommentOption {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_pages: 5,
+            max_sub_replies: 10,
+        }
+    }
+}
+
+/// 评论接口返回的原始结构
+#[derive(Debug, Deserialize)]
+pub struct ReplyItem {
+    pub rpid: i64,
+    pub mid: i64,
+    pub ctime: i64,
+    pub like: i64,
+    #[serde(default)]
+    pub rcount: i64,
+    pub member: ReplyMember,
+    pub content: ReplyContent,
+    #[serde(default)]
+    pub replies: Option<Vec<ReplyItem>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReplyMember {
+    pub uname: String,
+    pub avatar: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReplyContent {
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Comment {
+    pub rpid: i64,
+    pub mid: i64,
+    pub uname: String,
+    pub avatar: String,
+    pub message: String,
+    pub like: i64,
+    pub ctime: i64,
+    /// 接口返回的回复总数，可能大于 replies 中实际保存的数量
+    pub reply_count: i64,
+    pub replies: Vec<Comment>,
+}
+
+impl From<ReplyItem> for Comment {
+    fn from(item: ReplyItem) -> Self {
+        Self {
+            rpid: item.rpid,
+            mid: item.mid,
+            uname: item.member.uname,
+            avatar: item.member.avatar,
+            message: item.content.message,
+            like: item.like,
+            ctime: item.ctime,
+            reply_count: item.rcount,
+            replies: item
+                .replies
+                .unwrap_or_default()
+                .into_iter()
+                .map(Comment::from)
+                .collect(),
+        }
+    }
+}
+
+/// 将评论渲染为可以直接在浏览器中阅读的 html
+pub fn comments_html(title: &str, comments: &[Comment]) -> String {
+    let mut html = String::new();
+    let title = escape_html(title);
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title} - 评论</title>\n\
+        <style>\
+        body{{font-family:sans-serif;max-width:960px;margin:0 auto;padding:16px;color:#222}}\
+        ul{{list-style:none;padding-left:0}}\
+        li{{padding:8px 0;border-bottom:1px solid #eee}}\
+        .replies{{margin-left:32px;font-size:0.95em}}\
+        .replies li{{border-bottom:none}}\
+        .meta{{color:#888;font-size:0.85em}}\
+        .uname{{color:#fb7299;font-weight:bold;margin-right:8px}}\
+        .message{{white-space:pre-wrap;margin-top:4px}}\
+        </style>\n</head>\n<body>\n<h1>{title}</h1>\n<p class=\"meta\">共 {count} 条评论</p>\n<ul class=\"comments\">\n",
+        count = comments.len(),
+    );
+    for comment in comments {
+        write_comment(&mut html, comment);
+    }
+    html.push_str("</ul>\n</body>\n</html>\n");
+    html
+}
+
+fn write_comment(html: &mut String, comment: &Comment) {
+    let time = chrono::DateTime::from_timestamp(comment.ctime, 0)
+        .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_default();
+    let _ = write!(
+        html,
+        "<li id=\"{rpid}\">\n<div class=\"meta\"><span class=\"uname\">{uname}</span>{time} · 点赞 {like}</div>\n\
+        <div class=\"message\">{message}</div>\n",
+        rpid = comment.rpid,
+        uname = escape_html(&comment.uname),
+        like = comment.like,
+        message = escape_html(&comment.message),
+    );
+    if !comment.replies.is_empty() {
+        html.push_str("<ul class=\"replies\">\n");
+        for reply in &comment.replies {
+            write_comment(html, reply);
+        }
+        if comment.reply_count > comment.replies.len() as i64 {
+            let _ = writeln!(
+                html,
+                "<li class=\"meta\">…… 共 {} 条回复，仅保存了 {} 条</li>",
+                comment.reply_count,
+                comment.replies.len()
+            );
+        }
+        html.push_str("</ul>\n");
+    }
+    html.push_str("</li>\n");
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_render_comments() {
+        let item: ReplyItem = serde_json::from_value(serde_json::json!({
+            "rpid": 1,
+            "mid": 100,
+            "ctime": 1700000000,
+            "like": 42,
+            "rcount": 5,
+            "member": { "uname": "测试用户", "avatar": "https://i0.hdslb.com/face.jpg" },
+            "content": { "message": "<script>alert(1)</script> & 好耶" },
+            "replies": [{
+                "rpid": 2,
+                "mid": 101,
+                "ctime": 1700000100,
+                "like": 1,
+                "member": { "uname": "回复者", "avatar": "" },
+                "content": { "message": "回复 @测试用户 :+1" },
+                "replies": null
+            }]
+        }))
+        .unwrap();
+        let comment = Comment::from(item);
+        assert_eq!(comment.uname, "测试用户");
+        assert_eq!(comment.reply_count, 5);
+        assert_eq!(comment.replies.len(), 1);
+        assert!(comment.replies[0].replies.is_empty());
+        let html = comments_html("标题 <1>", &[comment]);
+        assert!(html.contains("<title>标题 &lt;1&gt; - 评论</title>"));
+        assert!(html.contains("&lt;script&gt;alert(1)&lt;/script&gt; &amp; 好耶"));
+        assert!(html.contains("回复 @测试用户 :+1"));
+        assert!(html.contains("共 5 条回复，仅保存了 1 条"));
+        assert!(!html.contains("<script>"));
+    }
+}
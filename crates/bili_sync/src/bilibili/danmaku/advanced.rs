@@ -0,0 +1,171 @@
+//! 高级弹幕（mode 7）的解析
+//!
+//! 弹幕正文是一个 JSON 数组，各项依次为：
+//! `[x, y, "起始透明度-结束透明度", 持续时间, 文本, z 轴旋转, y 轴旋转, 终点 x, 终点 y, 移动耗时, 移动延迟, 是否描边, 字体, 是否线性]`
+//!
+//! 数值既可能是数字也可能是字符串，坐标为小数（且不大于 1）时表示相对播放器的比例，否则为原播放器上的像素值
+use anyhow::{Context, Result, ensure};
+use serde_json::Value;
+
+/// 高级弹幕坐标所基于的原播放器尺寸
+pub const PLAYER_SIZE: (f64, f64) = (672.0, 438.0);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdvancedDanmu {
+    /// 起点，已换算为原播放器上的像素坐标
+    pub start: (f64, f64),
+    /// 终点，不存在时弹幕固定在起点
+    pub end: Option<(f64, f64)>,
+    /// 起止透明度，0 为完全透明，1 为完全不透明
+    pub alpha: (f64, f64),
+    /// 持续时间（秒）
+    pub duration: f64,
+    pub text: String,
+    /// 绕 z 轴旋转的角度
+    pub rotate_z: f64,
+    /// 绕 y 轴旋转的角度
+    pub rotate_y: f64,
+    /// 移动耗时（毫秒）
+    pub move_duration: u32,
+    /// 移动开始前的延迟（毫秒）
+    pub move_delay: u32,
+    pub outline: bool,
+    pub font: Option<String>,
+}
+
+impl AdvancedDanmu {
+    pub fn parse(content: &str) -> Result<Self> {
+        let payload: Vec<Value> = serde_json::from_str(content).context("invalid advanced danmu payload")?;
+        ensure!(
+            payload.len() >= 5,
+            "advanced danmu payload too short: {}",
+            payload.len()
+        );
+        let start = (
+            coordinate(&payload[0], PLAYER_SIZE.0).context("invalid start x")?,
+            coordinate(&payload[1], PLAYER_SIZE.1).context("invalid start y")?,
+        );
+        let alpha = payload[2]
+            .as_str()
+            .and_then(|s| s.split_once('-'))
+            .and_then(|(from, to)| Some((from.trim().parse::<f64>().ok()?, to.trim().parse::<f64>().ok()?)))
+            .map(|(from, to)| (from.clamp(0.0, 1.0), to.clamp(0.0, 1.0)))
+            .unwrap_or((1.0, 1.0));
+        let duration = number(&payload[3]).context("invalid duration")?;
+        ensure!(duration > 0.0, "invalid duration: {}", duration);
+        let text = match &payload[4] {
+            Value::String(s) => s.replace("/n", "\n"),
+            other => other.to_string(),
+        };
+        let rotate_z = payload.get(5).and_then(number).unwrap_or_default();
+        let rotate_y = payload.get(6).and_then(number).unwrap_or_default();
+        let end = match (payload.get(7), payload.get(8)) {
+            (Some(x), Some(y)) => Some((
+                coordinate(x, PLAYER_SIZE.0).context("invalid end x")?,
+                coordinate(y, PLAYER_SIZE.1).context("invalid end y")?,
+            )),
+            _ => None,
+        };
+        // 未指定移动耗时的情况下，移动贯穿整个弹幕的持续时间
+        let move_duration = payload
+            .get(9)
+            .and_then(number)
+            .map(|v| v.max(0.0) as u32)
+            .unwrap_or((duration * 1000.0) as u32);
+        let move_delay = payload
+            .get(10)
+            .and_then(number)
+            .map(|v| v.max(0.0) as u32)
+            .unwrap_or_default();
+        let outline = match payload.get(11) {
+            Some(Value::Bool(b)) => *b,
+            Some(v) => number(v).is_none_or(|v| v != 0.0),
+            None => true,
+        };
+        let font = payload
+            .get(12)
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_owned);
+        Ok(Self {
+            start,
+            end: end.filter(|end| *end != start),
+            alpha,
+            duration,
+            text,
+            rotate_z,
+            rotate_y,
+            move_duration,
+            move_delay,
+            outline,
+            font,
+        })
+    }
+}
+
+/// 读取数字，兼容字符串形式的数字
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// 读取坐标，小数形式且不大于 1 时视为比例，乘以对应的播放器尺寸
+fn coordinate(value: &Value, size: f64) -> Option<f64> {
+    let is_fraction = match value {
+        Value::Number(n) => n.is_f64(),
+        Value::String(s) => s.contains('.'),
+        _ => false,
+    };
+    let v = number(value)?;
+    Some(if is_fraction && v <= 1.0 { v * size } else { v })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_fraction_payload() {
+        let danmu = AdvancedDanmu::parse(
+            r#"["0.5","0.25","1-1","4.5","居中/n第二行",0,0,"0.5","0.25",500,0,true,"微软雅黑",1]"#,
+        )
+        .unwrap();
+        assert_eq!(danmu.start, (336.0, 109.5));
+        // 终点与起点一致，视为固定位置
+        assert_eq!(danmu.end, None);
+        assert_eq!(danmu.alpha, (1.0, 1.0));
+        assert_eq!(danmu.duration, 4.5);
+        assert_eq!(danmu.text, "居中\n第二行");
+        assert!(danmu.outline);
+        assert_eq!(danmu.font.as_deref(), Some("微软雅黑"));
+    }
+
+    #[test]
+    fn test_parse_pixel_payload() {
+        let danmu = AdvancedDanmu::parse(r#"[100,200,"1-0",3,"卡拉OK","30","-15",300,"200",1000,500,0,"",0]"#).unwrap();
+        assert_eq!(danmu.start, (100.0, 200.0));
+        assert_eq!(danmu.end, Some((300.0, 200.0)));
+        assert_eq!(danmu.alpha, (1.0, 0.0));
+        assert_eq!(danmu.duration, 3.0);
+        assert_eq!((danmu.rotate_z, danmu.rotate_y), (30.0, -15.0));
+        assert_eq!((danmu.move_duration, danmu.move_delay), (1000, 500));
+        assert!(!danmu.outline);
+        assert_eq!(danmu.font, None);
+    }
+
+    #[test]
+    fn test_parse_short_payload() {
+        let danmu = AdvancedDanmu::parse(r#"[10,20,"0.8-0.8","2","静止"]"#).unwrap();
+        assert_eq!(danmu.start, (10.0, 20.0));
+        assert_eq!(danmu.end, None);
+        assert_eq!(danmu.alpha, (0.8, 0.8));
+        assert_eq!(danmu.move_duration, 2000);
+        assert!(danmu.outline);
+        assert!(AdvancedDanmu::parse(r#"[10,20,"1-1"]"#).is_err());
+        assert!(AdvancedDanmu::parse("普通弹幕").is_err());
+    }
+}
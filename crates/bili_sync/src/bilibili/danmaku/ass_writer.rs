@@ -25,15 +25,51 @@ impl fmt::Display for TimePoint {
 
 struct AssEffect {
     effect: DrawEffect,
+    /// 弹幕持续的时间，用于计算淡入淡出
+    duration: f64,
 }
 impl fmt::Display for AssEffect {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self.effect {
+        match &self.effect {
             DrawEffect::Move { start, end } => {
                 let (x0, y0) = start;
                 let (x1, y1) = end;
                 write!(f, "\\move({x0}, {y0}, {x1}, {y1})")
             }
+            DrawEffect::Positioned(effect) => {
+                let (x0, y0) = effect.start;
+                match effect.movement {
+                    Some(((x1, y1), t1, t2)) => write!(f, "\\move({x0}, {y0}, {x1}, {y1}, {t1}, {t2})")?,
+                    None => write!(f, "\\pos({x0}, {y0})")?,
+                }
+                write!(f, "\\fs{}", effect.font_size)?;
+                if let Some(font) = &effect.font {
+                    write!(f, "\\fn{}", sanitize_font(font))?;
+                }
+                if !effect.outline {
+                    write!(f, "\\bord0")?;
+                }
+                let (z, y) = effect.rotate;
+                if z != 0 {
+                    write!(f, "\\frz{z}")?;
+                }
+                if y != 0 {
+                    write!(f, "\\fry{y}")?;
+                }
+                // ASS 中的 alpha 表示透明度，00 为不透明
+                let (a0, a1) = (
+                    ((1.0 - effect.alpha.0) * 255.0).round() as u8,
+                    ((1.0 - effect.alpha.1) * 255.0).round() as u8,
+                );
+                let duration_ms = (self.duration * 1000.0) as u32;
+                match (a0, a1) {
+                    (0, 0) => Ok(()),
+                    (a0, a1) if a0 == a1 => write!(f, "\\alpha&H{a0:02x}&"),
+                    (255, 0) => write!(f, "\\fad({duration_ms}, 0)"),
+                    (0, 255) => write!(f, "\\fad(0, {duration_ms})"),
+                    (a0, a1) => write!(f, "\\alpha&H{a0:02x}&\\t(\\alpha&H{a1:02x}&)"),
+                }
+            }
         }
     }
 }
@@ -74,6 +110,16 @@ impl DanmakuOption {
                 bold = self.bold as u8,
                 outline = self.outline,
             ),
+            // 高级弹幕自行指定透明度、字号等属性
+            format!(
+                "Style: Advanced,{font},{font_size},&H00FFFFFF,&H00FFFFFF,&H00000000,&H00000000,\
+                {bold}, 0, 0, 0, 100, 100, 0.00, 0.00, 1, \
+                {outline}, 0, 7, 0, 0, 0, 1",
+                font = self.font,
+                font_size = self.font_size,
+                bold = self.bold as u8,
+                outline = self.outline,
+            ),
         ]
     }
 }
@@ -164,7 +210,8 @@ impl<'a, W: AsyncWrite> AssWriter<'a, W> {
                     },
                     style = drawable.style_name,
                     effect = AssEffect {
-                        effect: drawable.effect
+                        effect: drawable.effect,
+                        duration: drawable.duration,
                     },
                     b = drawable.danmu.rgb.2,
                     g = drawable.danmu.rgb.1,
@@ -193,9 +240,19 @@ fn escape_text(text: &'_ str) -> Cow<'_, str> {
     }
 }
 
+/// 字体名称位于样式覆盖代码块中，ASS 无法转义 `\`、`{` 与 `}`，直接移除以免提前结束代码块
+fn sanitize_font(font: &'_ str) -> Cow<'_, str> {
+    if font.contains(['\\', '{', '}']) {
+        Cow::from(font.replace(['\\', '{', '}'], ""))
+    } else {
+        Cow::from(font)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::bilibili::danmaku::PositionedEffect;
 
     #[test]
     fn time_point_fmt() {
@@ -225,6 +282,53 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_positioned_effect_fmt() {
+        let effect = |movement, alpha, rotate, font: Option<&str>, outline| AssEffect {
+            effect: DrawEffect::Positioned(PositionedEffect {
+                start: (100, 200),
+                movement,
+                alpha,
+                rotate,
+                font_size: 41,
+                font: font.map(str::to_owned),
+                outline,
+            }),
+            duration: 3.0,
+        };
+        assert_eq!(
+            effect(None, (1.0, 1.0), (0, 0), None, true).to_string(),
+            r"\pos(100, 200)\fs41"
+        );
+        assert_eq!(
+            effect(
+                Some(((300, 200), 500, 1500)),
+                (1.0, 0.0),
+                (-45, 30),
+                Some("黑体"),
+                false
+            )
+            .to_string(),
+            r"\move(100, 200, 300, 200, 500, 1500)\fs41\fn黑体\bord0\frz-45\fry30\fad(0, 3000)"
+        );
+        assert_eq!(
+            effect(None, (0.0, 1.0), (0, 0), None, true).to_string(),
+            r"\pos(100, 200)\fs41\fad(3000, 0)"
+        );
+        assert_eq!(
+            effect(None, (0.6, 0.6), (0, 0), None, true).to_string(),
+            r"\pos(100, 200)\fs41\alpha&H66&"
+        );
+        assert_eq!(
+            effect(None, (1.0, 0.5), (0, 0), None, true).to_string(),
+            r"\pos(100, 200)\fs41\alpha&H00&\t(\alpha&H80&)"
+        );
+        assert_eq!(
+            effect(None, (1.0, 1.0), (0, 0), Some(r"黑体}\pos(0, 0){"), true).to_string(),
+            r"\pos(100, 200)\fs41\fn黑体pos(0, 0)"
+        );
+    }
+
     #[test]
     fn test_escape_text() {
         assert_eq!(
@@ -0,0 +1,79 @@
+//! 原始弹幕的本地缓存，用于在多次抓取之间合并弹幕
+use std::collections::HashSet;
+use std::path::Path;
+
+use anyhow::Result;
+use prost::Message;
+use tokio::fs;
+
+use crate::bilibili::danmaku::{DanmakuElem, DmSegMobileReply};
+
+pub struct DanmakuCache;
+
+impl DanmakuCache {
+    /// 读取缓存的原始弹幕，缓存不存在时返回空列表
+    pub async fn load(path: &Path) -> Result<Vec<DanmakuElem>> {
+        match fs::read(path).await {
+            Ok(bytes) => Ok(DmSegMobileReply::decode(bytes.as_slice())?.elems),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub async fn save(path: &Path, elems: Vec<DanmakuElem>) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        fs::write(path, DmSegMobileReply { elems }.encode_to_vec()).await?;
+        Ok(())
+    }
+
+    /// 合并缓存与新抓取的弹幕，按弹幕 id 去重，结果按出现时间排序
+    pub fn merge(cached: Vec<DanmakuElem>, fetched: Vec<DanmakuElem>) -> Vec<DanmakuElem> {
+        let mut seen = HashSet::with_capacity(cached.len() + fetched.len());
+        let mut merged: Vec<DanmakuElem> = cached
+            .into_iter()
+            .chain(fetched)
+            .filter(|elem| seen.insert(elem.id))
+            .collect();
+        merged.sort_by_key(|elem| elem.progress);
+        merged
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn elem(id: i64, progress: i32, content: &str) -> DanmakuElem {
+        DanmakuElem {
+            id,
+            progress,
+            content: content.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_merge_danmaku() {
+        let cached = vec![elem(1, 1000, "第一条"), elem(2, 3000, "被删除的弹幕")];
+        let fetched = vec![elem(3, 2000, "新弹幕"), elem(1, 1000, "第一条")];
+        let merged = DanmakuCache::merge(cached, fetched);
+        assert_eq!(
+            merged.iter().map(|e| (e.id, e.content.as_str())).collect::<Vec<_>>(),
+            vec![(1, "第一条"), (3, "新弹幕"), (2, "被删除的弹幕")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cache_roundtrip() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("bili-sync-danmaku-cache-{}", std::process::id()));
+        let path = dir.join("test.danmaku.pb");
+        assert!(DanmakuCache::load(&path).await?.is_empty());
+        DanmakuCache::save(&path, vec![elem(1, 1000, "第一条"), elem(2, 2000, "第二条")]).await?;
+        let loaded = DanmakuCache::load(&path).await?;
+        assert_eq!(loaded.iter().map(|e| e.id).collect::<Vec<_>>(), vec![1, 2]);
+        fs::remove_dir_all(&dir).await?;
+        Ok(())
+    }
+}
@@ -6,9 +6,10 @@ use float_ord::FloatOrd;
 use lane::Lane;
 
 use crate::bilibili::PageInfo;
+use crate::bilibili::danmaku::advanced::{AdvancedDanmu, PLAYER_SIZE};
 use crate::bilibili::danmaku::canvas::lane::Collision;
 use crate::bilibili::danmaku::danmu::DanmuType;
-use crate::bilibili::danmaku::{Danmu, DrawEffect, Drawable};
+use crate::bilibili::danmaku::{Danmu, DrawEffect, Drawable, PositionedEffect};
 
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
 pub struct DanmakuOption {
@@ -116,9 +117,52 @@ impl<'a> Canvas<'a> {
                 danmu.r#type = DanmuType::Float;
                 Ok(self.draw_float(danmu))
             }
+            DanmuType::Advanced => Ok(self.draw_advanced(danmu)),
+            DanmuType::Code => Ok(None),
         }
     }
 
+    fn draw_advanced(&self, mut danmu: Danmu) -> Option<Drawable> {
+        let advanced = match AdvancedDanmu::parse(&danmu.content) {
+            Ok(advanced) => advanced,
+            Err(e) => {
+                debug!("skipping advanced danmu: {}, {:#}", danmu.content, e);
+                return None;
+            }
+        };
+        // 高级弹幕的坐标基于原播放器，等比缩放到画布中并居中
+        let (width, height) = (self.config.width as f64, self.config.height as f64);
+        let scale = (width / PLAYER_SIZE.0).min(height / PLAYER_SIZE.1);
+        let (offset_x, offset_y) = (
+            (width - PLAYER_SIZE.0 * scale) / 2.0,
+            (height - PLAYER_SIZE.1 * scale) / 2.0,
+        );
+        let project = |(x, y): (f64, f64)| ((x * scale + offset_x) as i32, (y * scale + offset_y) as i32);
+        let effect = PositionedEffect {
+            start: project(advanced.start),
+            movement: advanced.end.map(|end| {
+                (
+                    project(end),
+                    advanced.move_delay,
+                    advanced.move_delay + advanced.move_duration,
+                )
+            }),
+            alpha: advanced.alpha,
+            // flash 中的旋转方向与 ASS 相反
+            rotate: (-advanced.rotate_z as i32, advanced.rotate_y as i32),
+            font_size: (danmu.fontsize as f64 * scale) as u32,
+            font: advanced.font,
+            outline: advanced.outline,
+        };
+        danmu.content = advanced.text;
+        Some(Drawable::new(
+            danmu,
+            advanced.duration,
+            "Advanced",
+            DrawEffect::Positioned(effect),
+        ))
+    }
+
     fn draw_float(&mut self, mut danmu: Danmu) -> Option<Drawable> {
         let mut collisions = Vec::with_capacity(self.float_lanes.len());
         for (idx, lane) in self.float_lanes.iter_mut().enumerate() {
@@ -170,3 +214,38 @@ impl<'a> Canvas<'a> {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_draw_advanced() {
+        let option = DanmakuOption::default();
+        let config = CanvasConfig {
+            width: 1280,
+            height: 720,
+            danmaku_option: &option,
+        };
+        let canvas = config.canvas();
+        let danmu = Danmu {
+            timeline_s: 10.0,
+            content: r#"[0,0,"1-0","3","卡拉OK",45,0,"1.0","1.0",1000,500]"#.to_string(),
+            r#type: DanmuType::Advanced,
+            fontsize: 25,
+            rgb: (255, 255, 255),
+        };
+        let drawable = canvas.draw_advanced(danmu).unwrap();
+        assert_eq!(drawable.danmu.content, "卡拉OK");
+        assert_eq!(drawable.duration, 3.0);
+        assert_eq!(drawable.style_name, "Advanced");
+        let DrawEffect::Positioned(effect) = drawable.effect else {
+            panic!("advanced danmu should be positioned");
+        };
+        // 按 720 / 438 等比缩放，水平方向居中
+        assert_eq!(effect.start, (87, 0));
+        assert_eq!(effect.movement, Some(((1192, 720), 500, 1500)));
+        assert_eq!(effect.rotate, (-45, 0));
+        assert_eq!(effect.font_size, 41);
+    }
+}
@@ -10,6 +10,10 @@ pub enum DanmuType {
     Top,
     Bottom,
     Reverse,
+    /// 高级弹幕，正文为描述位置、动画的 JSON
+    Advanced,
+    /// 代码弹幕，需要执行脚本，无法转换为 ASS
+    Code,
 }
 
 impl DanmuType {
@@ -19,7 +23,9 @@ impl DanmuType {
             4 => DanmuType::Bottom,
             5 => DanmuType::Top,
             6 => DanmuType::Reverse,
-            // 高级弹幕、代码弹幕等，不支持，这里 return error，外面 unwrap_or_default 当成 Float 处理
+            7 => DanmuType::Advanced,
+            8 => DanmuType::Code,
+            // BAS 弹幕等，不支持，这里 return error，外面 unwrap_or_default 当成 Float 处理
             _ => bail!("UnSupported danmu type"),
         })
     }
@@ -24,5 +24,23 @@ impl Drawable {
 }
 
 pub enum DrawEffect {
-    Move { start: (i32, i32), end: (i32, i32) },
+    Move {
+        start: (i32, i32),
+        end: (i32, i32),
+    },
+    /// 高级弹幕，固定在某个位置或在指定时间段内移动
+    Positioned(PositionedEffect),
+}
+
+pub struct PositionedEffect {
+    pub start: (i32, i32),
+    /// 移动的终点与起止时间（毫秒），为 None 时固定在起点
+    pub movement: Option<((i32, i32), u32, u32)>,
+    /// 起止透明度，0 为完全透明，1 为完全不透明
+    pub alpha: (f64, f64),
+    /// 绕 z 轴、y 轴旋转的角度
+    pub rotate: (i32, i32),
+    pub font_size: u32,
+    pub font: Option<String>,
+    pub outline: bool,
 }
@@ -1,4 +1,6 @@
+mod advanced;
 mod ass_writer;
+mod cache;
 mod canvas;
 mod danmu;
 mod drawable;
@@ -6,8 +8,9 @@ mod model;
 mod writer;
 
 pub use ass_writer::AssWriter;
+pub use cache::DanmakuCache;
 pub use canvas::DanmakuOption;
 pub use danmu::Danmu;
-pub use drawable::{DrawEffect, Drawable};
+pub use drawable::{DrawEffect, Drawable, PositionedEffect};
 pub use model::{DanmakuElem, DmSegMobileReply};
 pub use writer::DanmakuWriter;
@@ -5,10 +5,12 @@ pub use analyzer::{BestStream, FilterOption};
 use anyhow::{Result, bail, ensure};
 use arc_swap::ArcSwapOption;
 pub use bangumi_list::BangumiList;
+pub use chapter::{Chapter, chapters_ffmetadata, chapters_xml};
 use chrono::serde::ts_seconds;
 use chrono::{DateTime, Utc};
 pub use client::{BiliClient, Client};
 pub use collection::{Collection, CollectionItem, CollectionType};
+pub use comment::{CommentOption, comments_html};
 pub use credential::Credential;
 pub use danmaku::DanmakuOption;
 pub use dynamic::Dynamic;
@@ -19,13 +21,15 @@ pub use me::{BangumiType, Me};
 use once_cell::sync::Lazy;
 use reqwest::RequestBuilder;
 pub use submission::Submission;
-pub use video::{Dimension, PageInfo, Video};
+pub use video::{Dimension, PageInfo, Video, VideoStat, bvid_to_aid};
 pub use watch_later::WatchLater;
 
 mod analyzer;
 pub mod bangumi_list;
+mod chapter;
 mod client;
 mod collection;
+mod comment;
 mod credential;
 mod danmaku;
 mod dynamic;
@@ -126,6 +130,11 @@ pub enum VideoInfo {
         redirect_url: Option<String>,
         pages: Vec<PageInfo>,
         state: i32,
+        /// 分区名称
+        #[serde(default)]
+        tname: String,
+        #[serde(default)]
+        stat: VideoStat,
     },
     /// 从收藏夹接口获取的视频信息
     Favorite {
@@ -1,3 +1,6 @@
+use std::collections::HashSet;
+use std::path::Path;
+
 use anyhow::{Context, Result, ensure};
 use futures::TryStreamExt;
 use futures::stream::FuturesUnordered;
@@ -5,8 +8,10 @@ use prost::Message;
 use reqwest::Method;
 
 use crate::bilibili::analyzer::PageAnalyzer;
+use crate::bilibili::chapter::{Chapter, normalize_chapters};
 use crate::bilibili::client::BiliClient;
-use crate::bilibili::danmaku::{DanmakuElem, DanmakuWriter, DmSegMobileReply};
+use crate::bilibili::comment::{Comment, CommentOption, ReplyItem};
+use crate::bilibili::danmaku::{DanmakuCache, DanmakuElem, DanmakuWriter, DmSegMobileReply};
 use crate::bilibili::subtitle::{SubTitle, SubTitleBody, SubTitleInfo, SubTitlesInfo};
 use crate::bilibili::{Credential, MIXIN_KEY, Validate, VideoInfo, WbiSign};
 
@@ -27,6 +32,15 @@ pub struct PageInfo {
     pub dimension: Option<Dimension>,
 }
 
+/// 视频详情接口中的统计信息
+#[derive(Debug, serde::Deserialize, Default)]
+pub struct VideoStat {
+    pub view: i64,
+    pub like: i64,
+    pub coin: i64,
+    pub favorite: i64,
+}
+
 #[derive(Debug, serde::Deserialize, Default)]
 pub struct Dimension {
     pub width: u32,
@@ -109,6 +123,19 @@ impl<'a> Video<'a> {
     }
 
     pub async fn get_danmaku_writer(&self, page: &'a PageInfo) -> Result<DanmakuWriter<'a>> {
+        let result = self.get_danmaku(page).await?;
+        Ok(DanmakuWriter::new(page, result.into_iter().map(|x| x.into()).collect()))
+    }
+
+    /// 获取弹幕并与缓存的原始弹幕合并，合并后的结果会写回缓存
+    pub async fn get_merged_danmaku_writer(&self, page: &'a PageInfo, cache_path: &Path) -> Result<DanmakuWriter<'a>> {
+        let (cached, fetched) = tokio::try_join!(DanmakuCache::load(cache_path), self.get_danmaku(page))?;
+        let merged = DanmakuCache::merge(cached, fetched);
+        DanmakuCache::save(cache_path, merged.clone()).await?;
+        Ok(DanmakuWriter::new(page, merged.into_iter().map(|x| x.into()).collect()))
+    }
+
+    async fn get_danmaku(&self, page: &PageInfo) -> Result<Vec<DanmakuElem>> {
         let tasks = FuturesUnordered::new();
         for i in 1..=page.duration.div_ceil(360) {
             tasks.push(self.get_danmaku_segment(page, i as i64));
@@ -116,7 +143,7 @@ impl<'a> Video<'a> {
         let result: Vec<Vec<DanmakuElem>> = tasks.try_collect().await?;
         let mut result: Vec<DanmakuElem> = result.into_iter().flatten().collect();
         result.sort_by_key(|d| d.progress);
-        Ok(DanmakuWriter::new(page, result.into_iter().map(|x| x.into()).collect()))
+        Ok(result)
     }
 
     async fn get_danmaku_segment(&self, page: &PageInfo, segment_idx: i64) -> Result<Vec<DanmakuElem>> {
@@ -171,9 +198,9 @@ impl<'a> Video<'a> {
         Ok(PageAnalyzer::new(res["data"].take()))
     }
 
-    pub async fn get_subtitles(&self, page: &PageInfo) -> Result<Vec<SubTitle>> {
-        let mut res = self
-            .client
+    /// 播放器信息接口，包含字幕与视频看点等信息
+    async fn get_player_info(&self, page: &PageInfo) -> Result<serde_json::Value> {
+        self.client
             .request(Method::GET, "https://api.bilibili.com/x/player/wbi/v2", self.credential)
             .await
             .query(&[("bvid", self.bvid.as_str())])
@@ -184,7 +211,11 @@ impl<'a> Video<'a> {
             .error_for_status()?
             .json::<serde_json::Value>()
             .await?
-            .validate()?;
+            .validate()
+    }
+
+    pub async fn get_subtitles(&self, page: &PageInfo) -> Result<Vec<SubTitle>> {
+        let mut res = self.get_player_info(page).await?;
         // 接口返回的信息，包含了一系列的字幕，每个字幕包含了字幕的语言和 json 下载地址
         match serde_json::from_value::<Option<SubTitlesInfo>>(res["data"]["subtitle"].take())? {
             Some(subtitles_info) => {
@@ -200,6 +231,13 @@ impl<'a> Video<'a> {
         }
     }
 
+    /// 获取视频看点（分段章节），未设置看点的视频返回空列表
+    pub async fn get_chapters(&self, page: &PageInfo) -> Result<Vec<Chapter>> {
+        let mut res = self.get_player_info(page).await?;
+        let chapters = serde_json::from_value::<Option<Vec<Chapter>>>(res["data"]["view_points"].take())?;
+        Ok(normalize_chapters(chapters.unwrap_or_default()))
+    }
+
     async fn get_subtitle(&self, info: SubTitleInfo) -> Result<SubTitle> {
         let mut res = self
             .client
@@ -213,4 +251,127 @@ impl<'a> Video<'a> {
         let body: SubTitleBody = serde_json::from_value(res["body"].take())?;
         Ok(SubTitle { lan: info.lan, body })
     }
+
+    /// 按热度拉取一级评论，每条一级评论附带有限数量的楼中楼回复
+    pub async fn get_comments(&self, option: &CommentOption) -> Result<Vec<Comment>> {
+        let aid = bvid_to_aid(&self.bvid)?;
+        let (mut comments, mut offset) = (Vec::new(), String::new());
+        for page in 0..option.max_pages {
+            let mut res = self
+                .client
+                .request(
+                    Method::GET,
+                    "https://api.bilibili.com/x/v2/reply/wbi/main",
+                    self.credential,
+                )
+                .await
+                .query(&[("oid", aid), ("type", 1), ("mode", 3), ("plat", 1)])
+                .query(&[("pagination_str", serde_json::json!({ "offset": offset }).to_string())])
+                .wbi_sign(MIXIN_KEY.load().as_deref())?
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<serde_json::Value>()
+                .await?
+                .validate()?;
+            let mut replies = Vec::new();
+            if page == 0 {
+                // 置顶评论只在第一页返回
+                replies.extend(
+                    serde_json::from_value::<Option<Vec<ReplyItem>>>(res["data"]["top_replies"].take())?
+                        .unwrap_or_default(),
+                );
+            }
+            replies.extend(
+                serde_json::from_value::<Option<Vec<ReplyItem>>>(res["data"]["replies"].take())?.unwrap_or_default(),
+            );
+            for reply in replies {
+                let mut comment = Comment::from(reply);
+                if comment.reply_count > comment.replies.len() as i64 && comment.replies.len() < option.max_sub_replies
+                {
+                    // 接口仅附带少量楼中楼预览，不够时单独拉取
+                    comment.replies = self.get_sub_replies(aid, comment.rpid, option.max_sub_replies).await?;
+                }
+                comment.replies.truncate(option.max_sub_replies);
+                comments.push(comment);
+            }
+            let cursor = &res["data"]["cursor"];
+            if cursor["is_end"].as_bool().unwrap_or(true) {
+                break;
+            }
+            match cursor["pagination_reply"]["next_offset"].as_str() {
+                Some(next_offset) if !next_offset.is_empty() => offset = next_offset.to_owned(),
+                _ => break,
+            }
+        }
+        // 置顶评论可能在后续页中重复出现
+        let mut seen = HashSet::new();
+        comments.retain(|comment| seen.insert(comment.rpid));
+        Ok(comments)
+    }
+
+    async fn get_sub_replies(&self, aid: u64, root: i64, limit: usize) -> Result<Vec<Comment>> {
+        let mut replies = Vec::new();
+        let mut page = 1;
+        while replies.len() < limit {
+            let mut res = self
+                .client
+                .request(
+                    Method::GET,
+                    "https://api.bilibili.com/x/v2/reply/reply",
+                    self.credential,
+                )
+                .await
+                .query(&[("oid", aid), ("type", 1), ("ps", 20), ("pn", page)])
+                .query(&[("root", root)])
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<serde_json::Value>()
+                .await?
+                .validate()?;
+            let items =
+                serde_json::from_value::<Option<Vec<ReplyItem>>>(res["data"]["replies"].take())?.unwrap_or_default();
+            if items.is_empty() {
+                break;
+            }
+            replies.extend(items.into_iter().map(Comment::from));
+            page += 1;
+        }
+        replies.truncate(limit);
+        Ok(replies)
+    }
+}
+
+/// 将 bvid 转换为 aid，评论等接口仅接受 aid
+/// https://socialsisteryi.github.io/bilibili-API-collect/docs/misc/bvid_desc.html
+pub fn bvid_to_aid(bvid: &str) -> Result<u64> {
+    const XOR_CODE: u64 = 23442827791579;
+    const MASK_CODE: u64 = 2251799813685247;
+    const ALPHABET: &[u8] = b"FcwAPNKTMug3GV5Lj7EJnHpWsx4tb8haYeviqBz6rkCy12mUSDQX9RdoZf";
+    let mut chars = bvid.as_bytes().to_vec();
+    ensure!(chars.len() == 12 && bvid.starts_with("BV1"), "invalid bvid: {}", bvid);
+    chars.swap(3, 9);
+    chars.swap(4, 7);
+    let mut tmp = 0u64;
+    for ch in &chars[3..] {
+        let idx = ALPHABET
+            .iter()
+            .position(|c| c == ch)
+            .with_context(|| format!("invalid bvid: {}", bvid))?;
+        tmp = tmp * ALPHABET.len() as u64 + idx as u64;
+    }
+    Ok((tmp & MASK_CODE) ^ XOR_CODE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bvid_to_aid() {
+        assert_eq!(bvid_to_aid("BV1L9Uoa9EUx").unwrap(), 111298867365120);
+        assert_eq!(bvid_to_aid("BV17x411w7KC").unwrap(), 170001);
+        assert!(bvid_to_aid("BV1L9Uoa9EU").is_err());
+    }
 }
@@ -0,0 +1,149 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, bail};
+use clap::Subcommand;
+use sea_orm::DatabaseConnection;
+use serde_json::Value;
+use validator::Validate;
+
+use crate::bilibili::BiliClient;
+use crate::config::{BundleFormat, Config, ConfigBundle, VersionedConfig};
+
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// 查看配置，可以使用以点分隔的路径查看其中的某一项，如 concurrent_limit.video
+    Get { key: Option<String> },
+    /// 修改配置中的某一项，值会优先按照 JSON 解析，解析失败时视为字符串
+    Set { key: String, value: String },
+    /// 从文件导入配置与视频源，导入前会展示与当前配置的差异
+    Import {
+        file: PathBuf,
+        /// 文件格式，不指定时根据扩展名推断
+        #[arg(long, value_enum)]
+        format: Option<BundleFormat>,
+        /// 仅展示差异，不实际导入
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// 导出配置与全部视频源，不指定文件时输出到标准输出
+    Export {
+        file: Option<PathBuf>,
+        /// 文件格式，不指定时根据扩展名推断，默认为 TOML
+        #[arg(long, value_enum)]
+        format: Option<BundleFormat>,
+        /// 不导出鉴权令牌、登录凭据与通知器，导入时沿用当前的值
+        #[arg(long)]
+        exclude_secrets: bool,
+    },
+}
+
+impl ConfigCommand {
+    pub(super) fn mutating(&self) -> bool {
+        match self {
+            ConfigCommand::Set { .. } => true,
+            ConfigCommand::Import { dry_run, .. } => !dry_run,
+            ConfigCommand::Get { .. } | ConfigCommand::Export { .. } => false,
+        }
+    }
+}
+
+pub async fn run(command: &ConfigCommand, connection: &DatabaseConnection) -> Result<()> {
+    let config = VersionedConfig::get().snapshot();
+    match command {
+        ConfigCommand::Get { key } => {
+            let value = serde_json::to_value(config.as_ref())?;
+            let value = match key {
+                Some(key) => value
+                    .pointer(&pointer(key))
+                    .with_context(|| format!("配置项 {} 不存在", key))?,
+                None => &value,
+            };
+            println!("{}", serde_json::to_string_pretty(value)?);
+        }
+        ConfigCommand::Set { key, value } => {
+            let new_config = set_value(&config, key, value)?;
+            save(new_config, connection).await?;
+            println!("配置项 {} 已更新", key);
+        }
+        ConfigCommand::Import { file, format, dry_run } => {
+            let content = tokio::fs::read_to_string(file)
+                .await
+                .with_context(|| format!("读取文件 {} 失败", file.display()))?;
+            let format = format.unwrap_or_else(|| BundleFormat::from_path(file));
+            let (bundle, changes) =
+                ConfigBundle::prepare_import(&content, format, &BiliClient::new(), connection).await?;
+            if changes.is_empty() {
+                println!("导入的内容与当前配置一致");
+                return Ok(());
+            }
+            for change in &changes {
+                println!("{}", change);
+            }
+            if *dry_run {
+                return Ok(());
+            }
+            bundle.apply(connection).await?;
+            println!("已从 {} 导入配置，共 {} 项变更", file.display(), changes.len());
+        }
+        ConfigCommand::Export {
+            file,
+            format,
+            exclude_secrets,
+        } => {
+            let format = format.unwrap_or_else(|| file.as_deref().map(BundleFormat::from_path).unwrap_or_default());
+            let content = ConfigBundle::export(connection, *exclude_secrets).await?.dump(format)?;
+            match file {
+                Some(file) => {
+                    tokio::fs::write(file, content)
+                        .await
+                        .with_context(|| format!("写入文件 {} 失败", file.display()))?;
+                    println!("配置已导出到 {}", file.display());
+                }
+                None => print!("{}", content),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 校验并保存配置，与接口的校验规则保持一致
+async fn save(config: Config, connection: &DatabaseConnection) -> Result<()> {
+    config.validate()?;
+    config.check()?;
+    VersionedConfig::get().update(config, connection).await?;
+    Ok(())
+}
+
+/// 将以点分隔的路径转换为 JSON Pointer
+fn pointer(key: &str) -> String {
+    key.split('.')
+        .map(|part| format!("/{}", part.replace('~', "~0").replace('/', "~1")))
+        .collect()
+}
+
+/// 修改配置中已经存在的某一项，返回修改后的配置
+fn set_value(config: &Config, key: &str, raw: &str) -> Result<Config> {
+    let mut value = serde_json::to_value(config)?;
+    let Some(target) = value.pointer_mut(&pointer(key)) else {
+        bail!("配置项 {} 不存在", key);
+    };
+    *target = serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_owned()));
+    serde_json::from_value(value).with_context(|| format!("配置项 {} 的值 {} 不合法", key, raw))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_value() {
+        let config = Config::default();
+        let new_config = set_value(&config, "concurrent_limit.video", "5").unwrap();
+        assert_eq!(new_config.concurrent_limit.video, 5);
+        // 无法按照 JSON 解析的值视为字符串
+        let new_config = set_value(&config, "bind_address", "0.0.0.0:12346").unwrap();
+        assert_eq!(new_config.bind_address, "0.0.0.0:12346");
+        assert!(set_value(&config, "not_exist", "1").is_err());
+        assert!(set_value(&config, "concurrent_limit.video", "\"five\"").is_err());
+    }
+}
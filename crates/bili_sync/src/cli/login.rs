@@ -0,0 +1,60 @@
+use std::io::{BufRead, Write};
+
+use anyhow::{Context, Result, bail};
+use clap::Args;
+use sea_orm::DatabaseConnection;
+
+use crate::bilibili::{BiliClient, Credential};
+use crate::config::VersionedConfig;
+
+/// 未通过参数指定的字段会在终端中依次询问
+#[derive(Args)]
+pub struct LoginArgs {
+    #[arg(long)]
+    sessdata: Option<String>,
+    #[arg(long)]
+    bili_jct: Option<String>,
+    #[arg(long)]
+    buvid3: Option<String>,
+    #[arg(long)]
+    dedeuserid: Option<String>,
+    #[arg(long)]
+    ac_time_value: Option<String>,
+}
+
+pub async fn run(args: &LoginArgs, connection: &DatabaseConnection) -> Result<()> {
+    let mut credential = Credential {
+        sessdata: field("SESSDATA", &args.sessdata)?,
+        bili_jct: field("bili_jct", &args.bili_jct)?,
+        buvid3: field("buvid3", &args.buvid3)?,
+        dedeuserid: field("DedeUserID", &args.dedeuserid)?,
+        ac_time_value: field("ac_time_value", &args.ac_time_value)?,
+    };
+    // 凭据无效时检查刷新的接口会直接报错，需要刷新时顺便刷新后再保存
+    if let Some(new_credential) = BiliClient::new()
+        .check_refresh(&credential)
+        .await
+        .context("登录凭据校验失败")?
+    {
+        credential = new_credential;
+    }
+    VersionedConfig::get().update_credential(credential, connection).await?;
+    println!("登录凭据校验通过，已保存");
+    Ok(())
+}
+
+fn field(name: &str, value: &Option<String>) -> Result<String> {
+    if let Some(value) = value {
+        return Ok(value.clone());
+    }
+    // 提示输出到标准错误，避免混入标准输出
+    eprint!("{}：", name);
+    std::io::stderr().flush()?;
+    let mut line = String::new();
+    std::io::stdin().lock().read_line(&mut line)?;
+    let line = line.trim();
+    if line.is_empty() {
+        bail!("{} 不能为空", name);
+    }
+    Ok(line.to_owned())
+}
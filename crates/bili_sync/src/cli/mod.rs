@@ -0,0 +1,167 @@
+mod config;
+mod login;
+mod sources;
+mod videos;
+
+use std::process::ExitCode;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use clap::{Subcommand, ValueEnum};
+use sea_orm::DatabaseConnection;
+
+use crate::bilibili::BiliClient;
+use crate::cli::config::ConfigCommand;
+use crate::cli::login::LoginArgs;
+use crate::cli::sources::SourcesCommand;
+use crate::cli::videos::VideosCommand;
+use crate::config::{ARGS, CONFIG_DIR, VersionedConfig};
+use crate::database::setup_database;
+use crate::task::run_once;
+use crate::utils::init_cli_logger;
+use crate::utils::instance_lock::InstanceLock;
+use crate::utils::notify::wait_pending_notifications;
+use crate::utils::signal::terminate;
+use crate::utils::task_control::TaskControl;
+
+/// 命令行执行结束前等待通知发送的最长时间
+const NOTIFY_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// 执行一轮全部视频源的下载任务后退出
+    ///
+    /// 退出码：0 表示全部成功，1 表示任务无法执行，2 表示存在处理失败的视频源或视频
+    RunOnce,
+    /// 管理视频源
+    #[command(subcommand)]
+    Sources(SourcesCommand),
+    /// 查看视频与重置视频的下载状态
+    #[command(subcommand)]
+    Videos(VideosCommand),
+    /// 查看与修改配置
+    #[command(subcommand)]
+    Config(ConfigCommand),
+    /// 设置 b 站登录凭据，校验通过后保存
+    Login(LoginArgs),
+}
+
+impl Command {
+    /// 是否会修改数据库中的配置、视频源或视频状态
+    fn mutating(&self) -> bool {
+        match self {
+            Command::RunOnce | Command::Login(_) => true,
+            Command::Sources(command) => command.mutating(),
+            Command::Videos(command) => command.mutating(),
+            Command::Config(command) => command.mutating(),
+        }
+    }
+}
+
+/// 视频源的类型，与接口中使用的名称一致
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub enum SourceType {
+    Favorites,
+    Collections,
+    Submissions,
+    #[value(name = "watch_later")]
+    WatchLater,
+    Bangumi,
+}
+
+impl SourceType {
+    fn key(&self) -> &'static str {
+        match self {
+            SourceType::Favorites => "favorites",
+            SourceType::Collections => "collections",
+            SourceType::Submissions => "submissions",
+            SourceType::WatchLater => "watch_later",
+            SourceType::Bangumi => "bangumi",
+        }
+    }
+}
+
+/// 执行命令行子命令，不会启动 Web 服务与定时任务
+pub async fn run(command: &Command) -> ExitCode {
+    init_cli_logger(&ARGS.log_level);
+    // 会修改数据的子命令需要独占配置目录，避免与正在运行的服务互相覆盖
+    let _lock = match command
+        .mutating()
+        .then(InstanceLock::acquire)
+        .transpose()
+        .map(Option::flatten)
+    {
+        Ok(lock) => lock,
+        Err(e) => {
+            error!("{:#}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let connection = match init().await {
+        Ok(connection) => connection,
+        Err(e) => {
+            error!("{:#}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let res = match command {
+        Command::RunOnce => run_once_command(&connection).await,
+        Command::Sources(command) => sources::run(command, &connection).await.map(|_| ExitCode::SUCCESS),
+        Command::Videos(command) => videos::run(command, &connection).await.map(|_| ExitCode::SUCCESS),
+        Command::Config(command) => config::run(command, &connection).await.map(|_| ExitCode::SUCCESS),
+        Command::Login(args) => login::run(args, &connection).await.map(|_| ExitCode::SUCCESS),
+    };
+    if tokio::time::timeout(NOTIFY_TIMEOUT, wait_pending_notifications())
+        .await
+        .is_err()
+    {
+        warn!("等待通知发送超时，部分通知可能未能送达");
+    }
+    if let Err(e) = connection.close().await {
+        warn!("关闭数据库连接时遇到错误：{:#}", e);
+    }
+    res.unwrap_or_else(|e| {
+        error!("{:#}", e);
+        ExitCode::FAILURE
+    })
+}
+
+async fn init() -> Result<DatabaseConnection> {
+    let connection = setup_database(&CONFIG_DIR.join("data.sqlite"))
+        .await
+        .context("数据库初始化失败")?;
+    VersionedConfig::init(&connection).await.context("配置初始化失败")?;
+    Ok(connection)
+}
+
+async fn run_once_command(connection: &DatabaseConnection) -> Result<ExitCode> {
+    let bili_client = BiliClient::new();
+    let control = TaskControl::new();
+    // 收到终止信号时中断正在下载的内容，已经完成的部分会正常保存
+    tokio::spawn({
+        let control = control.clone();
+        async move {
+            if terminate().await.is_ok() {
+                warn!("接收到终止信号，正在结束本轮任务..");
+                control.cancel();
+            }
+        }
+    });
+    let round = run_once(connection, &bili_client, &control).await?;
+    let mut succeeded = true;
+    for source in &round.sources {
+        match &source.error {
+            Some(error) => println!("{}：处理失败，{}", source.name, error),
+            None => println!(
+                "{}：新增 {} 个视频，下载成功 {} 个，失败 {} 个",
+                source.name, source.new_videos, source.downloaded, source.failed
+            ),
+        }
+        succeeded &= source.error.is_none() && source.failed == 0;
+    }
+    Ok(if succeeded {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(2)
+    })
+}
@@ -0,0 +1,197 @@
+use std::collections::HashSet;
+
+use anyhow::{Result, bail};
+use clap::{Args, Subcommand};
+use sea_orm::DatabaseConnection;
+use serde::Serialize;
+
+use crate::adapter::VideoSource;
+use crate::bilibili::{BiliClient, CollectionItem, CollectionType};
+use crate::cli::SourceType;
+use crate::utils::model::{
+    NewVideoSource, delete_video_source, get_all_video_sources, get_enabled_video_sources, insert_video_source,
+};
+use crate::utils::validation::validate_path;
+
+#[derive(Subcommand)]
+pub enum SourcesCommand {
+    /// 列出全部视频源
+    List {
+        /// 以 JSON 格式输出
+        #[arg(long)]
+        json: bool,
+    },
+    /// 新增视频源，默认在新增后直接启用
+    #[command(subcommand)]
+    Add(AddSource),
+    /// 删除视频源，连同其下已经入库的视频记录（不会删除已下载的文件）
+    Remove {
+        #[arg(value_enum)]
+        source_type: SourceType,
+        id: i32,
+    },
+}
+
+impl SourcesCommand {
+    pub(super) fn mutating(&self) -> bool {
+        !matches!(self, SourcesCommand::List { .. })
+    }
+}
+
+#[derive(Subcommand)]
+pub enum AddSource {
+    /// 收藏夹
+    Favorite {
+        fid: i64,
+        #[command(flatten)]
+        options: AddOptions,
+    },
+    /// 合集，指定 --series 时表示列表
+    Collection {
+        /// 合集所属 UP 主的 mid
+        mid: i64,
+        sid: i64,
+        #[arg(long)]
+        series: bool,
+        #[command(flatten)]
+        options: AddOptions,
+    },
+    /// UP 主投稿
+    Submission {
+        upper_id: i64,
+        #[command(flatten)]
+        options: AddOptions,
+    },
+    /// 番剧
+    Bangumi {
+        season_id: i64,
+        #[command(flatten)]
+        options: AddOptions,
+    },
+}
+
+#[derive(Args)]
+pub struct AddOptions {
+    /// 视频源的保存路径，需要为绝对路径
+    #[arg(long)]
+    path: String,
+    /// 仅新增视频源，暂不启用
+    #[arg(long)]
+    disabled: bool,
+}
+
+#[derive(Serialize)]
+struct SourceItem {
+    source_type: &'static str,
+    id: i32,
+    name: String,
+    enabled: bool,
+    path: String,
+}
+
+pub async fn run(command: &SourcesCommand, connection: &DatabaseConnection) -> Result<()> {
+    match command {
+        SourcesCommand::List { json } => list(*json, connection).await,
+        SourcesCommand::Add(source) => add(source, connection).await,
+        SourcesCommand::Remove { source_type, id } => remove(*source_type, *id, connection).await,
+    }
+}
+
+async fn list(json: bool, connection: &DatabaseConnection) -> Result<()> {
+    let enabled = get_enabled_video_sources(connection)
+        .await?
+        .iter()
+        .map(|video_source| video_source.source_key())
+        .collect::<HashSet<_>>();
+    let items = get_all_video_sources(connection)
+        .await?
+        .into_iter()
+        .map(|video_source| {
+            let (source_type, id) = video_source.source_key();
+            SourceItem {
+                source_type,
+                id,
+                name: video_source.display_name().into_owned(),
+                enabled: enabled.contains(&(source_type, id)),
+                path: video_source.path().to_string_lossy().into_owned(),
+            }
+        })
+        .collect::<Vec<_>>();
+    if json {
+        println!("{}", serde_json::to_string_pretty(&items)?);
+        return Ok(());
+    }
+    for item in items {
+        println!(
+            "{}\t{}\t{}\t{}\t{}",
+            item.source_type,
+            item.id,
+            if item.enabled { "启用" } else { "禁用" },
+            item.name,
+            item.path
+        );
+    }
+    Ok(())
+}
+
+async fn add(source: &AddSource, connection: &DatabaseConnection) -> Result<()> {
+    let (new_source, options) = match source {
+        AddSource::Favorite { fid, options } => (NewVideoSource::Favorite { fid: *fid }, options),
+        AddSource::Collection {
+            mid,
+            sid,
+            series,
+            options,
+        } => (
+            NewVideoSource::Collection(CollectionItem {
+                sid: sid.to_string(),
+                mid: mid.to_string(),
+                collection_type: if *series {
+                    CollectionType::Series
+                } else {
+                    CollectionType::Season
+                },
+            }),
+            options,
+        ),
+        AddSource::Submission { upper_id, options } => (NewVideoSource::Submission { upper_id: *upper_id }, options),
+        AddSource::Bangumi { season_id, options } => (
+            NewVideoSource::Bangumi {
+                season_id: *season_id,
+                selected_section_ids: String::new(),
+            },
+            options,
+        ),
+    };
+    if validate_path(&options.path).is_err() {
+        bail!("视频源的保存路径需要为绝对路径");
+    }
+    let name = insert_video_source(
+        new_source,
+        options.path.clone(),
+        !options.disabled,
+        &BiliClient::new(),
+        connection,
+    )
+    .await?;
+    println!("已新增视频源「{}」", name);
+    Ok(())
+}
+
+async fn remove(source_type: SourceType, id: i32, connection: &DatabaseConnection) -> Result<()> {
+    if source_type == SourceType::WatchLater {
+        bail!("稍后再看不支持删除");
+    }
+    let key = (source_type.key(), id);
+    let Some(video_source) = get_all_video_sources(connection)
+        .await?
+        .into_iter()
+        .find(|video_source| video_source.source_key() == key)
+    else {
+        bail!("视频源 {} {} 不存在", key.0, id);
+    };
+    let display_name = video_source.display_name();
+    delete_video_source(video_source, connection).await?;
+    println!("已删除{}", display_name);
+    Ok(())
+}
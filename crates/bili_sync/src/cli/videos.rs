@@ -0,0 +1,175 @@
+use anyhow::Result;
+use bili_sync_entity::*;
+use clap::{Args, Subcommand};
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, Condition, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Select};
+use serde::Serialize;
+
+use crate::api::reset_videos_status;
+use crate::utils::status::{STATUS_MAX_RETRY, VideoStatus};
+
+#[derive(Subcommand)]
+pub enum VideosCommand {
+    /// 列出视频，按照入库时间倒序排列
+    List {
+        #[command(flatten)]
+        filter: VideoFilter,
+        /// 仅列出下载失败的视频
+        #[arg(long)]
+        failed: bool,
+        /// 最多列出的视频数量
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+        /// 以 JSON 格式输出
+        #[arg(long)]
+        json: bool,
+    },
+    /// 重置视频中失败的下载任务，使其在下一轮下载中重新执行
+    Reset {
+        /// 需要重置的视频 id，不指定时重置筛选出的全部视频
+        ids: Vec<i32>,
+        #[command(flatten)]
+        filter: VideoFilter,
+        /// 额外修正仍有子任务未执行却被标记为完成的视频
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+impl VideosCommand {
+    pub(super) fn mutating(&self) -> bool {
+        !matches!(self, VideosCommand::List { .. })
+    }
+}
+
+/// 与接口一致的视频筛选条件
+#[derive(Args)]
+pub struct VideoFilter {
+    #[arg(long)]
+    collection: Option<i32>,
+    #[arg(long)]
+    favorite: Option<i32>,
+    #[arg(long)]
+    submission: Option<i32>,
+    #[arg(long)]
+    watch_later: Option<i32>,
+    #[arg(long)]
+    bangumi: Option<i32>,
+    /// 按照视频名称或 bvid 查找
+    #[arg(long)]
+    query: Option<String>,
+}
+
+impl VideoFilter {
+    fn apply(&self, mut query: Select<video::Entity>) -> Select<video::Entity> {
+        for (field, column) in [
+            (self.collection, video::Column::CollectionId),
+            (self.favorite, video::Column::FavoriteId),
+            (self.submission, video::Column::SubmissionId),
+            (self.watch_later, video::Column::WatchLaterId),
+            (self.bangumi, video::Column::BangumiId),
+        ] {
+            if let Some(id) = field {
+                query = query.filter(column.eq(id));
+            }
+        }
+        if let Some(query_word) = &self.query {
+            query = query.filter(
+                video::Column::Name
+                    .contains(query_word)
+                    .or(video::Column::Bvid.contains(query_word)),
+            );
+        }
+        query
+    }
+}
+
+#[derive(Serialize)]
+struct VideoItem {
+    id: i32,
+    bvid: String,
+    name: String,
+    status: &'static str,
+    download_status: [u32; 6],
+}
+
+pub async fn run(command: &VideosCommand, connection: &DatabaseConnection) -> Result<()> {
+    match command {
+        VideosCommand::List {
+            filter,
+            failed,
+            limit,
+            json,
+        } => list(filter, *failed, *limit, *json, connection).await,
+        VideosCommand::Reset { ids, filter, force } => {
+            let mut query = filter.apply(video::Entity::find());
+            if !ids.is_empty() {
+                query = query.filter(video::Column::Id.is_in(ids.iter().copied()));
+            }
+            let res = reset_videos_status(query, *force, connection).await?;
+            println!(
+                "已重置 {} 个视频、{} 个分页",
+                res.resetted_videos_count, res.resetted_pages_count
+            );
+            Ok(())
+        }
+    }
+}
+
+async fn list(
+    filter: &VideoFilter,
+    failed: bool,
+    limit: usize,
+    json: bool,
+    connection: &DatabaseConnection,
+) -> Result<()> {
+    let mut query = filter.apply(video::Entity::find());
+    if failed {
+        // 与 status_label 的判断一致：存在达到最大重试次数的子任务
+        query = query.filter((0..6).fold(Condition::any(), |condition, offset| {
+            condition.add(Expr::cust_with_values(
+                "(download_status >> ?) & 7 = ?",
+                [offset * 3, STATUS_MAX_RETRY],
+            ))
+        }));
+    }
+    let items = query
+        .order_by_desc(video::Column::Id)
+        .limit(limit as u64)
+        .all(connection)
+        .await?
+        .into_iter()
+        .map(|video| {
+            let status = VideoStatus::from(video.download_status);
+            VideoItem {
+                id: video.id,
+                bvid: video.bvid,
+                name: video.name,
+                status: status_label(status),
+                download_status: status.into(),
+            }
+        })
+        .collect::<Vec<_>>();
+    if json {
+        println!("{}", serde_json::to_string_pretty(&items)?);
+        return Ok(());
+    }
+    for item in items {
+        println!("{}\t{}\t{}\t{}", item.id, item.bvid, item.status, item.name);
+    }
+    Ok(())
+}
+
+/// 存在达到最大重试次数的子任务时视为失败，升级后新增的子任务尚未执行的视频不算失败
+fn status_label(status: VideoStatus) -> &'static str {
+    let statuses: [u32; 6] = status.into();
+    if status.has_failed() {
+        "失败"
+    } else if status.get_completed() {
+        "完成"
+    } else if statuses.iter().all(|s| *s == 0) {
+        "等待"
+    } else {
+        "进行中"
+    }
+}
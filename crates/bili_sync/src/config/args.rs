@@ -3,19 +3,25 @@ use std::sync::LazyLock;
 
 use clap::Parser;
 
+use crate::cli::Command;
+
 pub static ARGS: LazyLock<Args> = LazyLock::new(Args::parse);
 
 #[derive(Parser)]
 #[command(name = "Bili-Sync", version = detail_version(), about, long_about = None)]
 pub struct Args {
-    #[arg(short, long, env = "SCAN_ONLY")]
+    #[arg(short, long, env = "SCAN_ONLY", global = true)]
     pub scan_only: bool,
 
-    #[arg(short, long, default_value = "None,bili_sync=info", env = "RUST_LOG")]
+    #[arg(short, long, default_value = "None,bili_sync=info", env = "RUST_LOG", global = true)]
     pub log_level: String,
 
-    #[arg(short, long, env = "DISABLE_CREDENTIAL_REFRESH")]
+    #[arg(short, long, env = "DISABLE_CREDENTIAL_REFRESH", global = true)]
     pub disable_credential_refresh: bool,
+
+    /// 不指定子命令时启动 Web 服务与定时下载任务
+    #[command(subcommand)]
+    pub command: Option<Command>,
 }
 
 mod built_info {
@@ -0,0 +1,763 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use bili_sync_entity::rule::Rule;
+use bili_sync_entity::*;
+use clap::ValueEnum;
+use sea_orm::ActiveValue::Set;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DatabaseTransaction, EntityTrait, QueryFilter, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use validator::Validate;
+
+use crate::bilibili::{BiliClient, CollectionItem, CollectionType, Credential};
+use crate::config::item::{deserialize_rule_dsl, serialize_rule_dsl};
+use crate::config::{Config, Trigger, VersionedConfig};
+use crate::utils::model::{FetchedVideoSource, NewVideoSource};
+use crate::utils::validation::{validate_path, validate_trigger};
+
+/// 仅用于展示的名称字段，导入时不会使用，也不参与差异比较
+const INFO_FIELDS: [&str; 3] = ["name", "upper_name", "title"];
+
+/// 视频源的标识字段，已经体现在差异比较的键中，不再单独比较
+const KEY_FIELDS: [&str; 6] = ["fid", "mid", "sid", "series", "upper_id", "season_id"];
+
+/// 差异预览中需要隐藏取值的配置项
+const SECRET_KEYS: [&str; 3] = ["config.auth_token", "config.credential", "config.notifiers"];
+
+/// 导入导出使用的文件格式
+#[derive(Serialize, Deserialize, ValueEnum, Clone, Copy, Default, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum BundleFormat {
+    #[default]
+    Toml,
+    Json,
+}
+
+impl BundleFormat {
+    /// 根据文件扩展名推断格式，无法推断时使用 TOML
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => BundleFormat::Json,
+            _ => BundleFormat::Toml,
+        }
+    }
+}
+
+/// 完整的配置与全部视频源的定义，用于备份或以文件的形式管理部署
+#[derive(Serialize, Deserialize)]
+pub struct ConfigBundle {
+    /// 导出时排除了鉴权令牌、登录凭据与通知器，导入时沿用当前的值
+    #[serde(default)]
+    pub secrets_excluded: bool,
+    pub config: Config,
+    /// 文件中未包含的视频源在导入时保持不变
+    #[serde(default)]
+    pub video_sources: VideoSources,
+    /// 导入前已经从 b 站获取到信息的新视频源
+    #[serde(skip)]
+    new_sources: Vec<FetchedVideoSource>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct VideoSources {
+    #[serde(default)]
+    pub favorites: Vec<FavoriteSource>,
+    #[serde(default)]
+    pub collections: Vec<CollectionSource>,
+    #[serde(default)]
+    pub submissions: Vec<SubmissionSource>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub watch_later: Option<SourceOption>,
+    #[serde(default)]
+    pub bangumi: Vec<BangumiSource>,
+}
+
+/// 各类视频源共有的设置
+#[derive(Serialize, Deserialize)]
+pub struct SourceOption {
+    pub path: String,
+    pub enabled: bool,
+    #[serde(default)]
+    pub danmaku_snapshot: bool,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_rule_dsl",
+        deserialize_with = "deserialize_rule_dsl"
+    )]
+    pub rule: Option<Rule>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trigger: Option<Trigger>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FavoriteSource {
+    pub fid: i64,
+    #[serde(default)]
+    pub name: String,
+    #[serde(flatten)]
+    pub option: SourceOption,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CollectionSource {
+    pub mid: i64,
+    pub sid: i64,
+    /// 为 true 时表示列表，否则表示合集
+    #[serde(default)]
+    pub series: bool,
+    #[serde(default)]
+    pub name: String,
+    #[serde(flatten)]
+    pub option: SourceOption,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SubmissionSource {
+    pub upper_id: i64,
+    #[serde(default)]
+    pub upper_name: String,
+    #[serde(default)]
+    pub use_dynamic_api: bool,
+    #[serde(flatten)]
+    pub option: SourceOption,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BangumiSource {
+    pub season_id: i64,
+    #[serde(default)]
+    pub title: String,
+    /// 额外下载的花絮 section，为空时仅下载正片
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub selected_section_ids: Vec<i64>,
+    #[serde(flatten)]
+    pub option: SourceOption,
+}
+
+/// 导入前后发生变化的配置项，old 与 new 为 None 分别表示新增与删除
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ConfigChange {
+    pub key: String,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+}
+
+impl fmt::Display for ConfigChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.old, &self.new) {
+            (Some(old), Some(new)) => write!(f, "~ {}: {} -> {}", self.key, old, new),
+            (None, Some(new)) => write!(f, "+ {}: {}", self.key, new),
+            (Some(old), None) => write!(f, "- {}: {}", self.key, old),
+            (None, None) => write!(f, "  {}", self.key),
+        }
+    }
+}
+
+impl SourceOption {
+    fn from_model(
+        path: String,
+        enabled: bool,
+        danmaku_snapshot: bool,
+        rule: Option<Rule>,
+        trigger: Option<Trigger>,
+    ) -> Self {
+        Self {
+            path,
+            enabled,
+            danmaku_snapshot,
+            rule,
+            trigger,
+        }
+    }
+
+    fn check(&self, key: &str) -> Result<()> {
+        validate_path(&self.path).with_context(|| format!("视频源 {} 的保存路径需要为绝对路径", key))?;
+        if let Some(trigger) = &self.trigger {
+            validate_trigger(trigger).with_context(|| format!("视频源 {} 的触发条件无效", key))?;
+        }
+        Ok(())
+    }
+}
+
+/// 将视频源共有的设置写入对应的 ActiveModel
+macro_rules! set_option {
+    ($active_model:ident, $option:expr) => {{
+        let option = $option;
+        $active_model.path = Set(option.path);
+        $active_model.enabled = Set(option.enabled);
+        $active_model.danmaku_snapshot = Set(option.danmaku_snapshot);
+        $active_model.rule = Set(option.rule);
+        $active_model.trigger = Set(option.trigger);
+    }};
+}
+
+impl VideoSources {
+    async fn load(connection: &DatabaseConnection) -> Result<Self> {
+        let (favorites, collections, submissions, watch_later, bangumi) = tokio::try_join!(
+            favorite::Entity::find().all(connection),
+            collection::Entity::find().all(connection),
+            submission::Entity::find().all(connection),
+            watch_later::Entity::find().one(connection),
+            bangumi::Entity::find().all(connection),
+        )?;
+        Ok(Self {
+            favorites: favorites
+                .into_iter()
+                .map(|m| FavoriteSource {
+                    fid: m.f_id,
+                    name: m.name,
+                    option: SourceOption::from_model(m.path, m.enabled, m.danmaku_snapshot, m.rule, m.trigger),
+                })
+                .collect(),
+            collections: collections
+                .into_iter()
+                .map(|m| CollectionSource {
+                    mid: m.m_id,
+                    sid: m.s_id,
+                    series: CollectionType::try_from(m.r#type).is_ok_and(|t| t == CollectionType::Series),
+                    name: m.name,
+                    option: SourceOption::from_model(m.path, m.enabled, m.danmaku_snapshot, m.rule, m.trigger),
+                })
+                .collect(),
+            submissions: submissions
+                .into_iter()
+                .map(|m| SubmissionSource {
+                    upper_id: m.upper_id,
+                    upper_name: m.upper_name,
+                    use_dynamic_api: m.use_dynamic_api,
+                    option: SourceOption::from_model(m.path, m.enabled, m.danmaku_snapshot, m.rule, m.trigger),
+                })
+                .collect(),
+            watch_later: watch_later
+                .map(|m| SourceOption::from_model(m.path, m.enabled, m.danmaku_snapshot, m.rule, m.trigger)),
+            bangumi: bangumi
+                .into_iter()
+                .map(|m| {
+                    Ok(BangumiSource {
+                        season_id: m.season_id,
+                        selected_section_ids: parse_selected_section_ids(&m.selected_section_ids)
+                            .with_context(|| format!("番剧 {} 的花絮选择无效", m.season_id))?,
+                        title: m.title,
+                        option: SourceOption::from_model(m.path, m.enabled, m.danmaku_snapshot, m.rule, m.trigger),
+                    })
+                })
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    fn check(&self) -> Result<()> {
+        for (key, option) in self.options() {
+            option.check(&key)?;
+        }
+        Ok(())
+    }
+
+    /// 以视频源的唯一标识为键，列出各个视频源的设置
+    fn options(&self) -> Vec<(String, &SourceOption)> {
+        let favorites = self
+            .favorites
+            .iter()
+            .map(|s| (format!("favorites.{}", s.fid), &s.option));
+        let collections = self
+            .collections
+            .iter()
+            .map(|s| (format!("collections.{}", collection_key(s)), &s.option));
+        let submissions = self
+            .submissions
+            .iter()
+            .map(|s| (format!("submissions.{}", s.upper_id), &s.option));
+        let watch_later = self.watch_later.iter().map(|option| ("watch_later".to_owned(), option));
+        let bangumi = self
+            .bangumi
+            .iter()
+            .map(|s| (format!("bangumi.{}", s.season_id), &s.option));
+        favorites
+            .chain(collections)
+            .chain(submissions)
+            .chain(watch_later)
+            .chain(bangumi)
+            .collect()
+    }
+
+    /// 转换为以视频源的唯一标识为键的对象，便于比较差异
+    fn keyed(&self) -> Result<Map<String, Value>> {
+        fn keyed_list<T: Serialize>(sources: &[T], key: impl Fn(&T) -> String) -> Result<Value> {
+            let mut map = Map::new();
+            for source in sources {
+                let mut value = serde_json::to_value(source)?;
+                if let Some(object) = value.as_object_mut() {
+                    for field in INFO_FIELDS.iter().chain(KEY_FIELDS.iter()) {
+                        object.remove(*field);
+                    }
+                }
+                map.insert(key(source), value);
+            }
+            Ok(Value::Object(map))
+        }
+        let mut map = Map::new();
+        map.insert(
+            "favorites".to_owned(),
+            keyed_list(&self.favorites, |s| s.fid.to_string())?,
+        );
+        map.insert("collections".to_owned(), keyed_list(&self.collections, collection_key)?);
+        map.insert(
+            "submissions".to_owned(),
+            keyed_list(&self.submissions, |s| s.upper_id.to_string())?,
+        );
+        map.insert("watch_later".to_owned(), serde_json::to_value(&self.watch_later)?);
+        map.insert(
+            "bangumi".to_owned(),
+            keyed_list(&self.bangumi, |s| s.season_id.to_string())?,
+        );
+        Ok(map)
+    }
+
+    /// 从 b 站获取数据库中尚不存在的视频源的信息，在写入任何内容之前完成，避免导入进行到一半时失败
+    async fn fetch_new(
+        &self,
+        bili_client: &BiliClient,
+        connection: &DatabaseConnection,
+    ) -> Result<Vec<FetchedVideoSource>> {
+        let mut fetched = Vec::new();
+        for source in &self.favorites {
+            if favorite::Entity::find()
+                .filter(favorite::Column::FId.eq(source.fid))
+                .one(connection)
+                .await?
+                .is_none()
+            {
+                let new_source = NewVideoSource::Favorite { fid: source.fid };
+                fetched.push(
+                    fetch(new_source, &source.option, bili_client)
+                        .await
+                        .with_context(|| format!("获取收藏夹 {} 的信息失败", source.fid))?,
+                );
+            }
+        }
+        for source in &self.collections {
+            let collection_type = collection_type(source);
+            if collection::Entity::find()
+                .filter(collection::Column::SId.eq(source.sid))
+                .filter(collection::Column::MId.eq(source.mid))
+                .filter(collection::Column::Type.eq(i32::from(collection_type)))
+                .one(connection)
+                .await?
+                .is_none()
+            {
+                let new_source = NewVideoSource::Collection(CollectionItem {
+                    sid: source.sid.to_string(),
+                    mid: source.mid.to_string(),
+                    collection_type,
+                });
+                fetched.push(
+                    fetch(new_source, &source.option, bili_client)
+                        .await
+                        .with_context(|| format!("获取合集 {} 的信息失败", collection_key(source)))?,
+                );
+            }
+        }
+        for source in &self.submissions {
+            if submission::Entity::find()
+                .filter(submission::Column::UpperId.eq(source.upper_id))
+                .one(connection)
+                .await?
+                .is_none()
+            {
+                let new_source = NewVideoSource::Submission {
+                    upper_id: source.upper_id,
+                };
+                fetched.push(
+                    fetch(new_source, &source.option, bili_client)
+                        .await
+                        .with_context(|| format!("获取 UP 主 {} 的信息失败", source.upper_id))?,
+                );
+            }
+        }
+        for source in &self.bangumi {
+            if bangumi::Entity::find()
+                .filter(bangumi::Column::SeasonId.eq(source.season_id))
+                .one(connection)
+                .await?
+                .is_none()
+            {
+                let new_source = NewVideoSource::Bangumi {
+                    season_id: source.season_id,
+                    selected_section_ids: selected_section_ids(source)?,
+                };
+                fetched.push(
+                    fetch(new_source, &source.option, bili_client)
+                        .await
+                        .with_context(|| format!("获取番剧 {} 的信息失败", source.season_id))?,
+                );
+            }
+        }
+        Ok(fetched)
+    }
+
+    /// 逐个更新视频源的设置，新增的视频源需要已经在同一事务中写入
+    async fn apply(self, txn: &DatabaseTransaction) -> Result<()> {
+        for source in self.favorites {
+            let mut active_model: favorite::ActiveModel = favorite::Entity::find()
+                .filter(favorite::Column::FId.eq(source.fid))
+                .one(txn)
+                .await?
+                .with_context(|| format!("收藏夹 {} 不存在", source.fid))?
+                .into();
+            set_option!(active_model, source.option);
+            active_model.save(txn).await?;
+        }
+        for source in self.collections {
+            let mut active_model: collection::ActiveModel = collection::Entity::find()
+                .filter(collection::Column::SId.eq(source.sid))
+                .filter(collection::Column::MId.eq(source.mid))
+                .filter(collection::Column::Type.eq(i32::from(collection_type(&source))))
+                .one(txn)
+                .await?
+                .with_context(|| format!("合集 {} 不存在", collection_key(&source)))?
+                .into();
+            set_option!(active_model, source.option);
+            active_model.save(txn).await?;
+        }
+        for source in self.submissions {
+            let mut active_model: submission::ActiveModel = submission::Entity::find()
+                .filter(submission::Column::UpperId.eq(source.upper_id))
+                .one(txn)
+                .await?
+                .with_context(|| format!("UP 主投稿 {} 不存在", source.upper_id))?
+                .into();
+            active_model.use_dynamic_api = Set(source.use_dynamic_api);
+            set_option!(active_model, source.option);
+            active_model.save(txn).await?;
+        }
+        if let Some(option) = self.watch_later {
+            // 稍后再看没有唯一标识，数据库中最多只有一条记录
+            let mut active_model: watch_later::ActiveModel = match watch_later::Entity::find().one(txn).await? {
+                Some(model) => model.into(),
+                None => Default::default(),
+            };
+            set_option!(active_model, option);
+            active_model.save(txn).await?;
+        }
+        for source in self.bangumi {
+            let selected_section_ids = selected_section_ids(&source)?;
+            let mut active_model: bangumi::ActiveModel = bangumi::Entity::find()
+                .filter(bangumi::Column::SeasonId.eq(source.season_id))
+                .one(txn)
+                .await?
+                .with_context(|| format!("番剧 {} 不存在", source.season_id))?
+                .into();
+            active_model.selected_section_ids = Set(selected_section_ids);
+            set_option!(active_model, source.option);
+            active_model.save(txn).await?;
+        }
+        Ok(())
+    }
+}
+
+async fn fetch(
+    new_source: NewVideoSource,
+    option: &SourceOption,
+    bili_client: &BiliClient,
+) -> Result<FetchedVideoSource> {
+    let (_, fetched) = FetchedVideoSource::fetch(new_source, option.path.clone(), option.enabled, bili_client).await?;
+    Ok(fetched)
+}
+
+fn collection_type(source: &CollectionSource) -> CollectionType {
+    if source.series {
+        CollectionType::Series
+    } else {
+        CollectionType::Season
+    }
+}
+
+fn selected_section_ids(source: &BangumiSource) -> Result<String> {
+    Ok(if source.selected_section_ids.is_empty() {
+        String::new()
+    } else {
+        serde_json::to_string(&source.selected_section_ids)?
+    })
+}
+
+/// selected_section_ids 的逆操作，空字符串表示未选择花絮
+fn parse_selected_section_ids(selected_section_ids: &str) -> Result<Vec<i64>> {
+    Ok(if selected_section_ids.is_empty() {
+        Vec::new()
+    } else {
+        serde_json::from_str(selected_section_ids)?
+    })
+}
+
+fn collection_key(source: &CollectionSource) -> String {
+    let collection_type = if source.series { "series" } else { "season" };
+    format!("{}:{}:{}", collection_type, source.mid, source.sid)
+}
+
+impl ConfigBundle {
+    /// 导出当前的配置与全部视频源，exclude_secrets 时不包含鉴权令牌、登录凭据与通知器
+    pub async fn export(connection: &DatabaseConnection, exclude_secrets: bool) -> Result<Self> {
+        let mut config = VersionedConfig::get().snapshot().as_ref().clone();
+        if exclude_secrets {
+            config.auth_token = String::new();
+            config.credential = Credential::default();
+            config.notifiers = None;
+        }
+        Ok(Self {
+            secrets_excluded: exclude_secrets,
+            config,
+            video_sources: VideoSources::load(connection).await?,
+            new_sources: Vec::new(),
+        })
+    }
+
+    pub fn parse(content: &str, format: BundleFormat) -> Result<Self> {
+        match format {
+            BundleFormat::Toml => toml::from_str(content).context("解析 TOML 格式的配置失败"),
+            BundleFormat::Json => serde_json::from_str(content).context("解析 JSON 格式的配置失败"),
+        }
+    }
+
+    pub fn dump(&self, format: BundleFormat) -> Result<String> {
+        Ok(match format {
+            BundleFormat::Toml => toml::to_string_pretty(self)?,
+            BundleFormat::Json => serde_json::to_string_pretty(self)?,
+        })
+    }
+
+    /// 解析待导入的内容并完成校验，获取新视频源的信息，同时返回与当前配置相比的差异
+    pub async fn prepare_import(
+        content: &str,
+        format: BundleFormat,
+        bili_client: &BiliClient,
+        connection: &DatabaseConnection,
+    ) -> Result<(Self, Vec<ConfigChange>)> {
+        let current = Self::export(connection, false).await?;
+        let mut bundle = Self::parse(content, format)?.resolve(&current.config);
+        bundle.config.validate()?;
+        bundle.config.check()?;
+        bundle.video_sources.check()?;
+        bundle.new_sources = bundle.video_sources.fetch_new(bili_client, connection).await?;
+        let changes = bundle.diff(&current)?;
+        Ok((bundle, changes))
+    }
+
+    /// 在同一事务中应用导入的配置与视频源，任一步骤失败时不会留下部分导入的内容，需要先经过 prepare_import 的校验
+    pub async fn apply(self, connection: &DatabaseConnection) -> Result<()> {
+        let txn = connection.begin().await?;
+        for new_source in self.new_sources {
+            new_source.insert(&txn).await?;
+        }
+        self.video_sources.apply(&txn).await?;
+        VersionedConfig::get().update_in_txn(self.config, txn).await?;
+        Ok(())
+    }
+
+    /// 沿用当前的配置版本，排除了敏感信息时同时沿用当前的敏感信息
+    fn resolve(mut self, current: &Config) -> Self {
+        self.config.version = current.version;
+        if self.secrets_excluded {
+            self.config.auth_token = current.auth_token.clone();
+            self.config.credential = current.credential.clone();
+            self.config.notifiers = current.notifiers.clone();
+            self.secrets_excluded = false;
+        }
+        self
+    }
+
+    /// 比较与当前配置的差异，仅比较文件中包含的视频源
+    fn diff(&self, current: &ConfigBundle) -> Result<Vec<ConfigChange>> {
+        let new_sources = self.video_sources.keyed()?;
+        let mut old_sources = current.video_sources.keyed()?;
+        for (category, old) in old_sources.iter_mut() {
+            match (old, new_sources.get(category)) {
+                (Value::Object(old), Some(Value::Object(new))) => old.retain(|key, _| new.contains_key(key)),
+                (old, Some(Value::Null)) => *old = Value::Null,
+                _ => {}
+            }
+        }
+        let (mut old, mut new) = (BTreeMap::new(), BTreeMap::new());
+        flatten("config", serde_json::to_value(&current.config)?, &mut old);
+        flatten("config", serde_json::to_value(&self.config)?, &mut new);
+        flatten("video_sources", Value::Object(old_sources), &mut old);
+        flatten("video_sources", Value::Object(new_sources), &mut new);
+        old.remove("config.version");
+        new.remove("config.version");
+        let mut keys = old.keys().chain(new.keys()).collect::<Vec<_>>();
+        keys.sort_unstable();
+        keys.dedup();
+        Ok(keys
+            .into_iter()
+            .filter(|key| old.get(*key) != new.get(*key))
+            .map(|key| {
+                let secret = SECRET_KEYS.iter().any(|prefix| key.starts_with(prefix));
+                let mask =
+                    |value: Option<&Value>| value.map(|v| if secret { Value::from("******") } else { v.clone() });
+                ConfigChange {
+                    key: key.clone(),
+                    old: mask(old.get(key)),
+                    new: mask(new.get(key)),
+                }
+            })
+            .collect())
+    }
+}
+
+/// 将嵌套的对象展开为以点分隔的路径，数组整体作为一个值，空值视为不存在
+fn flatten(prefix: &str, value: Value, out: &mut BTreeMap<String, Value>) {
+    match value {
+        Value::Null => {}
+        Value::Object(map) => {
+            for (key, value) in map {
+                flatten(&format!("{}.{}", prefix, key), value, out);
+            }
+        }
+        value => {
+            out.insert(prefix.to_owned(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundle() -> ConfigBundle {
+        let option = |path: &str| SourceOption {
+            path: path.to_owned(),
+            enabled: true,
+            danmaku_snapshot: false,
+            rule: None,
+            trigger: None,
+        };
+        ConfigBundle {
+            secrets_excluded: false,
+            config: Config::default(),
+            video_sources: VideoSources {
+                favorites: vec![FavoriteSource {
+                    fid: 1,
+                    name: "收藏夹".to_owned(),
+                    option: SourceOption {
+                        rule: Some("title contains \"测试\"".parse().unwrap()),
+                        trigger: Some(Trigger::Cron("0 0 * * * *".to_owned())),
+                        ..option("/video/favorite")
+                    },
+                }],
+                collections: vec![CollectionSource {
+                    mid: 2,
+                    sid: 3,
+                    series: true,
+                    name: "列表".to_owned(),
+                    option: option("/video/collection"),
+                }],
+                submissions: vec![],
+                watch_later: Some(option("/video/watch_later")),
+                bangumi: vec![BangumiSource {
+                    season_id: 4,
+                    title: "番剧".to_owned(),
+                    selected_section_ids: vec![5, 6],
+                    option: option("/video/bangumi"),
+                }],
+            },
+            new_sources: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_bundle_roundtrip() {
+        let bundle = bundle();
+        for format in [BundleFormat::Toml, BundleFormat::Json] {
+            let content = bundle.dump(format).unwrap();
+            let parsed = ConfigBundle::parse(&content, format).unwrap();
+            assert_eq!(parsed.dump(format).unwrap(), content);
+            assert!(parsed.diff(&bundle).unwrap().is_empty());
+        }
+        // 规则以 dsl 文本的形式导出
+        let content = bundle.dump(BundleFormat::Toml).unwrap();
+        assert!(
+            content
+                .lines()
+                .any(|line| line.starts_with("rule = ") && line.contains("title contains"))
+        );
+        assert_eq!(BundleFormat::from_path(Path::new("bili-sync.JSON")), BundleFormat::Json);
+        assert_eq!(BundleFormat::from_path(Path::new("bili-sync.toml")), BundleFormat::Toml);
+    }
+
+    #[test]
+    fn test_bundle_diff() {
+        let current = bundle();
+        let mut current_config = current.config.clone();
+        current_config.credential.sessdata = "sessdata".to_owned();
+        current_config.version = 3;
+        let current = ConfigBundle {
+            config: current_config,
+            ..current
+        };
+        let mut incoming = bundle();
+        incoming.secrets_excluded = true;
+        incoming.config.concurrent_limit.video = 7;
+        // 文件中未包含的视频源不参与比较，名称字段的变化会被忽略
+        incoming.video_sources.watch_later = None;
+        incoming.video_sources.bangumi.clear();
+        incoming.video_sources.collections[0].name = "新的名称".to_owned();
+        incoming.video_sources.collections[0].option.enabled = false;
+        incoming.video_sources.submissions.push(SubmissionSource {
+            upper_id: 8,
+            upper_name: String::new(),
+            use_dynamic_api: false,
+            option: SourceOption {
+                path: "/video/submission".to_owned(),
+                enabled: false,
+                danmaku_snapshot: false,
+                rule: None,
+                trigger: None,
+            },
+        });
+        let incoming = incoming.resolve(&current.config);
+        assert_eq!(incoming.config.version, 3);
+        assert_eq!(incoming.config.credential.sessdata, "sessdata");
+        let changes = incoming.diff(&current).unwrap();
+        let keys = changes.iter().map(|change| change.key.as_str()).collect::<Vec<_>>();
+        assert_eq!(
+            keys,
+            [
+                "config.concurrent_limit.video",
+                "video_sources.collections.series:2:3.enabled",
+                "video_sources.submissions.8.danmaku_snapshot",
+                "video_sources.submissions.8.enabled",
+                "video_sources.submissions.8.path",
+                "video_sources.submissions.8.use_dynamic_api",
+            ]
+        );
+        assert_eq!(
+            changes[1],
+            ConfigChange {
+                key: "video_sources.collections.series:2:3.enabled".to_owned(),
+                old: Some(Value::Bool(true)),
+                new: Some(Value::Bool(false)),
+            }
+        );
+        assert!(changes[2].old.is_none());
+        // 敏感信息的差异不会展示取值
+        let mut incoming = incoming;
+        incoming.config.credential.sessdata = "other".to_owned();
+        let changes = incoming.diff(&current).unwrap();
+        let change = changes
+            .iter()
+            .find(|change| change.key == "config.credential.sessdata")
+            .unwrap();
+        assert_eq!(change.new, Some(Value::from("******")));
+    }
+
+    #[test]
+    fn test_parse_selected_section_ids() {
+        assert!(parse_selected_section_ids("").unwrap().is_empty());
+        assert_eq!(parse_selected_section_ids("[5,6]").unwrap(), vec![5, 6]);
+        assert!(parse_selected_section_ids("5,6").is_err());
+    }
+}
@@ -2,19 +2,22 @@ use std::path::PathBuf;
 use std::sync::{Arc, LazyLock};
 
 use anyhow::{Result, bail};
-use croner::parser::CronParser;
-use sea_orm::DatabaseConnection;
+use sea_orm::{ConnectionTrait, DatabaseConnection};
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
-use crate::bilibili::{Credential, DanmakuOption, FilterOption};
+use crate::bilibili::{CommentOption, Credential, DanmakuOption, FilterOption};
 use crate::config::default::{
     default_auth_token, default_bangumi_name, default_bangumi_path, default_bind_address, default_collection_path,
-    default_favorite_path, default_submission_path, default_time_format,
+    default_danmaku_snapshot_days, default_favorite_path, default_submission_path, default_time_format,
+};
+use crate::config::item::{
+    ConcurrentLimit, DownloadWindow, GlobalRule, NFOTimeType, NotificationDigest, RiskControlOption, SkipOption,
+    Trigger,
 };
-use crate::config::item::{ConcurrentLimit, NFOTimeType, SkipOption, Trigger};
 use crate::notifier::Notifier;
 use crate::utils::model::{load_db_config, save_db_config};
+use crate::utils::validation::is_valid_cron;
 
 pub static CONFIG_DIR: LazyLock<PathBuf> =
     LazyLock::new(|| dirs::config_dir().expect("No config path found").join("bili-sync"));
@@ -26,14 +29,23 @@ pub struct Config {
     pub credential: Credential,
     pub filter_option: FilterOption,
     pub danmaku_option: DanmakuOption,
+    /// 开启了弹幕快照的视频源在分页下载后的这些天数重新抓取弹幕
+    #[serde(default = "default_danmaku_snapshot_days")]
+    pub danmaku_snapshot_days: Vec<u32>,
     #[serde(default)]
     pub skip_option: SkipOption,
+    #[serde(default)]
+    pub comment_option: CommentOption,
+    #[serde(default)]
+    pub global_rule: GlobalRule,
     pub video_name: String,
     pub page_name: String,
     #[serde(default)]
     pub bangumi_name: String,
     #[serde(default)]
     pub notifiers: Option<Arc<Vec<Notifier>>>,
+    #[serde(default)]
+    pub notification_digest: NotificationDigest,
     #[serde(default = "default_favorite_path")]
     pub favorite_default_path: String,
     #[serde(default = "default_collection_path")]
@@ -46,8 +58,14 @@ pub struct Config {
     pub upper_path: PathBuf,
     pub nfo_time_type: NFOTimeType,
     pub concurrent_limit: ConcurrentLimit,
+    #[serde(default)]
+    pub risk_control: RiskControlOption,
+    /// 允许下载视频的时间段，为空时不做限制
+    #[serde(default)]
+    pub download_windows: Vec<DownloadWindow>,
     pub time_format: String,
     pub cdn_sorting: bool,
+    #[serde(default)]
     pub version: u64,
 }
 
@@ -56,7 +74,7 @@ impl Config {
         load_db_config(connection).await
     }
 
-    pub async fn save_to_database(&self, connection: &DatabaseConnection) -> Result<()> {
+    pub async fn save_to_database(&self, connection: &impl ConnectionTrait) -> Result<()> {
         save_db_config(self, connection).await
     }
 
@@ -80,8 +98,20 @@ impl Config {
         {
             errors.push("Credential 信息不完整，请确保填写完整");
         }
-        if !(self.concurrent_limit.video > 0 && self.concurrent_limit.page > 0) {
-            errors.push("video 和 page 允许的并发数必须大于 0");
+        if !(self.concurrent_limit.source > 0 && self.concurrent_limit.video > 0 && self.concurrent_limit.page > 0) {
+            errors.push("source、video 和 page 允许的并发数必须大于 0");
+        }
+        let risk_control = &self.risk_control;
+        if risk_control.enabled
+            && (risk_control.base_backoff == 0 || risk_control.max_backoff < risk_control.base_backoff)
+        {
+            errors.push("风控退避的初始等待时间必须大于 0，且不能超过最长等待时间");
+        }
+        if self.download_windows.iter().any(|window| window.weekdays.is_empty()) {
+            errors.push("下载时间段至少需要选择一个生效的星期");
+        }
+        if self.comment_option.enabled && self.comment_option.max_pages == 0 {
+            errors.push("启用评论下载时，评论页数必须大于 0");
         }
         match &self.interval {
             Trigger::Interval(secs) => {
@@ -90,17 +120,22 @@ impl Config {
                 }
             }
             Trigger::Cron(cron) => {
-                if CronParser::builder()
-                    .seconds(croner::parser::Seconds::Required)
-                    .dom_and_dow(true)
-                    .build()
-                    .parse(cron)
-                    .is_err()
-                {
+                if !is_valid_cron(cron) {
                     errors.push("Cron 表达式无效，正确格式为“秒 分 时 日 月 周”");
                 }
             }
         };
+        if self.danmaku_snapshot_days.is_empty()
+            || self.danmaku_snapshot_days[0] == 0
+            || !self.danmaku_snapshot_days.is_sorted_by(|a, b| a < b)
+        {
+            errors.push("弹幕快照的天数需要为递增的正整数");
+        }
+        if let NotificationDigest::Cron(cron) = &self.notification_digest
+            && !is_valid_cron(cron)
+        {
+            errors.push("通知汇总的 Cron 表达式无效，正确格式为“秒 分 时 日 月 周”");
+        }
         if !errors.is_empty() {
             bail!(
                 errors
@@ -122,11 +157,15 @@ impl Default for Config {
             credential: Credential::default(),
             filter_option: FilterOption::default(),
             danmaku_option: DanmakuOption::default(),
+            danmaku_snapshot_days: default_danmaku_snapshot_days(),
             skip_option: SkipOption::default(),
+            comment_option: CommentOption::default(),
+            global_rule: GlobalRule::default(),
             video_name: "{{title}}".to_owned(),
             page_name: "{{title}}".to_owned(),
             bangumi_name: default_bangumi_name(),
             notifiers: None,
+            notification_digest: NotificationDigest::default(),
             favorite_default_path: default_favorite_path(),
             collection_default_path: default_collection_path(),
             submission_default_path: default_submission_path(),
@@ -135,6 +174,8 @@ impl Default for Config {
             upper_path: CONFIG_DIR.join("upper_face"),
             nfo_time_type: NFOTimeType::FavTime,
             concurrent_limit: ConcurrentLimit::default(),
+            risk_control: RiskControlOption::default(),
+            download_windows: Vec::new(),
             time_format: default_time_format(),
             cdn_sorting: false,
             version: 0,
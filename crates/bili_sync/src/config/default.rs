@@ -33,6 +33,16 @@ pub fn default_bangumi_path() -> String {
     "".to_owned()
 }
 
+/// 弹幕快照的抓取时间，以分页下载后经过的天数计
+pub fn default_danmaku_snapshot_days() -> Vec<u32> {
+    vec![1, 7, 30]
+}
+
 pub fn default_bangumi_name() -> String {
     "{{title}} - S{{season_pad}}E{{pid_pad}}".to_owned()
 }
+
+/// 默认同时只处理一个视频源，与旧版本的行为保持一致
+pub(super) fn default_source_concurrency() -> usize {
+    1
+}
@@ -1,7 +1,14 @@
 use std::sync::LazyLock;
 
 use anyhow::Result;
-use handlebars::handlebars_helper;
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use handlebars::{
+    Context, Handlebars, Helper, HelperResult, Output, RenderContext, RenderErrorReason, handlebars_helper,
+};
+use regex::Regex;
+use serde_json::Value;
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
 
 use crate::config::versioned_cache::VersionedCache;
 use crate::config::{Config, PathSafeTemplate};
@@ -12,7 +19,7 @@ pub static TEMPLATE: LazyLock<VersionedCache<handlebars::Handlebars<'static>>> =
 
 fn create_template(config: &Config) -> Result<handlebars::Handlebars<'static>> {
     let mut handlebars = handlebars::Handlebars::new();
-    handlebars.register_helper("truncate", Box::new(truncate));
+    register_helpers(&mut handlebars);
     handlebars.path_safe_register("video", config.video_name.clone())?;
     handlebars.path_safe_register("page", config.page_name.clone())?;
     handlebars.path_safe_register("bangumi", config.bangumi_name.clone())?;
@@ -30,6 +37,34 @@ fn create_template(config: &Config) -> Result<handlebars::Handlebars<'static>> {
     Ok(handlebars)
 }
 
+pub fn register_helpers(handlebars: &mut handlebars::Handlebars<'_>) {
+    handlebars.register_helper("truncate", Box::new(truncate));
+    handlebars.register_helper("date", Box::new(date));
+    handlebars.register_helper("pad", Box::new(pad));
+    handlebars.register_helper("replace", Box::new(replace));
+    handlebars.register_helper("regex_replace", Box::new(regex_replace));
+    handlebars.register_helper("lower", Box::new(lower));
+    handlebars.register_helper("upper", Box::new(upper));
+    handlebars.register_helper("ascii", Box::new(ascii));
+    handlebars.register_helper("slug", Box::new(slug));
+    handlebars.register_helper("default", Box::new(default));
+    handlebars.register_helper("json", Box::new(json));
+}
+
+// {{json message}}，输出带引号并转义后的 JSON 值，用于在 webhook 模板中嵌入任意文本
+// 结果需要原样输出，不能经过 html 转义，因此不使用 handlebars_helper!
+fn json(
+    h: &Helper<'_>,
+    _: &Handlebars<'_>,
+    _: &Context,
+    _: &mut RenderContext<'_, '_>,
+    out: &mut dyn Output,
+) -> HelperResult {
+    let value = h.param(0).ok_or(RenderErrorReason::ParamNotFoundForIndex("json", 0))?;
+    out.write(&serde_json::to_string(value.value()).map_err(RenderErrorReason::SerdeError)?)?;
+    Ok(())
+}
+
 handlebars_helper!(truncate: |s: String, len: usize| {
     if s.chars().count() > len {
         s.chars().take(len).collect::<String>()
@@ -38,6 +73,128 @@ handlebars_helper!(truncate: |s: String, len: usize| {
     }
 });
 
+// {{date pubtime_ts "%Y-%m"}}，支持时间戳（秒）与常见格式的时间字符串
+handlebars_helper!(date: |value: Json, pattern: str| {
+    let datetime = match value {
+        Value::Number(ts) => ts.as_i64().and_then(|ts| DateTime::from_timestamp(ts, 0)).map(|dt| dt.naive_utc()),
+        Value::String(s) => parse_datetime(s),
+        _ => None,
+    };
+    match datetime {
+        Some(datetime) => datetime.format(pattern).to_string(),
+        None => return Err(RenderErrorReason::Other(format!("无法解析时间：{}", value)).into()),
+    }
+});
+
+// {{pad pid 3}} => 001，可通过 fill="_" 指定填充字符
+handlebars_helper!(pad: |value: Json, width: usize, {fill: str = "0"}| {
+    let value = json_to_string(value);
+    let fill = fill.chars().next().unwrap_or('0');
+    let len = value.chars().count();
+    let mut padded = String::with_capacity(value.len() + width.saturating_sub(len));
+    padded.extend(std::iter::repeat_n(fill, width.saturating_sub(len)));
+    padded.push_str(&value);
+    padded
+});
+
+handlebars_helper!(replace: |s: str, from: str, to: str| s.replace(from, to));
+
+handlebars_helper!(regex_replace: |s: str, pattern: str, replacement: str| {
+    match Regex::new(pattern) {
+        Ok(regex) => regex.replace_all(s, replacement).into_owned(),
+        Err(e) => return Err(RenderErrorReason::Other(format!("正则表达式 {} 无效：{}", pattern, e)).into()),
+    }
+});
+
+handlebars_helper!(lower: |s: str| s.to_lowercase());
+
+handlebars_helper!(upper: |s: str| s.to_uppercase());
+
+handlebars_helper!(ascii: |s: str| transliterate(s));
+
+// 转写为 ascii 后仅保留小写字母与数字，其余字符以 - 连接
+handlebars_helper!(slug: |s: str| {
+    let mut result = String::with_capacity(s.len());
+    for ch in transliterate(s).chars() {
+        if ch.is_ascii_alphanumeric() {
+            result.push(ch.to_ascii_lowercase());
+        } else if !result.is_empty() && !result.ends_with('-') {
+            result.push('-');
+        }
+    }
+    result.trim_end_matches('-').to_owned()
+});
+
+// {{default partition "未分区"}}，值为空字符串、null 或不存在时使用默认值
+handlebars_helper!(default: |value: Json, fallback: Json| {
+    match value {
+        Value::Null => fallback.clone(),
+        Value::String(s) if s.is_empty() => fallback.clone(),
+        Value::Array(arr) if arr.is_empty() => fallback.clone(),
+        _ => value.clone(),
+    }
+});
+
+fn parse_datetime(s: &str) -> Option<NaiveDateTime> {
+    let s = s.trim();
+    DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.naive_utc())
+        .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S"))
+        .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S"))
+        .or_else(|_| NaiveDate::parse_from_str(s, "%Y-%m-%d").map(|d| d.and_time(Default::default())))
+        .ok()
+}
+
+fn json_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// 将文本尽量转写为 ascii：去除变音符号、全角字符转半角、常见中文标点转为对应的英文标点，
+/// 无法转写的字符（如汉字）会被丢弃
+fn transliterate(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    for ch in s.chars() {
+        let replacement = match ch {
+            'ß' => "ss",
+            'æ' => "ae",
+            'Æ' => "AE",
+            'œ' => "oe",
+            'Œ' => "OE",
+            'ø' => "o",
+            'Ø' => "O",
+            'đ' => "d",
+            'Đ' => "D",
+            'ł' => "l",
+            'Ł' => "L",
+            'þ' => "th",
+            'Þ' => "TH",
+            '，' | '、' => ",",
+            '。' => ".",
+            '：' => ":",
+            '；' => ";",
+            '！' => "!",
+            '？' => "?",
+            '（' | '【' | '「' | '『' | '《' => "(",
+            '）' | '】' | '」' | '』' | '》' => ")",
+            '“' | '”' => "\"",
+            '‘' | '’' => "'",
+            '—' | '～' => "-",
+            '\u{3000}' => " ",
+            _ => {
+                // NFKD 分解后，全角字符会变为半角，带变音符号的字母会拆为基础字母与组合符号
+                result.extend(ch.nfkd().filter(|c| c.is_ascii() && !is_combining_mark(*c)));
+                continue;
+            }
+        };
+        result.push_str(replacement);
+    }
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use serde_json::json;
@@ -47,7 +204,7 @@ mod tests {
     #[test]
     fn test_template_usage() {
         let mut template = handlebars::Handlebars::new();
-        template.register_helper("truncate", Box::new(truncate));
+        register_helpers(&mut template);
         let _ = template.path_safe_register("video", "test{{bvid}}test");
         let _ = template.path_safe_register("test_truncate", "哈哈，{{ truncate title 30 }}");
         let _ = template.path_safe_register("test_path_unix", "{{ truncate title 7 }}/test/a");
@@ -101,4 +258,49 @@ mod tests {
             "哈哈，你说得对，但是 Rust 是由 Mozilla 自主研发的一"
         );
     }
+
+    #[test]
+    fn test_template_helpers() {
+        let mut template = handlebars::Handlebars::new();
+        register_helpers(&mut template);
+        let data = json!({
+            "title": "Café Déjà Vu：第２集（完结）",
+            "pid": 7,
+            "pubtime_ts": 1700000000,
+            "fav_time": "2024-02-29 08:30:00",
+            "partition": "",
+            "season_id": "12345",
+        });
+        let testcases = [
+            (r#"{{date pubtime_ts "%Y/%m"}}"#, "2023/11"),
+            (r#"{{date fav_time "%Y%m%d"}}"#, "20240229"),
+            ("{{pad pid 3}}", "007"),
+            (r#"{{pad pid 3 fill="_"}}"#, "__7"),
+            (r#"{{replace title "Café" "Coffee"}}"#, "Coffee Déjà Vu：第２集（完结）"),
+            (r#"{{regex_replace title "（.*）" ""}}"#, "Café Déjà Vu：第２集"),
+            ("{{lower (ascii title)}}", "cafe deja vu:2()"),
+            ("{{upper (ascii title)}}", "CAFE DEJA VU:2()"),
+            ("{{slug title}}", "cafe-deja-vu-2"),
+            (r#"{{default partition "未分区"}}"#, "未分区"),
+            (r#"{{default missing "无"}}-{{default season_id "无"}}"#, "无-12345"),
+            (
+                r#"{{#if season_id}}S{{pad season_id 6}}{{else}}normal{{/if}}"#,
+                "S012345",
+            ),
+        ];
+        for (tpl, expected) in testcases {
+            assert_eq!(
+                template.render_template(tpl, &data).unwrap(),
+                expected,
+                "template: {}",
+                tpl
+            );
+        }
+        assert!(template.render_template(r#"{{date title "%Y"}}"#, &data).is_err());
+        assert!(
+            template
+                .render_template(r#"{{regex_replace title "(" ""}}"#, &data)
+                .is_err()
+        );
+    }
 }
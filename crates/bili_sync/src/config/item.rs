@@ -1,6 +1,11 @@
 use anyhow::Result;
-use serde::{Deserialize, Serialize};
+use bili_sync_entity::rule::Rule;
+pub use bili_sync_entity::trigger::Trigger;
+use chrono::{NaiveTime, Weekday};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+use crate::config::default::default_source_concurrency;
 use crate::utils::filenamify::filenamify;
 
 /// NFO 文件使用的时间类型
@@ -15,6 +20,9 @@ pub enum NFOTimeType {
 /// 并发下载相关的配置
 #[derive(Serialize, Deserialize, Clone)]
 pub struct ConcurrentLimit {
+    /// 同时处理的视频源数量
+    #[serde(default = "default_source_concurrency")]
+    pub source: usize,
     pub video: usize,
     pub page: usize,
     pub rate_limit: Option<RateLimit>,
@@ -45,16 +53,58 @@ pub struct RateLimit {
     pub duration: u64,
 }
 
+/// 默认的限速配置，每 250ms 允许请求 4 次
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            limit: 4,
+            duration: 250,
+        }
+    }
+}
+
+/// 触发风控后的自适应退避策略
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RiskControlOption {
+    /// 是否启用自适应退避，关闭时触发风控后仅终止本轮任务，等待下一次定时执行
+    pub enabled: bool,
+    /// 首次触发风控后等待多少秒自动重试，之后每次连续触发翻倍
+    pub base_backoff: u64,
+    /// 自动重试的最长等待秒数
+    pub max_backoff: u64,
+    /// 自动重试后持续多少秒未再触发风控即恢复正常请求速率
+    pub recovery_period: u64,
+}
+
+impl Default for RiskControlOption {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            base_backoff: 10 * 60,
+            max_backoff: 6 * 60 * 60,
+            recovery_period: 2 * 60 * 60,
+        }
+    }
+}
+
+/// 允许下载视频的时间段
+///
+/// 结束时间不晚于开始时间时表示跨越午夜，结束于次日；两者相同时表示全天
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DownloadWindow {
+    /// 时间段生效的星期，跨越午夜的时间段以开始时间所在的星期为准
+    pub weekdays: Vec<Weekday>,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
 impl Default for ConcurrentLimit {
     fn default() -> Self {
         Self {
+            source: default_source_concurrency(),
             video: 3,
             page: 2,
-            // 默认的限速配置，每 250ms 允许请求 4 次
-            rate_limit: Some(RateLimit {
-                limit: 4,
-                duration: 250,
-            }),
+            rate_limit: Some(RateLimit::default()),
             download: ConcurrentDownloadLimit::default(),
         }
     }
@@ -69,17 +119,57 @@ pub struct SkipOption {
     pub no_subtitle: bool,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
-#[serde(untagged)]
-pub enum Trigger {
-    Interval(u64),
-    Cron(String),
+/// 对所有视频源生效的全局规则，与视频源自身的规则组合使用：
+/// 命中 deny 的视频一律不下载；未命中 deny 时，命中 allow 或满足视频源规则的视频会被下载，
+/// 即 allow 会覆盖视频源规则，而不是在其基础上追加限制
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct GlobalRule {
+    #[serde(
+        default,
+        serialize_with = "serialize_rule_dsl",
+        deserialize_with = "deserialize_rule_dsl"
+    )]
+    pub deny: Option<Rule>,
+    #[serde(
+        default,
+        serialize_with = "serialize_rule_dsl",
+        deserialize_with = "deserialize_rule_dsl"
+    )]
+    pub allow: Option<Rule>,
 }
 
-impl Default for Trigger {
-    fn default() -> Self {
-        Trigger::Interval(1200)
+/// 全局规则以 dsl 文本的形式保存，便于在配置中直接编辑
+pub(super) fn serialize_rule_dsl<S: Serializer>(rule: &Option<Rule>, serializer: S) -> Result<S::Ok, S::Error> {
+    rule.as_ref().map(Rule::to_dsl).serialize(serializer)
+}
+
+/// 兼容 dsl 文本与结构化的规则，空文本视为未设置
+pub(super) fn deserialize_rule_dsl<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Rule>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum RuleInput {
+        Text(String),
+        Rule(Rule),
     }
+    match Option::<RuleInput>::deserialize(deserializer)? {
+        Some(RuleInput::Text(text)) if text.trim().is_empty() => Ok(None),
+        Some(RuleInput::Text(text)) => text.parse().map(Some).map_err(D::Error::custom),
+        Some(RuleInput::Rule(rule)) => Ok(Some(rule)),
+        None => Ok(None),
+    }
+}
+
+/// 视频下载成功与失败通知的汇总方式
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationDigest {
+    /// 不汇总，每个视频单独发送通知
+    #[default]
+    Off,
+    /// 每轮下载任务结束时发送一条汇总
+    PerRun,
+    /// 按照 Cron 表达式定时发送汇总
+    Cron(String),
 }
 
 pub trait PathSafeTemplate {
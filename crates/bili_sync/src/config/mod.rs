@@ -1,4 +1,5 @@
 mod args;
+mod bundle;
 mod current;
 mod default;
 mod handlebar;
@@ -7,9 +8,13 @@ mod versioned_cache;
 mod versioned_config;
 
 pub use crate::config::args::{ARGS, version};
+pub use crate::config::bundle::{BundleFormat, ConfigBundle, ConfigChange};
 pub use crate::config::current::{CONFIG_DIR, Config};
 pub(crate) use crate::config::default::default_bind_address;
-pub use crate::config::handlebar::TEMPLATE;
-pub use crate::config::item::{ConcurrentDownloadLimit, NFOTimeType, PathSafeTemplate, RateLimit, Trigger};
+pub use crate::config::handlebar::{TEMPLATE, register_helpers};
+pub use crate::config::item::{
+    ConcurrentDownloadLimit, DownloadWindow, GlobalRule, NFOTimeType, NotificationDigest, PathSafeTemplate, RateLimit,
+    RiskControlOption, Trigger,
+};
 pub use crate::config::versioned_cache::VersionedCache;
 pub use crate::config::versioned_config::VersionedConfig;
@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use anyhow::{Result, bail};
 use arc_swap::{ArcSwap, Guard};
-use sea_orm::DatabaseConnection;
+use sea_orm::{DatabaseConnection, DatabaseTransaction, TransactionTrait};
 use tokio::sync::{OnceCell, watch};
 
 use crate::bilibili::Credential;
@@ -110,14 +110,20 @@ impl VersionedConfig {
     }
 
     /// 外部 API 会调用这个方法，如果更新失败直接返回错误
-    pub async fn update(&self, mut new_config: Config, connection: &DatabaseConnection) -> Result<Arc<Config>> {
+    pub async fn update(&self, new_config: Config, connection: &DatabaseConnection) -> Result<Arc<Config>> {
+        self.update_in_txn(new_config, connection.begin().await?).await
+    }
+
+    /// 在事务中保存配置并提交，事务中已有的其它修改与配置一同生效，提交成功后才会更新内存中的配置
+    pub async fn update_in_txn(&self, mut new_config: Config, txn: DatabaseTransaction) -> Result<Arc<Config>> {
         let _lock = self.update_lock.lock().await;
         let old_config = self.inner.load();
         if old_config.version != new_config.version {
             bail!("配置版本不匹配，请刷新页面修改后重新提交");
         }
         new_config.version += 1;
-        new_config.save_to_database(connection).await?;
+        new_config.save_to_database(&txn).await?;
+        txn.commit().await?;
         let new_config = Arc::new(new_config);
         self.inner.store(new_config.clone());
         self.tx.send(new_config.clone())?;
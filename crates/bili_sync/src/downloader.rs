@@ -1,11 +1,12 @@
 use core::str;
 use std::io::SeekFrom;
 use std::path::Path;
+use std::process::Output;
 use std::sync::Arc;
 
 use anyhow::{Context, Result, bail, ensure};
 use async_tempfile::TempFile;
-use futures::TryStreamExt;
+use futures::{Stream, TryStreamExt};
 use reqwest::{Method, StatusCode, header};
 use tokio::fs::{self};
 use tokio::io::{AsyncSeekExt, AsyncWriteExt};
@@ -15,6 +16,7 @@ use tokio_util::io::StreamReader;
 
 use crate::bilibili::Client;
 use crate::config::ConcurrentDownloadLimit;
+use crate::utils::progress::{ByteCounter, Progress};
 
 pub struct Downloader {
     client: Client,
@@ -30,7 +32,7 @@ impl Downloader {
 
     pub async fn fetch(&self, url: &str, path: &Path, concurrent_download: &ConcurrentDownloadLimit) -> Result<()> {
         let mut temp_file = TempFile::new().await?;
-        self.fetch_internal(url, &mut temp_file, false, concurrent_download)
+        self.fetch_internal(url, &mut temp_file, false, concurrent_download, None)
             .await?;
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent).await?;
@@ -47,9 +49,20 @@ impl Downloader {
         &self,
         urls: &[&str],
         path: &Path,
+        ffmetadata: Option<&str>,
         concurrent_download: &ConcurrentDownloadLimit,
+        progress: Option<&Arc<Progress>>,
     ) -> Result<()> {
-        let temp_file = self.multi_fetch_internal(urls, true, concurrent_download).await?;
+        let temp_file = self
+            .multi_fetch_internal(urls, true, concurrent_download, progress)
+            .await?;
+        if ffmetadata.is_some() {
+            // 存在章节时需要经过 ffmpeg 重新封装写入
+            if let Some(progress) = progress {
+                progress.set_merging();
+            }
+            return Self::mux(vec![temp_file], path, ffmetadata).await;
+        }
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent).await?;
         }
@@ -63,31 +76,57 @@ impl Downloader {
         video_urls: &[&str],
         audio_urls: &[&str],
         path: &Path,
+        ffmetadata: Option<&str>,
         concurrent_download: &ConcurrentDownloadLimit,
+        progress: Option<&Arc<Progress>>,
     ) -> Result<()> {
         let (video_temp_file, audio_temp_file) = tokio::try_join!(
-            self.multi_fetch_internal(video_urls, true, concurrent_download),
-            self.multi_fetch_internal(audio_urls, true, concurrent_download)
+            self.multi_fetch_internal(video_urls, true, concurrent_download, progress),
+            self.multi_fetch_internal(audio_urls, true, concurrent_download, progress)
         )?;
+        if let Some(progress) = progress {
+            progress.set_merging();
+        }
+        Self::mux(vec![video_temp_file, audio_temp_file], path, ffmetadata).await
+    }
+
+    /// 使用 ffmpeg 将输入的音视频流封装为 mp4，提供 ffmetadata 时一并写入章节
+    async fn mux(inputs: Vec<TempFile>, path: &Path, ffmetadata: Option<&str>) -> Result<()> {
+        // ffmetadata 仅在封装时使用，写入临时文件，不在视频目录中留下侧车文件
+        let ffmetadata_temp_file = match ffmetadata {
+            Some(ffmetadata) => {
+                let mut temp_file = TempFile::new().await?;
+                temp_file.write_all(ffmetadata.as_bytes()).await?;
+                temp_file.flush().await?;
+                Some(temp_file)
+            }
+            None => None,
+        };
         let final_temp_file = TempFile::new().await?;
-        let output = Command::new("ffmpeg")
-            .args([
-                "-i",
-                video_temp_file.file_path().to_string_lossy().as_ref(),
+        let mut command = Command::new("ffmpeg");
+        for input in &inputs {
+            command.args(["-i", input.file_path().to_string_lossy().as_ref()]);
+        }
+        if let Some(ffmetadata_temp_file) = &ffmetadata_temp_file {
+            // 最后一个输入为 ffmetadata，仅从中读取章节信息
+            command.args([
                 "-i",
-                audio_temp_file.file_path().to_string_lossy().as_ref(),
-                "-c",
-                "copy",
-                "-strict",
-                "unofficial",
-                "-f",
-                "mp4",
-                "-y",
-                final_temp_file.file_path().to_string_lossy().as_ref(),
-            ])
-            .output()
-            .await
-            .context("failed to run ffmpeg")?;
+                ffmetadata_temp_file.file_path().to_string_lossy().as_ref(),
+                "-map_chapters",
+                inputs.len().to_string().as_str(),
+            ]);
+        }
+        command.args([
+            "-c",
+            "copy",
+            "-strict",
+            "unofficial",
+            "-f",
+            "mp4",
+            "-y",
+            final_temp_file.file_path().to_string_lossy().as_ref(),
+        ]);
+        let output = run_command(command).await.context("failed to run ffmpeg")?;
         if !output.status.success() {
             bail!("ffmpeg error: {}", str::from_utf8(&output.stderr).unwrap_or("unknown"));
         }
@@ -96,9 +135,13 @@ impl Downloader {
         }
         fs::copy(final_temp_file.file_path(), path).await?;
         tokio::join!(
-            video_temp_file.drop_async(),
-            audio_temp_file.drop_async(),
-            final_temp_file.drop_async()
+            futures::future::join_all(inputs.into_iter().map(TempFile::drop_async)),
+            final_temp_file.drop_async(),
+            async {
+                if let Some(ffmetadata_temp_file) = ffmetadata_temp_file {
+                    ffmetadata_temp_file.drop_async().await;
+                }
+            }
         );
         Ok(())
     }
@@ -108,18 +151,24 @@ impl Downloader {
         urls: &[&str],
         is_stream: bool,
         concurrent_download: &ConcurrentDownloadLimit,
+        progress: Option<&Arc<Progress>>,
     ) -> Result<TempFile> {
         if urls.is_empty() {
             bail!("no urls provided");
         }
         let mut temp_file = TempFile::new().await?;
         for (idx, url) in urls.iter().enumerate() {
+            // 每个地址单独计数，失败后撤销已计入的进度再尝试下一个地址
+            let counter = progress.map(|progress| progress.counter());
             match self
-                .fetch_internal(url, &mut temp_file, is_stream, concurrent_download)
+                .fetch_internal(url, &mut temp_file, is_stream, concurrent_download, counter.as_ref())
                 .await
             {
                 Ok(_) => return Ok(temp_file),
                 Err(e) => {
+                    if let Some(counter) = &counter {
+                        counter.rollback();
+                    }
                     if idx == urls.len() - 1 {
                         temp_file.drop_async().await;
                         return Err(e).with_context(|| format!("failed to download file from all {} urls", urls.len()));
@@ -138,15 +187,17 @@ impl Downloader {
         file: &mut TempFile,
         is_stream: bool,
         concurrent_download: &ConcurrentDownloadLimit,
+        counter: Option<&Arc<ByteCounter>>,
     ) -> Result<()> {
         if concurrent_download.enable {
-            self.fetch_parallel(url, file, is_stream, concurrent_download).await
+            self.fetch_parallel(url, file, is_stream, concurrent_download, counter)
+                .await
         } else {
-            self.fetch_serial(url, file).await
+            self.fetch_serial(url, file, counter).await
         }
     }
 
-    async fn fetch_serial(&self, url: &str, file: &mut TempFile) -> Result<()> {
+    async fn fetch_serial(&self, url: &str, file: &mut TempFile, counter: Option<&Arc<ByteCounter>>) -> Result<()> {
         let resp = self
             .client
             .request(Method::GET, url, None)
@@ -154,7 +205,10 @@ impl Downloader {
             .await?
             .error_for_status()?;
         let expected = resp.header_content_length();
-        let mut stream_reader = StreamReader::new(resp.bytes_stream().map_err(std::io::Error::other));
+        if let (Some(counter), Some(expected)) = (counter, expected) {
+            counter.add_total(expected);
+        }
+        let mut stream_reader = StreamReader::new(counted(resp.bytes_stream(), counter.cloned()));
         let received = tokio::io::copy(&mut stream_reader, file).await?;
         file.flush().await?;
         if let Some(expected) = expected {
@@ -174,6 +228,7 @@ impl Downloader {
         file: &mut TempFile,
         is_stream: bool,
         concurrent_download: &ConcurrentDownloadLimit,
+        counter: Option<&Arc<ByteCounter>>,
     ) -> Result<()> {
         let (concurrency, threshold) = (concurrent_download.concurrency, concurrent_download.threshold);
         let file_size = if is_stream {
@@ -186,7 +241,7 @@ impl Downloader {
                 .await?
                 .error_for_status()?;
             if resp.status() != StatusCode::PARTIAL_CONTENT {
-                return self.fetch_serial(url, file).await;
+                return self.fetch_serial(url, file, counter).await;
             }
             resp.header_file_size()
         } else {
@@ -203,18 +258,21 @@ impl Downloader {
                 // https://developer.mozilla.org/en-US/docs/Web/HTTP/Reference/Headers/Accept-Ranges#none
                 .is_none_or(|v| v.to_str().unwrap_or_default() == "none")
             {
-                return self.fetch_serial(url, file).await;
+                return self.fetch_serial(url, file, counter).await;
             }
             resp.header_content_length()
         };
         let Some(file_size) = file_size else {
-            return self.fetch_serial(url, file).await;
+            return self.fetch_serial(url, file, counter).await;
         };
         let chunk_size = file_size / concurrency as u64;
         if chunk_size < threshold {
-            return self.fetch_serial(url, file).await;
+            return self.fetch_serial(url, file, counter).await;
         }
         file.set_len(file_size).await?;
+        if let Some(counter) = counter {
+            counter.add_total(file_size);
+        }
         let mut tasks = JoinSet::new();
         let url = Arc::new(url.to_string());
         for i in 0..concurrency {
@@ -224,7 +282,7 @@ impl Downloader {
             } else {
                 start + chunk_size
             } - 1;
-            let (url_clone, client_clone) = (url.clone(), self.client.clone());
+            let (url_clone, client_clone, counter) = (url.clone(), self.client.clone(), counter.cloned());
             let mut file_clone = file.open_rw().await?;
             tasks.spawn(async move {
                 file_clone.seek(SeekFrom::Start(start)).await?;
@@ -243,7 +301,7 @@ impl Downloader {
                         content_length
                     );
                 }
-                let mut stream_reader = StreamReader::new(resp.bytes_stream().map_err(std::io::Error::other));
+                let mut stream_reader = StreamReader::new(counted(resp.bytes_stream(), counter));
                 let received = tokio::io::copy(&mut stream_reader, &mut file_clone).await?;
                 file_clone.flush().await?;
                 ensure!(
@@ -262,6 +320,18 @@ impl Downloader {
     }
 }
 
+/// 将响应流的错误转换为 io::Error，并在接收到数据时更新下载进度
+fn counted<B: AsRef<[u8]>>(
+    stream: impl Stream<Item = reqwest::Result<B>>,
+    counter: Option<Arc<ByteCounter>>,
+) -> impl Stream<Item = std::io::Result<B>> {
+    stream.map_err(std::io::Error::other).inspect_ok(move |chunk| {
+        if let Some(counter) = &counter {
+            counter.advance(chunk.as_ref().len() as u64);
+        }
+    })
+}
+
 /// reqwest.content_length() 居然指的是 body_size 而非 content-length header，没办法自己实现一下
 /// https://github.com/seanmonstar/reqwest/issues/1814
 trait ResponseExt {
@@ -291,16 +361,62 @@ impl ResponseExt for reqwest::Response {
     }
 }
 
+/// 执行外部命令并等待其结束，任务被暂停或取消导致 future 被丢弃时一并结束子进程，避免其继续占用临时文件
+async fn run_command(mut command: Command) -> std::io::Result<Output> {
+    command.kill_on_drop(true).output().await
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::Path;
 
     use anyhow::Result;
 
+    use async_tempfile::TempFile;
+    use tokio::process::Command;
+    use tokio_util::future::FutureExt;
+    use tokio_util::sync::CancellationToken;
+
     use crate::bilibili::{BestStream, BiliClient, Video};
     use crate::config::VersionedConfig;
     use crate::database::setup_database;
-    use crate::downloader::Downloader;
+    use crate::downloader::{Downloader, run_command};
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_command_killed_on_cancel() {
+        // 用 sleep 模拟耗时的合并，记录子进程的 pid 用于确认其已结束
+        let pid_file = TempFile::new().await.unwrap();
+        let mut command = Command::new("sh");
+        command.args([
+            "-c",
+            &format!("echo $$ > '{}'; exec sleep 30", pid_file.file_path().display()),
+        ]);
+        let token = CancellationToken::new();
+        tokio::spawn({
+            let token = token.clone();
+            async move {
+                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                token.cancel();
+            }
+        });
+        // 与 workflow 中的 cancellable 相同，取消时直接丢弃执行中的 future
+        assert!(run_command(command).with_cancellation_token(&token).await.is_none());
+        let pid = tokio::fs::read_to_string(pid_file.file_path()).await.unwrap();
+        let stat_path = format!("/proc/{}/stat", pid.trim());
+        let mut exited = false;
+        for _ in 0..50 {
+            // 被结束的子进程在回收前处于僵尸状态
+            match tokio::fs::read_to_string(&stat_path).await {
+                Ok(stat) if !stat.contains(") Z ") => tokio::time::sleep(std::time::Duration::from_millis(100)).await,
+                _ => {
+                    exited = true;
+                    break;
+                }
+            }
+        }
+        assert!(exited, "子进程在任务取消后仍在运行");
+    }
 
     #[ignore = "only for manual test"]
     #[tokio::test(flavor = "multi_thread")]
@@ -335,7 +451,9 @@ mod tests {
                 &video.urls(true),
                 &audio.urls(true),
                 Path::new("./output.mp4"),
+                None,
                 &config.concurrent_limit.download,
+                None,
             )
             .await
             .expect("failed to download video");
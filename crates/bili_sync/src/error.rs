@@ -7,6 +7,8 @@ pub enum ExecutionStatus {
     Succeeded,
     Ignored(anyhow::Error),
     Failed(anyhow::Error),
+    // 任务被暂停或取消而中断，不计入失败次数，之后重新执行
+    Cancelled,
     // 任务可以返回该状态固定自己的 status
     Fixed(u32),
 }
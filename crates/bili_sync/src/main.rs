@@ -4,6 +4,7 @@ extern crate tracing;
 mod adapter;
 mod api;
 mod bilibili;
+mod cli;
 mod config;
 mod database;
 mod downloader;
@@ -16,6 +17,7 @@ mod workflow;
 use std::collections::VecDeque;
 use std::fmt::Debug;
 use std::future::Future;
+use std::process::ExitCode;
 use std::sync::Arc;
 
 use bilibili::BiliClient;
@@ -29,11 +31,17 @@ use crate::api::{LogHelper, MAX_HISTORY_LOGS};
 use crate::config::{ARGS, CONFIG_DIR, VersionedConfig};
 use crate::database::setup_database;
 use crate::utils::init_logger;
+use crate::utils::instance_lock::InstanceLock;
 use crate::utils::signal::terminate;
 
 #[tokio::main]
-async fn main() {
-    let (connection, log_writer) = init().await;
+async fn main() -> ExitCode {
+    if let Some(command) = &ARGS.command {
+        return cli::run(command).await;
+    }
+    let Some((connection, log_writer, _lock)) = init().await else {
+        return ExitCode::FAILURE;
+    };
     let bili_client = Arc::new(BiliClient::new());
 
     let token = CancellationToken::new();
@@ -54,7 +62,8 @@ async fn main() {
     );
 
     tracker.close();
-    handle_shutdown(connection, tracker, token).await
+    handle_shutdown(connection, tracker, token).await;
+    ExitCode::SUCCESS
 }
 
 fn spawn_task(
@@ -76,8 +85,8 @@ fn spawn_task(
     });
 }
 
-/// 初始化日志系统、打印欢迎信息，初始化数据库连接和全局配置
-async fn init() -> (DatabaseConnection, LogHelper) {
+/// 初始化日志系统、打印欢迎信息，锁定配置目录，初始化数据库连接和全局配置，已有其它实例运行时返回 None
+async fn init() -> Option<(DatabaseConnection, LogHelper, Option<InstanceLock>)> {
     let (tx, _rx) = tokio::sync::broadcast::channel(30);
     let log_history = Arc::new(RwLock::new(VecDeque::with_capacity(MAX_HISTORY_LOGS + 1)));
     let log_writer = LogHelper::new(tx, log_history.clone());
@@ -85,6 +94,13 @@ async fn init() -> (DatabaseConnection, LogHelper) {
     init_logger(&ARGS.log_level, Some(log_writer.clone()));
     info!("欢迎使用 Bili-Sync，当前程序版本：{}", config::version());
     info!("项目地址：https://github.com/amtoaer/bili-sync");
+    let lock = match InstanceLock::acquire() {
+        Ok(lock) => lock,
+        Err(e) => {
+            error!("{:#}", e);
+            return None;
+        }
+    };
     let connection = setup_database(&CONFIG_DIR.join("data.sqlite"))
         .await
         .expect("数据库初始化失败");
@@ -92,7 +108,7 @@ async fn init() -> (DatabaseConnection, LogHelper) {
     VersionedConfig::init(&connection).await.expect("配置初始化失败");
     info!("配置初始化完成");
 
-    (connection, log_writer)
+    Some((connection, log_writer, lock))
 }
 
 async fn handle_shutdown(connection: DatabaseConnection, tracker: TaskTracker, token: CancellationToken) {
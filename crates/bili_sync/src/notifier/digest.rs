@@ -0,0 +1,248 @@
+use std::path::PathBuf;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use super::NotificationEvent;
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct DigestVideo {
+    pub bvid: String,
+    pub title: String,
+    pub upper: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+/// 同一视频源下的视频列表
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct DigestGroup {
+    pub source: String,
+    pub videos: Vec<DigestVideo>,
+}
+
+/// 尚未发送的汇总内容
+#[derive(Default, Deserialize, Serialize)]
+struct PendingDigest {
+    downloaded: Vec<DigestGroup>,
+    failed: Vec<DigestGroup>,
+}
+
+/// 暂存开启汇总后的视频下载事件，在每轮任务结束或定时任务触发时合并为一条汇总通知
+///
+/// 指定了文件路径时，暂存的内容会同步写入文件，避免定时汇总的内容在程序重启后丢失
+#[derive(Default)]
+pub struct DigestBuffer {
+    inner: Mutex<PendingDigest>,
+    path: Option<PathBuf>,
+}
+
+impl DigestBuffer {
+    /// 从文件中恢复上次未发送的内容，文件不存在或无法解析时从空的内容开始
+    pub fn load(path: PathBuf) -> Self {
+        let pending = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                warn!("解析暂存的通知汇总失败，将丢弃其中的内容：{:#}", e);
+                PendingDigest::default()
+            }),
+            Err(_) => PendingDigest::default(),
+        };
+        Self {
+            inner: Mutex::new(pending),
+            path: Some(path),
+        }
+    }
+
+    /// 暂存视频下载成功或失败的事件，其它事件原样返回
+    ///
+    /// 同一视频在汇总发送前多次下载时只保留最近一次的结果
+    pub fn push(&self, event: NotificationEvent) -> Option<NotificationEvent> {
+        let (failed, source, video) = match event {
+            NotificationEvent::VideoDownloaded {
+                source,
+                bvid,
+                title,
+                upper,
+                ..
+            } => (
+                false,
+                source,
+                DigestVideo {
+                    bvid,
+                    title,
+                    upper,
+                    errors: Vec::new(),
+                },
+            ),
+            NotificationEvent::DownloadFailed {
+                source,
+                bvid,
+                title,
+                upper,
+                errors,
+            } => (
+                true,
+                source,
+                DigestVideo {
+                    bvid,
+                    title,
+                    upper,
+                    errors,
+                },
+            ),
+            event => return Some(event),
+        };
+        let mut pending = self.inner.lock();
+        remove(&mut pending.downloaded, &video.bvid);
+        remove(&mut pending.failed, &video.bvid);
+        append(
+            if failed {
+                &mut pending.failed
+            } else {
+                &mut pending.downloaded
+            },
+            source,
+            video,
+        );
+        self.save(&pending);
+        None
+    }
+
+    /// 取出暂存的全部内容，没有内容时返回 None
+    pub fn take(&self) -> Option<NotificationEvent> {
+        let mut pending = self.inner.lock();
+        if pending.downloaded.is_empty() && pending.failed.is_empty() {
+            return None;
+        }
+        let PendingDigest { downloaded, failed } = std::mem::take(&mut *pending);
+        self.save(&pending);
+        Some(NotificationEvent::Digest { downloaded, failed })
+    }
+
+    fn save(&self, pending: &PendingDigest) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let res = serde_json::to_string(pending)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| Ok(std::fs::write(path, content)?));
+        if let Err(e) = res {
+            warn!("保存暂存的通知汇总失败：{:#}", e);
+        }
+    }
+}
+
+fn append(groups: &mut Vec<DigestGroup>, source: String, video: DigestVideo) {
+    match groups.iter_mut().find(|group| group.source == source) {
+        Some(group) => group.videos.push(video),
+        None => groups.push(DigestGroup {
+            source,
+            videos: vec![video],
+        }),
+    }
+}
+
+/// 移除该视频之前的结果，移除后为空的分组一并移除
+fn remove(groups: &mut Vec<DigestGroup>, bvid: &str) {
+    for group in groups.iter_mut() {
+        group.videos.retain(|video| video.bvid != bvid);
+    }
+    groups.retain(|group| !group.videos.is_empty());
+}
+
+pub(super) fn message(downloaded: &[DigestGroup], failed: &[DigestGroup]) -> String {
+    let count = |groups: &[DigestGroup]| groups.iter().map(|group| group.videos.len()).sum::<usize>();
+    let mut message = format!("下载汇总：成功 {} 个，失败 {} 个", count(downloaded), count(failed));
+    if !downloaded.is_empty() {
+        message.push_str("\n\n新增视频：");
+        for group in downloaded {
+            message.push_str(&format!("\n【{}】", group.source));
+            for video in &group.videos {
+                message.push_str(&format!("\n- {}（{}）", video.title, video.upper));
+            }
+        }
+    }
+    if !failed.is_empty() {
+        message.push_str("\n\n下载失败：");
+        for group in failed {
+            message.push_str(&format!("\n【{}】", group.source));
+            for video in &group.videos {
+                message.push_str(&format!(
+                    "\n- {}（{}）：{}",
+                    video.title,
+                    video.upper,
+                    video.errors.join("；")
+                ));
+            }
+        }
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn downloaded(source: &str, bvid: &str) -> NotificationEvent {
+        NotificationEvent::VideoDownloaded {
+            source: source.to_owned(),
+            bvid: bvid.to_owned(),
+            title: format!("视频 {}", bvid),
+            upper: "UP".to_owned(),
+            cover: String::new(),
+            path: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_digest_buffer() {
+        let buffer = DigestBuffer::default();
+        assert!(buffer.take().is_none());
+        assert!(buffer.push(downloaded("收藏夹「A」", "BV1")).is_none());
+        assert!(buffer.push(downloaded("合集「B」", "BV2")).is_none());
+        assert!(buffer.push(downloaded("收藏夹「A」", "BV3")).is_none());
+        assert!(
+            buffer
+                .push(NotificationEvent::DownloadFailed {
+                    source: "合集「B」".to_owned(),
+                    bvid: "BV4".to_owned(),
+                    title: "视频 BV4".to_owned(),
+                    upper: "UP".to_owned(),
+                    errors: vec!["网络错误".to_owned()],
+                })
+                .is_none()
+        );
+        // 重试后下载成功的视频只保留最近一次的结果
+        assert!(
+            buffer
+                .push(NotificationEvent::DownloadFailed {
+                    source: "收藏夹「A」".to_owned(),
+                    bvid: "BV5".to_owned(),
+                    title: "视频 BV5".to_owned(),
+                    upper: "UP".to_owned(),
+                    errors: vec!["网络错误".to_owned()],
+                })
+                .is_none()
+        );
+        assert!(buffer.push(downloaded("收藏夹「A」", "BV5")).is_none());
+        assert!(buffer.push(downloaded("收藏夹「A」", "BV5")).is_none());
+        assert!(buffer.push(NotificationEvent::RunStarted).is_some());
+        let event = buffer.take().unwrap();
+        let NotificationEvent::Digest { downloaded, failed } = &event else {
+            panic!("unexpected event: {:?}", event);
+        };
+        assert_eq!(
+            downloaded
+                .iter()
+                .map(|group| (group.source.as_str(), group.videos.len()))
+                .collect::<Vec<_>>(),
+            vec![("收藏夹「A」", 3), ("合集「B」", 1)]
+        );
+        assert_eq!(failed.len(), 1);
+        assert_eq!(
+            event.message(),
+            "下载汇总：成功 4 个，失败 1 个\n\n新增视频：\n【收藏夹「A」】\n- 视频 BV1（UP）\n- 视频 BV3（UP）\n- 视频 BV5（UP）\n【合集「B」】\n- 视频 BV2（UP）\n\n下载失败：\n【合集「B」】\n- 视频 BV4（UP）：网络错误"
+        );
+        assert!(buffer.take().is_none());
+    }
+}
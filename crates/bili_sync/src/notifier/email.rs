@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use lettre::message::Mailbox;
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::{Deserialize, Serialize};
+
+/// SMTP 连接的加密方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpSecurity {
+    /// 直接使用 TLS 连接，通常为 465 端口
+    #[default]
+    Tls,
+    /// 明文连接后通过 STARTTLS 升级，通常为 587 端口
+    StartTls,
+    /// 不加密，仅建议在本地中继中使用
+    None,
+}
+
+pub(super) struct SmtpOptions<'a> {
+    pub host: &'a str,
+    pub port: u16,
+    pub security: SmtpSecurity,
+    pub username: Option<&'a str>,
+    pub password: Option<&'a str>,
+}
+
+pub(super) async fn send(
+    options: SmtpOptions<'_>,
+    from: &str,
+    to: &[String],
+    subject: &str,
+    body: String,
+) -> Result<()> {
+    let mut builder = Message::builder()
+        .from(
+            from.parse::<Mailbox>()
+                .with_context(|| format!("无效的发件人地址：{}", from))?,
+        )
+        .subject(subject)
+        .header(ContentType::TEXT_PLAIN);
+    for to in to {
+        builder = builder.to(to
+            .parse::<Mailbox>()
+            .with_context(|| format!("无效的收件人地址：{}", to))?);
+    }
+    let message = builder.body(body).context("构造邮件失败")?;
+    let mut transport = match options.security {
+        SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(options.host)?,
+        SmtpSecurity::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(options.host)?,
+        SmtpSecurity::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(options.host),
+    }
+    .port(options.port)
+    .timeout(Some(Duration::from_secs(30)));
+    if let Some(username) = options.username.filter(|u| !u.is_empty()) {
+        transport = transport.credentials(Credentials::new(
+            username.to_owned(),
+            options.password.unwrap_or_default().to_owned(),
+        ));
+    }
+    transport
+        .build()
+        .send(message)
+        .await
+        .with_context(|| format!("通过 {}:{} 发送邮件失败", options.host, options.port))?;
+    Ok(())
+}
@@ -1,8 +1,17 @@
-use anyhow::Result;
+mod digest;
+mod email;
+mod telegram;
+
+use std::borrow::Cow;
+
+use anyhow::{Result, bail};
 use futures::future;
 use reqwest::header;
 use serde::{Deserialize, Serialize};
+use strum::EnumDiscriminants;
 
+pub use self::digest::{DigestBuffer, DigestGroup};
+pub use self::email::SmtpSecurity;
 use crate::config::TEMPLATE;
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -11,14 +20,271 @@ pub enum Notifier {
     Telegram {
         bot_token: String,
         chat_id: String,
+        #[serde(default = "default_events")]
+        events: Vec<NotificationEventKind>,
     },
     Webhook {
         url: String,
         template: Option<String>,
+        #[serde(default = "default_events")]
+        events: Vec<NotificationEventKind>,
         #[serde(skip)]
         // 一个内部辅助字段，用于决定是否强制渲染当前模板，在测试时使用
         ignore_cache: Option<()>,
     },
+    Ntfy {
+        #[serde(default = "default_ntfy_url")]
+        server_url: String,
+        topic: String,
+        /// access token，服务端未开启认证时留空
+        token: Option<String>,
+        #[serde(default = "default_events")]
+        events: Vec<NotificationEventKind>,
+    },
+    Gotify {
+        server_url: String,
+        /// 应用的 token
+        token: String,
+        #[serde(default = "default_events")]
+        events: Vec<NotificationEventKind>,
+    },
+    Bark {
+        #[serde(default = "default_bark_url")]
+        server_url: String,
+        device_key: String,
+        #[serde(default = "default_events")]
+        events: Vec<NotificationEventKind>,
+    },
+    ServerChan {
+        send_key: String,
+        /// 自定义接口地址，留空时根据 send_key 自动选择
+        server_url: Option<String>,
+        #[serde(default = "default_events")]
+        events: Vec<NotificationEventKind>,
+    },
+    Discord {
+        webhook_url: String,
+        #[serde(default = "default_events")]
+        events: Vec<NotificationEventKind>,
+    },
+    Email {
+        smtp_host: String,
+        smtp_port: u16,
+        #[serde(default)]
+        security: SmtpSecurity,
+        username: Option<String>,
+        password: Option<String>,
+        from: String,
+        to: Vec<String>,
+        #[serde(default = "default_events")]
+        events: Vec<NotificationEventKind>,
+    },
+}
+
+fn default_ntfy_url() -> String {
+    "https://ntfy.sh".to_owned()
+}
+
+fn default_bark_url() -> String {
+    "https://api.day.app".to_owned()
+}
+
+/// 单个视频源在本轮下载任务中的统计
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceSummary {
+    pub name: String,
+    pub new_videos: usize,
+    pub downloaded: usize,
+    pub failed: usize,
+    pub error: Option<String>,
+}
+
+/// 通知事件，webhook 模板中可以使用事件的所有字段，以及渲染好的 message
+#[derive(Debug, Clone, Serialize, EnumDiscriminants)]
+#[serde(tag = "event", rename_all = "snake_case")]
+#[strum_discriminants(
+    name(NotificationEventKind),
+    derive(Serialize, Deserialize, Hash),
+    serde(rename_all = "snake_case")
+)]
+pub enum NotificationEvent {
+    RunStarted,
+    RunFinished {
+        sources: Vec<SourceSummary>,
+    },
+    VideoDownloaded {
+        source: String,
+        bvid: String,
+        title: String,
+        upper: String,
+        cover: String,
+        path: String,
+    },
+    DownloadFailed {
+        source: String,
+        bvid: String,
+        title: String,
+        upper: String,
+        errors: Vec<String>,
+    },
+    /// 开启通知汇总后，视频下载成功与失败的通知会被合并为该事件，按视频源分组
+    Digest {
+        downloaded: Vec<DigestGroup>,
+        failed: Vec<DigestGroup>,
+    },
+    CredentialRefreshFailed {
+        error: String,
+    },
+    RiskControl {
+        source: String,
+        error: String,
+        /// 触发后的退避等级，请求间隔为正常时的 2^level 倍
+        level: u32,
+        /// 自动重试前等待的秒数，未启用自适应退避时为空
+        retry_after: Option<u64>,
+    },
+    Error {
+        message: String,
+    },
+    Test {
+        message: String,
+    },
+}
+
+/// 未指定订阅的事件时仅推送错误相关的通知，与引入事件类型之前的行为保持一致
+fn default_events() -> Vec<NotificationEventKind> {
+    vec![
+        NotificationEventKind::DownloadFailed,
+        NotificationEventKind::CredentialRefreshFailed,
+        NotificationEventKind::RiskControl,
+        NotificationEventKind::Error,
+    ]
+}
+
+impl NotificationEvent {
+    /// 事件的简短标题，用于支持标题的通知渠道
+    pub fn title(&self) -> &'static str {
+        match self {
+            NotificationEvent::RunStarted => "下载任务开始",
+            NotificationEvent::RunFinished { .. } => "下载任务完成",
+            NotificationEvent::VideoDownloaded { .. } => "视频下载完成",
+            NotificationEvent::DownloadFailed { .. } => "视频下载失败",
+            NotificationEvent::Digest { .. } => "下载汇总",
+            NotificationEvent::CredentialRefreshFailed { .. } => "Credential 刷新失败",
+            NotificationEvent::RiskControl { .. } => "触发风控",
+            NotificationEvent::Error { .. } => "运行出错",
+            NotificationEvent::Test { .. } => "测试通知",
+        }
+    }
+
+    /// 与事件相关的视频链接
+    pub fn link(&self) -> Option<String> {
+        match self {
+            NotificationEvent::VideoDownloaded { bvid, .. } | NotificationEvent::DownloadFailed { bvid, .. } => {
+                Some(format!("https://www.bilibili.com/video/{}", bvid))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn cover(&self) -> Option<&str> {
+        match self {
+            NotificationEvent::VideoDownloaded { cover, .. } => Some(cover.as_str()).filter(|c| !c.is_empty()),
+            _ => None,
+        }
+    }
+
+    fn is_failure(&self) -> bool {
+        match self {
+            NotificationEvent::Digest { failed, .. } => !failed.is_empty(),
+            _ => matches!(
+                self,
+                NotificationEvent::DownloadFailed { .. }
+                    | NotificationEvent::CredentialRefreshFailed { .. }
+                    | NotificationEvent::RiskControl { .. }
+                    | NotificationEvent::Error { .. }
+            ),
+        }
+    }
+
+    /// 事件的纯文本描述，用于 Telegram 与 webhook 模板中的 message 字段
+    pub fn message(&self) -> String {
+        match self {
+            NotificationEvent::RunStarted => "开始执行视频下载任务".to_owned(),
+            NotificationEvent::RunFinished { sources } => {
+                let mut message = "视频下载任务执行完毕".to_owned();
+                for source in sources {
+                    message.push_str(&match &source.error {
+                        Some(error) => format!("\n{}：处理失败，{}", source.name, error),
+                        None => format!(
+                            "\n{}：新增 {} 个视频，下载成功 {} 个，失败 {} 个",
+                            source.name, source.new_videos, source.downloaded, source.failed
+                        ),
+                    });
+                }
+                message
+            }
+            NotificationEvent::VideoDownloaded { title, upper, path, .. } => {
+                format!("视频「{}」（{}）下载完成，保存于 {}", title, upper, path)
+            }
+            NotificationEvent::DownloadFailed {
+                title, upper, errors, ..
+            } => {
+                format!("视频「{}」（{}）下载失败：{}", title, upper, errors.join("；"))
+            }
+            NotificationEvent::Digest { downloaded, failed } => digest::message(downloaded, failed),
+            NotificationEvent::CredentialRefreshFailed { error } => format!("检查刷新 Credential 失败：{}", error),
+            NotificationEvent::RiskControl {
+                source,
+                error,
+                level,
+                retry_after,
+            } => {
+                let mut message = format!("处理 {} 时触发风控，已终止本轮下载任务：{}", source, error);
+                if let Some(retry_after) = retry_after {
+                    message.push_str(&format!(
+                        "\n请求间隔已放慢为正常的 {} 倍，将在 {} 分钟后自动重试",
+                        1u32 << level,
+                        retry_after.div_ceil(60)
+                    ));
+                }
+                message
+            }
+            NotificationEvent::Error { message } | NotificationEvent::Test { message } => message.clone(),
+        }
+    }
+
+    fn template_data(&self) -> serde_json::Value {
+        let mut data = serde_json::to_value(self).unwrap_or_default();
+        if let Some(map) = data.as_object_mut() {
+            map.insert("message".to_owned(), self.message().into());
+        }
+        data
+    }
+
+    /// 根据通知器的订阅情况返回实际需要发送的事件，汇总事件只保留通知器订阅了的部分
+    fn for_notifier(&self, notifier: &Notifier) -> Option<Cow<'_, NotificationEvent>> {
+        let NotificationEvent::Digest { downloaded, failed } = self else {
+            return notifier
+                .subscribes(NotificationEventKind::from(self))
+                .then_some(Cow::Borrowed(self));
+        };
+        let pick = |kind, groups: &Vec<DigestGroup>| {
+            if notifier.subscribes(kind) {
+                groups.clone()
+            } else {
+                Vec::new()
+            }
+        };
+        let (downloaded, failed) = (
+            pick(NotificationEventKind::VideoDownloaded, downloaded),
+            pick(NotificationEventKind::DownloadFailed, failed),
+        );
+        if downloaded.is_empty() && failed.is_empty() {
+            return None;
+        }
+        Some(Cow::Owned(NotificationEvent::Digest { downloaded, failed }))
+    }
 }
 
 pub fn webhook_template_key(url: &str) -> String {
@@ -29,52 +295,564 @@ pub fn webhook_template_content(template: &Option<String>) -> &str {
     template
         .as_deref()
         .filter(|t| !t.trim().is_empty())
-        .unwrap_or(r#"{"text": "{{{message}}}"}"#)
+        .unwrap_or(r#"{"text": {{json message}} }"#)
 }
 
 pub trait NotifierAllExt {
-    async fn notify_all(&self, client: &reqwest::Client, message: &str) -> Result<()>;
+    async fn notify_all(&self, client: &reqwest::Client, event: &NotificationEvent) -> Result<()>;
 }
 
 impl NotifierAllExt for Vec<Notifier> {
-    async fn notify_all(&self, client: &reqwest::Client, message: &str) -> Result<()> {
-        future::join_all(self.iter().map(|notifier| notifier.notify(client, message))).await;
+    async fn notify_all(&self, client: &reqwest::Client, event: &NotificationEvent) -> Result<()> {
+        let results = future::join_all(self.iter().filter_map(|notifier| {
+            let event = event.for_notifier(notifier)?;
+            Some(async move { notifier.notify(client, &event).await })
+        }))
+        .await;
+        for result in results {
+            if let Err(e) = result {
+                warn!("发送通知失败：{:#}", e);
+            }
+        }
         Ok(())
     }
 }
 
 impl Notifier {
-    pub async fn notify(&self, client: &reqwest::Client, message: &str) -> Result<()> {
+    /// 测试通知不受订阅设置的限制，汇总通知发送给订阅了视频下载成功或失败的通知器
+    pub fn subscribes(&self, kind: NotificationEventKind) -> bool {
+        let (Notifier::Telegram { events, .. }
+        | Notifier::Webhook { events, .. }
+        | Notifier::Ntfy { events, .. }
+        | Notifier::Gotify { events, .. }
+        | Notifier::Bark { events, .. }
+        | Notifier::ServerChan { events, .. }
+        | Notifier::Discord { events, .. }
+        | Notifier::Email { events, .. }) = self;
+        match kind {
+            NotificationEventKind::Test => true,
+            NotificationEventKind::Digest => {
+                events.contains(&NotificationEventKind::VideoDownloaded)
+                    || events.contains(&NotificationEventKind::DownloadFailed)
+            }
+            kind => events.contains(&kind),
+        }
+    }
+
+    pub async fn notify(&self, client: &reqwest::Client, event: &NotificationEvent) -> Result<()> {
         match self {
-            Notifier::Telegram { bot_token, chat_id } => {
-                let url = format!("https://api.telegram.org/bot{}/sendMessage", bot_token);
-                let params = [("chat_id", chat_id.as_str()), ("text", message)];
-                client.post(&url).form(&params).send().await?;
+            Notifier::Telegram { bot_token, chat_id, .. } => {
+                telegram::send(client, telegram::TELEGRAM_API, bot_token, chat_id, &event.message()).await?;
             }
             Notifier::Webhook {
                 url,
                 template,
                 ignore_cache,
+                ..
             } => {
                 let key = webhook_template_key(url);
-                let data = serde_json::json!(
-                    {
-                        "message": message,
-                    }
-                );
+                let data = event.template_data();
                 let handlebar = TEMPLATE.read();
                 let payload = match ignore_cache {
                     Some(_) => handlebar.render_template(webhook_template_content(template), &data)?,
                     None => handlebar.render(&key, &data)?,
                 };
-                client
+                let response = client
                     .post(url)
                     .header(header::CONTENT_TYPE, "application/json")
                     .body(payload)
                     .send()
                     .await?;
+                check_response(response, "Webhook").await?;
+            }
+            Notifier::Ntfy {
+                server_url,
+                topic,
+                token,
+                ..
+            } => {
+                let mut payload = serde_json::json!({
+                    "topic": topic,
+                    "title": event.title(),
+                    "message": event.message(),
+                    "tags": [if event.is_failure() { "warning" } else { "tv" }],
+                });
+                if let Some(link) = event.link() {
+                    payload["click"] = link.into();
+                }
+                let mut request = client.post(server_url.trim_end_matches('/')).json(&payload);
+                if let Some(token) = token.as_deref().filter(|t| !t.is_empty()) {
+                    request = request.bearer_auth(token);
+                }
+                check_response(request.send().await?, "ntfy").await?;
+            }
+            Notifier::Gotify { server_url, token, .. } => {
+                let payload = serde_json::json!({
+                    "title": event.title(),
+                    "message": event.message(),
+                    "priority": if event.is_failure() { 8 } else { 5 },
+                });
+                let response = client
+                    .post(format!("{}/message", server_url.trim_end_matches('/')))
+                    .header("X-Gotify-Key", token)
+                    .json(&payload)
+                    .send()
+                    .await?;
+                check_response(response, "Gotify").await?;
+            }
+            Notifier::Bark {
+                server_url, device_key, ..
+            } => {
+                let mut payload = serde_json::json!({
+                    "device_key": device_key,
+                    "title": event.title(),
+                    "body": event.message(),
+                    "group": "bili-sync",
+                });
+                if let Some(link) = event.link() {
+                    payload["url"] = link.into();
+                }
+                if let Some(cover) = event.cover() {
+                    payload["image"] = cover.into();
+                }
+                let response = client
+                    .post(format!("{}/push", server_url.trim_end_matches('/')))
+                    .json(&payload)
+                    .send()
+                    .await?;
+                // Bark 在响应体中通过 code 表示结果，成功时为 200
+                check_json_code(check_response(response, "Bark").await?, "Bark", 200)?;
+            }
+            Notifier::ServerChan {
+                send_key, server_url, ..
+            } => {
+                let url = match server_url.as_deref().filter(|u| !u.is_empty()) {
+                    Some(server_url) => format!("{}/{}.send", server_url.trim_end_matches('/'), send_key),
+                    None => server_chan_url(send_key),
+                };
+                let params = [("title", event.title().to_owned()), ("desp", event.message())];
+                let response = client.post(url).form(&params).send().await?;
+                check_json_code(check_response(response, "Server酱").await?, "Server酱", 0)?;
+            }
+            Notifier::Discord { webhook_url, .. } => {
+                let mut embed = serde_json::json!({
+                    "title": event.title(),
+                    "description": event.message(),
+                    "color": if event.is_failure() { 0xED4245 } else { 0x00A1D6 },
+                    "timestamp": chrono::Utc::now().to_rfc3339(),
+                });
+                if let Some(link) = event.link() {
+                    embed["url"] = link.into();
+                }
+                if let Some(cover) = event.cover() {
+                    embed["thumbnail"] = serde_json::json!({ "url": cover });
+                }
+                let response = client
+                    .post(webhook_url)
+                    .json(&serde_json::json!({ "username": "bili-sync", "embeds": [embed] }))
+                    .send()
+                    .await?;
+                check_response(response, "Discord").await?;
+            }
+            Notifier::Email {
+                smtp_host,
+                smtp_port,
+                security,
+                username,
+                password,
+                from,
+                to,
+                ..
+            } => {
+                email::send(
+                    email::SmtpOptions {
+                        host: smtp_host,
+                        port: *smtp_port,
+                        security: *security,
+                        username: username.as_deref(),
+                        password: password.as_deref(),
+                    },
+                    from,
+                    to,
+                    &format!("[bili-sync] {}", event.title()),
+                    event.message(),
+                )
+                .await?;
             }
         }
         Ok(())
     }
 }
+
+/// Server酱³ 的 SendKey 以 sctp{uid}t 开头，使用独立的推送域名
+fn server_chan_url(send_key: &str) -> String {
+    if let Some(uid) = send_key
+        .strip_prefix("sctp")
+        .and_then(|rest| rest.split_once('t'))
+        .map(|(uid, _)| uid)
+        .filter(|uid| !uid.is_empty() && uid.bytes().all(|b| b.is_ascii_digit()))
+    {
+        return format!("https://{}.push.ft07.com/send/{}.send", uid, send_key);
+    }
+    format!("https://sctapi.ftqq.com/{}.send", send_key)
+}
+
+/// 检查响应状态码，失败时将响应内容附加到错误信息中，成功时返回响应内容
+async fn check_response(response: reqwest::Response, name: &str) -> Result<String> {
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    if !status.is_success() {
+        bail!("{} 通知发送失败，状态码 {}：{}", name, status, body);
+    }
+    Ok(body)
+}
+
+fn check_json_code(body: String, name: &str, success_code: i64) -> Result<()> {
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap_or_default();
+    match json["code"].as_i64() {
+        Some(code) if code != success_code => bail!("{} 通知发送失败，错误码 {}：{}", name, code, body),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use axum::http::{HeaderMap, StatusCode, Uri};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc;
+
+    use super::digest::DigestVideo;
+    use super::*;
+
+    struct CapturedRequest {
+        path: String,
+        headers: HeaderMap,
+        body: String,
+    }
+
+    /// 启动一个本地 HTTP 服务，记录收到的请求并以固定的内容响应
+    async fn mock_http_server(
+        status: StatusCode,
+        response: &'static str,
+    ) -> (String, mpsc::UnboundedReceiver<CapturedRequest>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let app = axum::Router::new().fallback(move |uri: Uri, headers: HeaderMap, body: String| {
+            let tx = tx.clone();
+            async move {
+                let _ = tx.send(CapturedRequest {
+                    path: uri.path().to_owned(),
+                    headers,
+                    body,
+                });
+                (status, response)
+            }
+        });
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (format!("http://{}", addr), rx)
+    }
+
+    /// 一个只实现了必要命令的 SMTP 服务，返回收到的全部命令与邮件内容
+    async fn mock_smtp_server() -> (u16, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut lines = BufReader::new(reader).lines();
+            let (mut transcript, mut in_data) = (String::new(), false);
+            writer.write_all(b"220 localhost ESMTP\r\n").await.unwrap();
+            while let Ok(Some(line)) = lines.next_line().await {
+                transcript.push_str(&line);
+                transcript.push('\n');
+                let reply: &[u8] = if in_data {
+                    if line != "." {
+                        continue;
+                    }
+                    in_data = false;
+                    b"250 OK\r\n"
+                } else if line.starts_with("EHLO") {
+                    b"250 localhost\r\n"
+                } else if line == "DATA" {
+                    in_data = true;
+                    b"354 Start mail input\r\n"
+                } else if line == "QUIT" {
+                    writer.write_all(b"221 Bye\r\n").await.unwrap();
+                    break;
+                } else {
+                    b"250 OK\r\n"
+                };
+                writer.write_all(reply).await.unwrap();
+            }
+            transcript
+        });
+        (port, handle)
+    }
+
+    fn downloaded_event() -> NotificationEvent {
+        NotificationEvent::VideoDownloaded {
+            source: "收藏夹「默认」".to_owned(),
+            bvid: "BV1b5411h7g7".to_owned(),
+            title: "视频".to_owned(),
+            upper: "UP".to_owned(),
+            cover: "https://i0.hdslb.com/cover.jpg".to_owned(),
+            path: "/video".to_owned(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_http_notifiers() {
+        let client = reqwest::Client::new();
+        let event = downloaded_event();
+
+        let (url, mut rx) = mock_http_server(StatusCode::OK, "{}").await;
+        let notifier = Notifier::Ntfy {
+            server_url: format!("{}/", url),
+            topic: "bili-sync".to_owned(),
+            token: Some("tk_123".to_owned()),
+            events: vec![],
+        };
+        notifier.notify(&client, &event).await.unwrap();
+        let request = rx.recv().await.unwrap();
+        let body: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+        assert_eq!(request.path, "/");
+        assert_eq!(request.headers["authorization"], "Bearer tk_123");
+        assert_eq!(body["topic"], "bili-sync");
+        assert_eq!(body["title"], "视频下载完成");
+        assert_eq!(body["click"], "https://www.bilibili.com/video/BV1b5411h7g7");
+
+        let notifier = Notifier::Gotify {
+            server_url: url.clone(),
+            token: "app_token".to_owned(),
+            events: vec![],
+        };
+        notifier.notify(&client, &event).await.unwrap();
+        let request = rx.recv().await.unwrap();
+        let body: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+        assert_eq!(request.path, "/message");
+        assert_eq!(request.headers["x-gotify-key"], "app_token");
+        assert_eq!(body["message"], event.message());
+
+        let notifier = Notifier::Discord {
+            webhook_url: format!("{}/api/webhooks/1/token", url),
+            events: vec![],
+        };
+        notifier.notify(&client, &event).await.unwrap();
+        let request = rx.recv().await.unwrap();
+        let body: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+        assert_eq!(request.path, "/api/webhooks/1/token");
+        assert_eq!(body["embeds"][0]["thumbnail"]["url"], "https://i0.hdslb.com/cover.jpg");
+        assert_eq!(body["embeds"][0]["url"], "https://www.bilibili.com/video/BV1b5411h7g7");
+
+        let (url, mut rx) = mock_http_server(StatusCode::OK, r#"{"code": 200, "message": "success"}"#).await;
+        let notifier = Notifier::Bark {
+            server_url: url,
+            device_key: "device".to_owned(),
+            events: vec![],
+        };
+        notifier.notify(&client, &event).await.unwrap();
+        let request = rx.recv().await.unwrap();
+        let body: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+        assert_eq!(request.path, "/push");
+        assert_eq!(body["device_key"], "device");
+        assert_eq!(body["image"], "https://i0.hdslb.com/cover.jpg");
+
+        let (url, mut rx) = mock_http_server(StatusCode::OK, r#"{"code": 0}"#).await;
+        let notifier = Notifier::ServerChan {
+            send_key: "SCT123".to_owned(),
+            server_url: Some(url),
+            events: vec![],
+        };
+        notifier.notify(&client, &event).await.unwrap();
+        let request = rx.recv().await.unwrap();
+        let form = serde_urlencoded::from_str::<HashMap<String, String>>(&request.body).unwrap();
+        assert_eq!(request.path, "/SCT123.send");
+        assert_eq!(form["title"], "视频下载完成");
+        assert_eq!(form["desp"], event.message());
+    }
+
+    #[tokio::test]
+    async fn test_webhook_default_template() {
+        let client = reqwest::Client::new();
+        let (url, mut rx) = mock_http_server(StatusCode::OK, "{}").await;
+        let notifier = Notifier::Webhook {
+            url,
+            template: None,
+            events: vec![],
+            ignore_cache: Some(()),
+        };
+        // 多行的消息与错误信息中的引号都需要转义，否则请求体不是合法的 JSON
+        let event = NotificationEvent::RunFinished {
+            sources: vec![SourceSummary {
+                name: "收藏夹「默认」".to_owned(),
+                new_videos: 0,
+                downloaded: 0,
+                failed: 0,
+                error: Some(r#"请求失败：{"code": -352}"#.to_owned()),
+            }],
+        };
+        assert!(event.message().contains('\n') && event.message().contains('"'));
+        notifier.notify(&client, &event).await.unwrap();
+        let request = rx.recv().await.unwrap();
+        let body: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+        assert_eq!(body, serde_json::json!({ "text": event.message() }));
+    }
+
+    #[tokio::test]
+    async fn test_notifier_errors() {
+        let client = reqwest::Client::new();
+        let event = downloaded_event();
+        let (url, _rx) = mock_http_server(StatusCode::UNAUTHORIZED, "unauthorized").await;
+        let notifier = Notifier::Gotify {
+            server_url: url,
+            token: "wrong".to_owned(),
+            events: vec![],
+        };
+        let err = notifier.notify(&client, &event).await.unwrap_err();
+        assert!(err.to_string().contains("401"), "{:#}", err);
+        // 状态码正常，但响应体中的错误码表示失败
+        let (url, _rx) = mock_http_server(StatusCode::OK, r#"{"code": 400, "message": "failed"}"#).await;
+        let notifier = Notifier::Bark {
+            server_url: url,
+            device_key: "device".to_owned(),
+            events: vec![],
+        };
+        let err = notifier.notify(&client, &event).await.unwrap_err();
+        assert!(err.to_string().contains("400"), "{:#}", err);
+    }
+
+    #[tokio::test]
+    async fn test_email_notifier() {
+        let (port, handle) = mock_smtp_server().await;
+        let notifier = Notifier::Email {
+            smtp_host: "127.0.0.1".to_owned(),
+            smtp_port: port,
+            security: SmtpSecurity::None,
+            username: None,
+            password: None,
+            from: "bili-sync <sender@example.com>".to_owned(),
+            to: vec!["receiver@example.com".to_owned()],
+            events: vec![],
+        };
+        notifier
+            .notify(&reqwest::Client::new(), &downloaded_event())
+            .await
+            .unwrap();
+        let transcript = handle.await.unwrap();
+        assert!(transcript.contains("MAIL FROM:<sender@example.com>"), "{}", transcript);
+        assert!(transcript.contains("RCPT TO:<receiver@example.com>"), "{}", transcript);
+        assert!(transcript.contains("Subject: [bili-sync] =?utf-8?"), "{}", transcript);
+        assert!(
+            transcript.contains("Content-Type: text/plain; charset=utf-8"),
+            "{}",
+            transcript
+        );
+    }
+
+    #[test]
+    fn test_server_chan_url() {
+        assert_eq!(server_chan_url("SCT123abc"), "https://sctapi.ftqq.com/SCT123abc.send");
+        assert_eq!(
+            server_chan_url("sctp42tabc"),
+            "https://42.push.ft07.com/send/sctp42tabc.send"
+        );
+    }
+
+    #[test]
+    fn test_notifier_events() {
+        let notifier: Notifier = serde_json::from_str(r#"{"type": "webhook", "url": "http://localhost"}"#).unwrap();
+        assert!(notifier.subscribes(NotificationEventKind::Error));
+        assert!(notifier.subscribes(NotificationEventKind::Test));
+        assert!(!notifier.subscribes(NotificationEventKind::VideoDownloaded));
+        let notifier: Notifier = serde_json::from_str(
+            r#"{"type": "telegram", "bot_token": "token", "chat_id": "1", "events": ["video_downloaded", "run_finished"]}"#,
+        )
+        .unwrap();
+        assert!(notifier.subscribes(NotificationEventKind::RunFinished));
+        assert!(!notifier.subscribes(NotificationEventKind::Error));
+        assert!(notifier.subscribes(NotificationEventKind::Digest));
+        let group = |source: &str| DigestGroup {
+            source: source.to_owned(),
+            videos: vec![DigestVideo {
+                bvid: "BV1b5411h7g7".to_owned(),
+                title: "视频".to_owned(),
+                upper: "UP".to_owned(),
+                errors: Vec::new(),
+            }],
+        };
+        let digest = NotificationEvent::Digest {
+            downloaded: vec![group("收藏夹「默认」")],
+            failed: vec![group("合集「默认」")],
+        };
+        // 只订阅了下载成功事件的通知器不会收到汇总中的失败部分
+        let Some(event) = digest.for_notifier(&notifier) else {
+            panic!("digest should be sent");
+        };
+        let NotificationEvent::Digest { downloaded, failed } = event.as_ref() else {
+            panic!("unexpected event: {:?}", event);
+        };
+        assert_eq!(downloaded.len(), 1);
+        assert!(failed.is_empty());
+        let notifier: Notifier =
+            serde_json::from_str(r#"{"type": "discord", "webhook_url": "http://localhost"}"#).unwrap();
+        assert!(digest.for_notifier(&notifier).is_some());
+        let digest = NotificationEvent::Digest {
+            downloaded: vec![group("收藏夹「默认」")],
+            failed: Vec::new(),
+        };
+        assert!(digest.for_notifier(&notifier).is_none());
+    }
+
+    #[test]
+    fn test_template_data() {
+        let event = NotificationEvent::VideoDownloaded {
+            source: "收藏夹「默认」".to_owned(),
+            bvid: "BV1b5411h7g7".to_owned(),
+            title: "视频".to_owned(),
+            upper: "UP".to_owned(),
+            cover: "https://i0.hdslb.com/cover.jpg".to_owned(),
+            path: "/video".to_owned(),
+        };
+        assert_eq!(
+            event.template_data(),
+            serde_json::json!({
+                "event": "video_downloaded",
+                "source": "收藏夹「默认」",
+                "bvid": "BV1b5411h7g7",
+                "title": "视频",
+                "upper": "UP",
+                "cover": "https://i0.hdslb.com/cover.jpg",
+                "path": "/video",
+                "message": "视频「视频」（UP）下载完成，保存于 /video",
+            })
+        );
+        let event = NotificationEvent::RunFinished {
+            sources: vec![SourceSummary {
+                name: "收藏夹「默认」".to_owned(),
+                new_videos: 2,
+                downloaded: 1,
+                failed: 1,
+                error: None,
+            }],
+        };
+        assert_eq!(
+            event.message(),
+            "视频下载任务执行完毕\n收藏夹「默认」：新增 2 个视频，下载成功 1 个，失败 1 个"
+        );
+        let mut handlebars = handlebars::Handlebars::new();
+        handlebars
+            .register_template_string(
+                "payload",
+                r#"{{event}}:{{#each sources}}{{name}}={{downloaded}}{{/each}}"#,
+            )
+            .unwrap();
+        assert_eq!(
+            handlebars.render("payload", &event.template_data()).unwrap(),
+            "run_finished:收藏夹「默认」=1"
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use anyhow::{Result, bail};
+use parking_lot::Mutex;
+use tokio::time::Instant;
+
+use super::check_response;
+
+pub(super) const TELEGRAM_API: &str = "https://api.telegram.org";
+/// Telegram 单条消息的最大长度（按字符计）
+const MAX_MESSAGE_LENGTH: usize = 4096;
+/// 同一 bot 向同一会话发送消息的频率限制约为每秒一条，这里按照 bot 与会话分别保持间隔
+const MIN_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_ATTEMPTS: u32 = 5;
+/// retry_after 的上限，避免异常值导致通知任务长时间挂起
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(300);
+
+/// 各个 bot 与会话下一次允许发送消息的时间
+static NEXT_SEND: LazyLock<Mutex<HashMap<(String, String), Instant>>> = LazyLock::new(Default::default);
+
+/// 发送 Telegram 消息，超长消息会被拆分为多条依次发送
+pub(super) async fn send(
+    client: &reqwest::Client,
+    api_base: &str,
+    bot_token: &str,
+    chat_id: &str,
+    text: &str,
+) -> Result<()> {
+    let url = format!("{}/bot{}/sendMessage", api_base.trim_end_matches('/'), bot_token);
+    for chunk in split_message(text, MAX_MESSAGE_LENGTH) {
+        tokio::time::sleep_until(reserve(bot_token, chat_id, Instant::now())).await;
+        let result = send_with_retry(client, &url, chat_id, &chunk).await;
+        // 重试可能耗时较久，发送结束后顺延下一次允许发送的时间
+        let next = Instant::now() + MIN_INTERVAL;
+        NEXT_SEND
+            .lock()
+            .entry((bot_token.to_owned(), chat_id.to_owned()))
+            .and_modify(|at| *at = (*at).max(next))
+            .or_insert(next);
+        result?;
+    }
+    Ok(())
+}
+
+/// 预留该 bot 与会话的下一个发送时间并返回，只在预留时持有锁，等待期间不会阻塞其它的 bot 与会话
+fn reserve(bot_token: &str, chat_id: &str, now: Instant) -> Instant {
+    let mut next_send = NEXT_SEND.lock();
+    let at = next_send
+        .get(&(bot_token.to_owned(), chat_id.to_owned()))
+        .map_or(now, |at| (*at).max(now));
+    next_send.insert((bot_token.to_owned(), chat_id.to_owned()), at + MIN_INTERVAL);
+    at
+}
+
+/// 遇到 429 时按照响应中的 retry_after 等待，遇到 5xx 或网络错误时指数退避，其余错误直接返回
+async fn send_with_retry(client: &reqwest::Client, url: &str, chat_id: &str, text: &str) -> Result<()> {
+    let params = [("chat_id", chat_id), ("text", text)];
+    let mut backoff = Duration::from_secs(1);
+    for attempt in 1..=MAX_ATTEMPTS {
+        let wait = match client.post(url).form(&params).send().await {
+            Ok(response) if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                let body = response.text().await.unwrap_or_default();
+                let json: serde_json::Value = serde_json::from_str(&body).unwrap_or_default();
+                match json["parameters"]["retry_after"].as_u64() {
+                    Some(retry_after) => Duration::from_secs(retry_after).min(MAX_RETRY_AFTER),
+                    None => backoff,
+                }
+            }
+            Ok(response) if response.status().is_server_error() && attempt < MAX_ATTEMPTS => backoff,
+            Ok(response) => return check_response(response, "Telegram").await.map(|_| ()),
+            Err(e) if attempt < MAX_ATTEMPTS => {
+                warn!("发送 Telegram 通知失败：{:#}，将在 {:?} 后重试", e, backoff);
+                backoff
+            }
+            Err(e) => return Err(e.into()),
+        };
+        tokio::time::sleep(wait).await;
+        backoff *= 2;
+    }
+    bail!("Telegram 通知发送失败，已重试 {} 次", MAX_ATTEMPTS)
+}
+
+/// 按行拆分消息，使每段不超过 limit 个字符，单行过长时再按字符强制拆分
+fn split_message(text: &str, limit: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let (mut current, mut current_len) = (String::new(), 0);
+    for line in text.split('\n') {
+        let line_len = line.chars().count();
+        // 加上换行符后放不下当前行，先结束当前段
+        if current_len > 0 && current_len + 1 + line_len > limit {
+            chunks.push(std::mem::take(&mut current));
+            current_len = 0;
+        }
+        if current_len > 0 {
+            current.push('\n');
+            current_len += 1;
+        }
+        for c in line.chars() {
+            if current_len == limit {
+                chunks.push(std::mem::take(&mut current));
+                current_len = 0;
+            }
+            current.push(c);
+            current_len += 1;
+        }
+    }
+    if !current.is_empty() || chunks.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use axum::http::StatusCode;
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    #[test]
+    fn test_split_message() {
+        assert_eq!(split_message("", 10), vec![""]);
+        assert_eq!(split_message("短消息\n第二行", 10), vec!["短消息\n第二行"]);
+        assert_eq!(split_message("aaaa\nbbbb\ncccc", 9), vec!["aaaa\nbbbb", "cccc"]);
+        assert_eq!(split_message("aaaaaaaaaaaa\nbb", 5), vec!["aaaaa", "aaaaa", "aa\nbb"]);
+        let text = "一二三四五\n".repeat(2000);
+        let chunks = split_message(&text, MAX_MESSAGE_LENGTH);
+        assert!(chunks.iter().all(|chunk| chunk.chars().count() <= MAX_MESSAGE_LENGTH));
+        assert_eq!(chunks.join("\n"), text);
+    }
+
+    #[test]
+    fn test_reserve() {
+        let now = Instant::now();
+        assert_eq!(reserve("reserve_token", "chat", now), now);
+        assert_eq!(reserve("reserve_token", "chat", now), now + MIN_INTERVAL);
+        // 不同的会话互不影响
+        assert_eq!(reserve("reserve_token", "other_chat", now), now);
+    }
+
+    #[tokio::test]
+    async fn test_send_retry() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let app = axum::Router::new().fallback({
+            let counter = counter.clone();
+            move || async move {
+                match counter.fetch_add(1, Ordering::SeqCst) {
+                    0 => (
+                        StatusCode::TOO_MANY_REQUESTS,
+                        r#"{"ok":false,"error_code":429,"parameters":{"retry_after":0}}"#,
+                    ),
+                    _ => (StatusCode::OK, r#"{"ok":true}"#),
+                }
+            }
+        });
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let client = reqwest::Client::new();
+        send(&client, &url, "token", "chat", "测试").await.unwrap();
+        assert_eq!(counter.load(Ordering::SeqCst), 2);
+
+        let app = axum::Router::new().fallback(|| async { (StatusCode::BAD_REQUEST, "chat not found") });
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let err = send(&client, &url, "token", "chat", "测试").await.unwrap_err();
+        assert!(err.to_string().contains("chat not found"));
+    }
+}
@@ -1,5 +1,7 @@
 mod http_server;
+mod relayout;
 mod video_downloader;
 
 pub use http_server::http_server;
-pub use video_downloader::{DownloadTaskManager, TaskStatus, video_downloader};
+pub use relayout::RelayoutReport;
+pub use video_downloader::{DownloadTaskManager, TaskStatus, run_once, video_downloader};
@@ -0,0 +1,384 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use bili_sync_entity::*;
+use sea_orm::ActiveValue::Set;
+use sea_orm::entity::prelude::*;
+use sea_orm::{DatabaseConnection, QuerySelect, TransactionTrait, Unchanged};
+use serde::Serialize;
+use tokio::fs;
+use tokio_util::sync::CancellationToken;
+
+use crate::adapter::VideoSource;
+use crate::bilibili::BiliClient;
+use crate::config::Config;
+use crate::utils::layout::{VIDEO_LEVEL_FILES, VideoLayout, compute_layout, page_sidecar_suffix};
+use crate::utils::model::get_all_video_sources;
+use crate::workflow::get_cached_season_title;
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct FileMove {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+/// 单个视频的重新整理计划
+#[derive(Serialize, Debug)]
+pub struct VideoRelayout {
+    pub video_id: i32,
+    pub bvid: String,
+    pub name: String,
+    pub old_path: String,
+    pub new_path: String,
+    pub moves: Vec<FileMove>,
+    /// 存在冲突的视频会被跳过，不做任何修改
+    pub conflicts: Vec<String>,
+    pub error: Option<String>,
+    #[serde(skip)]
+    page_paths: Vec<(i32, String)>,
+}
+
+#[derive(Serialize, Default, Debug)]
+pub struct RelayoutReport {
+    pub dry_run: bool,
+    /// 路径需要变更的视频数量
+    pub planned: usize,
+    pub relocated: usize,
+    pub conflicted: usize,
+    pub failed: usize,
+    pub videos: Vec<VideoRelayout>,
+}
+
+/// 使用当前的模板重新计算已下载视频的路径，并将视频文件及其附属文件移动到新的位置
+///
+/// 每个视频的文件移动与数据库更新作为一个整体，任一步骤失败都会回滚该视频已经移动的文件；
+/// 源文件不存在而目标文件存在时视为已经移动过，因此中断后可以直接重新执行
+pub async fn relayout(
+    connection: &DatabaseConnection,
+    bili_client: &BiliClient,
+    config: &Config,
+    template: &handlebars::Handlebars<'_>,
+    dry_run: bool,
+) -> Result<RelayoutReport> {
+    let mut report = RelayoutReport {
+        dry_run,
+        ..Default::default()
+    };
+    let shared_dirs = shared_video_dirs(connection).await?;
+    let mut claimed = HashSet::new();
+    for video_source in get_all_video_sources(connection).await? {
+        let videos = video::Entity::find()
+            .filter(video_source.filter_expr().and(video::Column::Path.ne("")))
+            .find_with_related(page::Entity)
+            .all(connection)
+            .await?;
+        for (video_model, page_models) in videos {
+            let api_title = match &video_model.season_id {
+                Some(season_id) if video_model.bangumi_id.is_some() => {
+                    get_cached_season_title(bili_client, season_id, CancellationToken::new()).await
+                }
+                _ => None,
+            };
+            let layout = match compute_layout(
+                template,
+                video_source.path(),
+                &video_model,
+                &page_models,
+                &config.time_format,
+                api_title.as_deref(),
+            ) {
+                Ok(layout) => layout,
+                Err(e) => {
+                    warn!("计算视频「{}」的新路径失败，跳过：{:#}", video_model.name, e);
+                    continue;
+                }
+            };
+            let is_shared =
+                shared_dirs.contains(&video_model.path) || video_model.path == video_source.path().to_string_lossy();
+            let mut plan = plan_video(&video_model, &page_models, &layout, is_shared, &mut claimed).await;
+            if plan.new_path == plan.old_path && plan.page_paths.is_empty() {
+                continue;
+            }
+            report.planned += 1;
+            if !plan.conflicts.is_empty() {
+                report.conflicted += 1;
+            } else if !dry_run {
+                match apply_plan(&plan, connection, video_source.path()).await {
+                    Ok(_) => report.relocated += 1,
+                    Err(e) => {
+                        error!("重新整理视频「{}」失败，已回滚：{:#}", plan.name, e);
+                        plan.error = Some(format!("{:#}", e));
+                        report.failed += 1;
+                    }
+                }
+            }
+            report.videos.push(plan);
+        }
+    }
+    Ok(report)
+}
+
+/// 被多个视频共用的视频目录，这些目录中的视频级文件不做移动
+async fn shared_video_dirs(connection: &DatabaseConnection) -> Result<HashSet<String>> {
+    let mut counter = HashMap::<String, usize>::new();
+    for path in video::Entity::find()
+        .select_only()
+        .column(video::Column::Path)
+        .filter(video::Column::Path.ne(""))
+        .into_tuple::<String>()
+        .all(connection)
+        .await?
+    {
+        *counter.entry(path).or_default() += 1;
+    }
+    Ok(counter
+        .into_iter()
+        .filter_map(|(path, count)| (count > 1).then_some(path))
+        .collect())
+}
+
+async fn plan_video(
+    video_model: &video::Model,
+    page_models: &[page::Model],
+    layout: &VideoLayout,
+    is_shared: bool,
+    claimed: &mut HashSet<PathBuf>,
+) -> VideoRelayout {
+    let (old_base, new_base) = (PathBuf::from(&video_model.path), &layout.base_path);
+    let mut plan = VideoRelayout {
+        video_id: video_model.id,
+        bvid: video_model.bvid.clone(),
+        name: video_model.name.clone(),
+        old_path: video_model.path.clone(),
+        new_path: new_base.to_string_lossy().into_owned(),
+        moves: Vec::new(),
+        conflicts: Vec::new(),
+        error: None,
+        page_paths: Vec::new(),
+    };
+    let new_page_paths = layout.pages.iter().cloned().collect::<HashMap<_, _>>();
+    for page_model in page_models {
+        let (Some(old_path), Some(new_path)) = (
+            page_model.path.as_deref().filter(|p| !p.is_empty()).map(Path::new),
+            new_page_paths.get(&page_model.id),
+        ) else {
+            continue;
+        };
+        if old_path == new_path {
+            continue;
+        }
+        plan.page_paths
+            .push((page_model.id, new_path.to_string_lossy().into_owned()));
+        let (Some((old_dir, old_stem)), Some((new_dir, new_stem))) =
+            (split_video_path(old_path), split_video_path(new_path))
+        else {
+            plan.conflicts.push(format!("无法解析分页路径 {}", old_path.display()));
+            continue;
+        };
+        let Ok(mut entries) = fs::read_dir(old_dir).await else {
+            // 旧目录不存在时文件可能已经全部移动过
+            continue;
+        };
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let file_name = entry.file_name();
+            let Some(suffix) = file_name.to_str().and_then(|name| page_sidecar_suffix(name, old_stem)) else {
+                continue;
+            };
+            plan.moves.push(FileMove {
+                from: entry.path(),
+                to: new_dir.join(format!("{}{}", new_stem, suffix)),
+            });
+        }
+    }
+    if old_base != *new_base && !is_shared {
+        for file_name in VIDEO_LEVEL_FILES {
+            let from = old_base.join(file_name);
+            if fs::try_exists(&from).await.unwrap_or(false) {
+                plan.moves.push(FileMove {
+                    from,
+                    to: new_base.join(file_name),
+                });
+            }
+        }
+    }
+    let mut targets = HashSet::new();
+    for file_move in &plan.moves {
+        if claimed.contains(&file_move.to) || !targets.insert(&file_move.to) {
+            plan.conflicts
+                .push(format!("目标路径 {} 与其它文件冲突", file_move.to.display()));
+        } else if fs::try_exists(&file_move.to).await.unwrap_or(false)
+            && !is_same_file(&file_move.from, &file_move.to).await
+        {
+            plan.conflicts
+                .push(format!("目标文件 {} 已存在", file_move.to.display()));
+        }
+    }
+    if plan.conflicts.is_empty() {
+        claimed.extend(plan.moves.iter().map(|m| m.to.clone()));
+    }
+    plan
+}
+
+/// 将视频文件路径拆分为所在目录与去掉 .mp4 后缀的文件名
+fn split_video_path(path: &Path) -> Option<(&Path, &str)> {
+    Some((path.parent()?, path.file_name()?.to_str()?.strip_suffix(".mp4")?))
+}
+
+/// 大小写不敏感的文件系统中，仅修改大小写时目标文件与源文件是同一个文件
+async fn is_same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a).await, fs::canonicalize(b).await) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+async fn apply_plan(plan: &VideoRelayout, connection: &DatabaseConnection, source_path: &Path) -> Result<()> {
+    let mut moved = Vec::with_capacity(plan.moves.len());
+    for file_move in &plan.moves {
+        if let Err(e) = move_file(&file_move.from, &file_move.to).await {
+            rollback(&moved).await;
+            return Err(e);
+        }
+        moved.push(file_move);
+    }
+    let update_result = async {
+        let txn = connection.begin().await?;
+        video::ActiveModel {
+            id: Unchanged(plan.video_id),
+            path: Set(plan.new_path.clone()),
+            ..Default::default()
+        }
+        .update(&txn)
+        .await?;
+        for (page_id, path) in &plan.page_paths {
+            page::ActiveModel {
+                id: Unchanged(*page_id),
+                path: Set(Some(path.clone())),
+                ..Default::default()
+            }
+            .update(&txn)
+            .await?;
+        }
+        txn.commit().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    }
+    .await;
+    if let Err(e) = update_result {
+        rollback(&moved).await;
+        return Err(e);
+    }
+    for file_move in moved {
+        if let Some(dir) = file_move.from.parent() {
+            remove_empty_dirs(dir, source_path).await;
+        }
+    }
+    Ok(())
+}
+
+async fn move_file(from: &Path, to: &Path) -> Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)
+            .await
+            .with_context(|| format!("创建目录 {} 失败", parent.display()))?;
+    }
+    rename_or_copy(from, to)
+        .await
+        .with_context(|| format!("移动文件 {} 到 {} 失败", from.display(), to.display()))
+}
+
+/// 跨文件系统时无法直接重命名，退化为复制后删除源文件
+async fn rename_or_copy(from: &Path, to: &Path) -> std::io::Result<()> {
+    match fs::rename(from, to).await {
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+            fs::copy(from, to).await?;
+            fs::remove_file(from).await
+        }
+        result => result,
+    }
+}
+
+async fn rollback(moved: &[&FileMove]) {
+    for file_move in moved.iter().rev() {
+        if let Err(e) = rename_or_copy(&file_move.to, &file_move.from).await {
+            error!(
+                "回滚文件 {} 到 {} 失败：{:#}",
+                file_move.to.display(),
+                file_move.from.display(),
+                e
+            );
+        }
+    }
+}
+
+/// 自下而上删除移动后留下的空目录，不会删除视频源目录本身
+async fn remove_empty_dirs(dir: &Path, source_path: &Path) {
+    let mut current = Some(dir);
+    while let Some(dir) = current {
+        if !dir.starts_with(source_path) || dir == source_path || fs::remove_dir(dir).await.is_err() {
+            break;
+        }
+        current = dir.parent();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_plan_and_apply_moves() {
+        let root = std::env::temp_dir().join(format!("bili-sync-relayout-{}", uuid::Uuid::new_v4()));
+        let (old_dir, new_dir) = (root.join("旧"), root.join("新"));
+        fs::create_dir_all(&old_dir).await.unwrap();
+        for file_name in ["视频.mp4", "视频.nfo", "视频-poster.jpg", "视频.zh-CN.srt", "视频2.mp4"] {
+            fs::write(old_dir.join(file_name), file_name).await.unwrap();
+        }
+        fs::write(new_dir.with_extension("keep"), "").await.unwrap();
+        let video_model = video::Model {
+            id: 1,
+            path: old_dir.to_string_lossy().into_owned(),
+            ..Default::default()
+        };
+        let page_models = vec![page::Model {
+            id: 1,
+            path: Some(old_dir.join("视频.mp4").to_string_lossy().into_owned()),
+            ..Default::default()
+        }];
+        let layout = VideoLayout {
+            base_path: new_dir.clone(),
+            pages: vec![(1, new_dir.join("新视频.mp4"))],
+        };
+        let mut claimed = HashSet::new();
+        let plan = plan_video(&video_model, &page_models, &layout, false, &mut claimed).await;
+        assert!(plan.conflicts.is_empty());
+        let mut targets = plan.moves.iter().map(|m| m.to.clone()).collect::<Vec<_>>();
+        targets.sort();
+        let mut expected = ["新视频-poster.jpg", "新视频.mp4", "新视频.nfo", "新视频.zh-CN.srt"]
+            .map(|name| new_dir.join(name))
+            .to_vec();
+        expected.sort();
+        assert_eq!(targets, expected);
+        // 同一轮中其它视频不能再占用这些路径
+        let conflicted = plan_video(&video_model, &page_models, &layout, false, &mut claimed).await;
+        assert_eq!(conflicted.conflicts.len(), 4);
+
+        let moved = plan.moves.iter().collect::<Vec<_>>();
+        for file_move in &moved {
+            move_file(&file_move.from, &file_move.to).await.unwrap();
+        }
+        assert_eq!(
+            fs::read_to_string(new_dir.join("新视频.nfo")).await.unwrap(),
+            "视频.nfo"
+        );
+        assert!(fs::try_exists(old_dir.join("视频2.mp4")).await.unwrap());
+        // 中断后重新计划时，已经移动的文件不会重复出现
+        let replan = plan_video(&video_model, &page_models, &layout, false, &mut HashSet::new()).await;
+        assert!(replan.moves.is_empty() && replan.conflicts.is_empty());
+        rollback(&moved).await;
+        assert!(fs::try_exists(old_dir.join("视频.mp4")).await.unwrap());
+        assert!(!fs::try_exists(new_dir.join("新视频.mp4")).await.unwrap());
+
+        fs::remove_dir_all(&root).await.unwrap();
+    }
+}
@@ -1,19 +1,30 @@
+use std::collections::{HashMap, HashSet};
 use std::pin::Pin;
 use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::{Context, Result, bail};
+use futures::StreamExt;
 use sea_orm::DatabaseConnection;
 use serde::Serialize;
 use tokio::sync::{OnceCell, watch};
 use tokio_cron_scheduler::{Job, JobScheduler};
+use tokio_util::sync::CancellationToken;
 
-use crate::adapter::VideoSource;
+use crate::adapter::{VideoSource, VideoSourceEnum};
 use crate::bilibili::{self, BiliClient, BiliError};
-use crate::config::{ARGS, Config, TEMPLATE, Trigger, VersionedConfig};
+use crate::config::{ARGS, Config, NotificationDigest, TEMPLATE, Trigger, VersionedConfig};
+use crate::notifier::{NotificationEvent, SourceSummary};
+use crate::task::relayout::{RelayoutReport, relayout};
+use crate::utils::download_window::{self, WindowState};
 use crate::utils::model::get_enabled_video_sources;
-use crate::utils::notify::error_and_notify;
-use crate::workflow::process_video_source;
+use crate::utils::notify::{error_and_notify, flush_digest, notify};
+use crate::utils::queue::{
+    QueueStats, count_pending_items, refresh_queue_stats, reset_running_items, subscribe_queue_stats,
+};
+use crate::utils::risk_control;
+use crate::utils::task_control::TaskControl;
+use crate::workflow::{download_queued_videos, process_video_source};
 
 static INSTANCE: OnceCell<DownloadTaskManager> = OnceCell::const_new();
 
@@ -32,9 +43,19 @@ pub struct DownloadTaskManager {
 #[derive(Serialize, Default, Clone, Copy, Debug)]
 pub struct TaskStatus {
     is_running: bool,
+    is_paused: bool,
     last_run: Option<chrono::DateTime<chrono::Local>>,
     last_finish: Option<chrono::DateTime<chrono::Local>>,
     next_run: Option<chrono::DateTime<chrono::Local>>,
+    /// 当前运行的任务仅处理单个视频源时，记录该视频源
+    source: Option<SourceKey>,
+    queue: QueueStats,
+}
+
+#[derive(Serialize, Clone, Copy, Debug)]
+pub struct SourceKey {
+    source_type: &'static str,
+    id: i32,
 }
 
 struct TaskContext {
@@ -44,6 +65,37 @@ struct TaskContext {
     status_tx: watch::Sender<TaskStatus>,
     status_rx: watch::Receiver<TaskStatus>,
     video_task_id: tokio::sync::Mutex<Option<uuid::Uuid>>, // 存储当前视频下载任务的 UUID
+    digest_task_id: tokio::sync::Mutex<Option<uuid::Uuid>>, // 存储定时发送通知汇总任务的 UUID
+    source_task_ids: tokio::sync::Mutex<SourceTasks>,      // 存储单独设置了触发条件的视频源任务
+    control: parking_lot::Mutex<Option<TaskControl>>,      // 当前正在运行的视频下载任务的控制器
+    waiting: parking_lot::Mutex<HashSet<TaskScope>>,       // 正在等待上一轮任务结束的视频下载任务范围
+    retry_task_ids: tokio::sync::Mutex<HashMap<TaskScope, uuid::Uuid>>, // 存储各范围计划中的自动重试任务
+}
+
+type JobRun = Box<dyn FnMut(uuid::Uuid, JobScheduler) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>;
+
+/// 视频源与其下载任务的触发条件、UUID 的映射
+type SourceTasks = HashMap<(&'static str, i32), (Trigger, uuid::Uuid)>;
+
+/// 一轮下载任务需要处理的视频源范围
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum TaskScope {
+    /// 全部启用的视频源，用于手动触发
+    All,
+    /// 未单独设置触发条件的视频源，跟随全局触发条件执行
+    Default,
+    /// 单独设置了触发条件的某个视频源
+    Source(&'static str, i32),
+}
+
+impl TaskScope {
+    fn contains(&self, video_source: &VideoSourceEnum) -> bool {
+        match self {
+            TaskScope::All => true,
+            TaskScope::Default => video_source.trigger().is_none(),
+            TaskScope::Source(source_type, id) => video_source.source_key() == (*source_type, *id),
+        }
+    }
 }
 
 impl DownloadTaskManager {
@@ -75,12 +127,96 @@ impl DownloadTaskManager {
             .await
             .add(Job::new_one_shot_async(
                 Duration::from_secs(0),
-                DownloadTaskManager::download_video_task(self.cx.clone()),
+                DownloadTaskManager::download_video_task(self.cx.clone(), TaskScope::All),
+            )?)
+            .await?;
+        Ok(())
+    }
+
+    /// 暂停正在运行的视频下载任务，正在下载的视频会被中断，恢复后重新下载未完成的部分
+    pub fn pause(&self) -> Result<bool> {
+        let changed = self.running_control()?.pause();
+        if changed {
+            info!("视频下载任务已暂停");
+            self.cx.status_tx.send_modify(|status| status.is_paused = true);
+        }
+        Ok(changed)
+    }
+
+    /// 恢复已暂停的视频下载任务
+    pub fn resume(&self) -> Result<bool> {
+        let changed = self.running_control()?.resume();
+        if changed {
+            info!("视频下载任务已恢复");
+            self.cx.status_tx.send_modify(|status| status.is_paused = false);
+        }
+        Ok(changed)
+    }
+
+    /// 取消正在运行的视频下载任务，已完成的部分会正常保存，其余内容留待下一轮处理
+    pub fn cancel(&self) -> Result<bool> {
+        let changed = self.running_control()?.cancel();
+        if changed {
+            warn!("视频下载任务已被取消，将在当前正在处理的内容中断后结束");
+        }
+        Ok(changed)
+    }
+
+    fn running_control(&self) -> Result<TaskControl> {
+        self.cx.control.lock().clone().context("当前没有正在运行的视频下载任务")
+    }
+
+    /// 立即执行一次单个视频源的下载任务，与其它下载任务互斥执行，已有任务在运行时会等待其结束
+    pub async fn sync_source(&self, video_source: &VideoSourceEnum) -> Result<()> {
+        let (source_type, id) = video_source.source_key();
+        info!("手动触发{}的同步..", video_source.display_name());
+        let _ = self
+            .sched
+            .lock()
+            .await
+            .add(Job::new_one_shot_async(
+                Duration::from_secs(0),
+                DownloadTaskManager::download_video_task(self.cx.clone(), TaskScope::Source(source_type, id)),
             )?)
             .await?;
         Ok(())
     }
 
+    /// 视频源变更后重新注册视频源单独的下载任务
+    ///
+    /// 调用时视频源的变更已经保存，失败时仅记录并通知错误，返回错误信息供调用方展示
+    pub async fn reload_source_tasks(&self) -> Option<String> {
+        let err = DownloadTaskManager::reschedule_sources(&self.sched, &self.cx)
+            .await
+            .err()?;
+        let msg = format!("重新注册视频源下载任务失败：{:#}", err);
+        error_and_notify(&VersionedConfig::get().read(), &self.cx.bili_client, msg.clone());
+        Some(msg)
+    }
+
+    /// 使用当前模板重新整理已下载的文件，与下载任务互斥执行
+    pub async fn relayout(&self, dry_run: bool) -> Result<RelayoutReport> {
+        let Ok(_lock) = self.cx.running.try_lock() else {
+            bail!("视频下载任务正在运行，请等待其结束后再重新整理文件");
+        };
+        let (config, template) = (VersionedConfig::get().snapshot(), TEMPLATE.snapshot());
+        if dry_run {
+            info!("开始预览文件重新整理结果..");
+        } else {
+            info!("开始按照当前模板重新整理文件..");
+        }
+        let report = relayout(&self.cx.connection, &self.cx.bili_client, &config, &template, dry_run).await?;
+        info!(
+            "文件重新整理{}完毕，共 {} 个视频需要调整，成功 {} 个，冲突 {} 个，失败 {} 个",
+            if dry_run { "预览" } else { "" },
+            report.planned,
+            report.relocated,
+            report.conflicted,
+            report.failed
+        );
+        Ok(report)
+    }
+
     /// 启动任务调度器
     async fn start(&self) -> Result<()> {
         self.sched.lock().await.start().await?;
@@ -105,6 +241,25 @@ impl DownloadTaskManager {
             status_tx,
             status_rx,
             video_task_id,
+            digest_task_id: tokio::sync::Mutex::new(None),
+            source_task_ids: tokio::sync::Mutex::new(HashMap::new()),
+            control: parking_lot::Mutex::new(None),
+            waiting: parking_lot::Mutex::new(HashSet::new()),
+            retry_task_ids: tokio::sync::Mutex::new(HashMap::new()),
+        });
+        // 恢复上次运行中断的下载队列，并将队列的统计信息同步到任务状态中
+        reset_running_items(&cx.connection).await.context("恢复下载队列失败")?;
+        refresh_queue_stats(&cx.connection).await?;
+        let cx_clone = cx.clone();
+        tokio::spawn(async move {
+            let mut queue_rx = subscribe_queue_stats();
+            loop {
+                let queue = *queue_rx.borrow_and_update();
+                cx_clone.status_tx.send_modify(|status| status.queue = queue);
+                if queue_rx.changed().await.is_err() {
+                    break;
+                }
+            }
         });
         // 读取初始配置
         let mut rx = VersionedConfig::get().subscribe();
@@ -125,11 +280,10 @@ impl DownloadTaskManager {
         }
         // 初始化并添加视频下载任务，将任务 ID 保存到 TaskManager 中
         let video_task_id = async {
-            let job_run = DownloadTaskManager::download_video_task(cx.clone());
-            let job = match &initial_config.interval {
-                Trigger::Interval(interval) => Job::new_repeated_async(Duration::from_secs(*interval), job_run)?,
-                Trigger::Cron(cron) => Job::new_async_tz(cron, chrono::Local, job_run)?,
-            };
+            let job = new_job(
+                &initial_config.interval,
+                DownloadTaskManager::download_video_task(cx.clone(), TaskScope::Default),
+            )?;
             Result::<_, anyhow::Error>::Ok(sched.lock().await.add(job).await?)
         }
         .await;
@@ -156,6 +310,21 @@ impl DownloadTaskManager {
                 )?)
                 .await?;
         }
+        if let Err(err) = DownloadTaskManager::reschedule_digest(&sched, &cx, &initial_config).await {
+            error_and_notify(
+                &initial_config,
+                &cx.bili_client,
+                format!("初始化通知汇总任务失败：{:#}", err),
+            );
+        }
+        // 为单独设置了触发条件的视频源添加各自的下载任务
+        if let Err(err) = DownloadTaskManager::reschedule_sources(&sched, &cx).await {
+            error_and_notify(
+                &initial_config,
+                &cx.bili_client,
+                format!("初始化视频源下载任务失败：{:#}", err),
+            );
+        }
         // 发起一个新任务，用来监听配置变更，动态更新视频下载任务
         let cx_clone = cx.clone();
         let sched_clone = sched.clone();
@@ -176,13 +345,10 @@ impl DownloadTaskManager {
                             .context("移除旧的视频下载任务失败")?;
                     }
                     let new_video_task_id = async {
-                        let job_run = DownloadTaskManager::download_video_task(cx.clone());
-                        let job = match &new_config.interval {
-                            Trigger::Interval(interval) => {
-                                Job::new_repeated_async(Duration::from_secs(*interval), job_run)?
-                            }
-                            Trigger::Cron(cron) => Job::new_async_tz(cron, chrono::Local, job_run)?,
-                        };
+                        let job = new_job(
+                            &new_config.interval,
+                            DownloadTaskManager::download_video_task(cx.clone(), TaskScope::Default),
+                        )?;
                         Result::<_, anyhow::Error>::Ok(sched_clone.lock().await.add(job).await?)
                     }
                     .await;
@@ -208,6 +374,10 @@ impl DownloadTaskManager {
                             )?)
                             .await?;
                     }
+                    drop(video_task_id);
+                    if let Err(err) = DownloadTaskManager::reschedule_digest(&sched_clone, &cx, &new_config).await {
+                        error_and_notify(&new_config, &cx.bili_client, format!("重载通知汇总任务失败：{:#}", err));
+                    }
                 }
                 Result::<(), anyhow::Error>::Ok(())
             }
@@ -218,6 +388,81 @@ impl DownloadTaskManager {
         Ok(Self { sched, cx, shutdown_rx })
     }
 
+    /// 根据视频源单独设置的触发条件增删对应的下载任务，未变化的任务保持不动
+    async fn reschedule_sources(sched: &tokio::sync::Mutex<JobScheduler>, cx: &Arc<TaskContext>) -> Result<()> {
+        let video_sources = get_enabled_video_sources(&cx.connection)
+            .await
+            .context("获取视频源列表失败")?;
+        let mut source_task_ids = cx.source_task_ids.lock().await;
+        let sched = sched.lock().await;
+        let expected = video_sources
+            .iter()
+            .filter_map(|video_source| Some((video_source.source_key(), video_source.trigger().as_ref()?)))
+            .collect::<HashMap<_, _>>();
+        // 移除已被删除、禁用或修改了触发条件的视频源任务
+        let stale_keys = source_task_ids
+            .iter()
+            .filter(|(key, (trigger, _))| expected.get(key) != Some(&trigger))
+            .map(|(key, _)| *key)
+            .collect::<Vec<_>>();
+        for key in stale_keys {
+            if let Some((_, task_id)) = source_task_ids.remove(&key) {
+                sched.remove(&task_id).await.context("移除旧的视频源下载任务失败")?;
+            }
+        }
+        for video_source in &video_sources {
+            let (key, Some(trigger)) = (video_source.source_key(), video_source.trigger()) else {
+                continue;
+            };
+            if source_task_ids.contains_key(&key) {
+                continue;
+            }
+            let job_run = DownloadTaskManager::download_video_task(cx.clone(), TaskScope::Source(key.0, key.1));
+            match new_job(trigger, job_run) {
+                Ok(job) => {
+                    source_task_ids.insert(key, (trigger.clone(), sched.add(job).await?));
+                }
+                Err(e) => error!("为{}添加下载任务失败：{:#}", video_source.display_name(), e),
+            }
+        }
+        Ok(())
+    }
+
+    /// 根据配置重新添加定时发送通知汇总的任务，关闭汇总时会立即发送尚未发出的汇总
+    async fn reschedule_digest(
+        sched: &tokio::sync::Mutex<JobScheduler>,
+        cx: &Arc<TaskContext>,
+        config: &Config,
+    ) -> Result<()> {
+        let mut digest_task_id = cx.digest_task_id.lock().await;
+        if let Some(old_digest_task_id) = digest_task_id.take() {
+            sched
+                .lock()
+                .await
+                .remove(&old_digest_task_id)
+                .await
+                .context("移除旧的通知汇总任务失败")?;
+        }
+        let cron = match &config.notification_digest {
+            NotificationDigest::Cron(cron) => cron,
+            // 每轮汇总的内容会在本轮任务结束时发送，此处发送会把正在进行的一轮拆成两条
+            NotificationDigest::PerRun => return Ok(()),
+            NotificationDigest::Off => {
+                flush_digest(config, &cx.bili_client);
+                return Ok(());
+            }
+        };
+        let bili_client = cx.bili_client.clone();
+        let job = Job::new_async_tz(cron, chrono::Local, move |_uuid, _l| {
+            let bili_client = bili_client.clone();
+            Box::pin(async move {
+                flush_digest(&VersionedConfig::get().read(), &bili_client);
+            })
+        })?;
+        *digest_task_id = Some(sched.lock().await.add(job).await?);
+        Ok(())
+    }
+
     fn check_and_refresh_credential_task(
         cx: Arc<TaskContext>,
     ) -> impl FnMut(uuid::Uuid, JobScheduler) -> Pin<Box<dyn Future<Output = ()> + Send>> {
@@ -230,10 +475,13 @@ impl DownloadTaskManager {
                 match check_and_refresh_credential(&cx.connection, &cx.bili_client, &config).await {
                     Ok(_) => info!("本轮凭据检查与刷新任务执行完毕"),
                     Err(e) => {
-                        error_and_notify(
+                        error!("本轮凭据检查与刷新任务执行遇到错误：{:#}", e);
+                        notify(
                             &config,
                             &cx.bili_client,
-                            format!("本轮凭据检查与刷新任务执行遇到错误：{:#}", e),
+                            NotificationEvent::CredentialRefreshFailed {
+                                error: format!("{:#}", e),
+                            },
                         );
                     }
                 }
@@ -248,44 +496,109 @@ impl DownloadTaskManager {
         move |_uuid, mut l| {
             let cx = cx.clone();
             Box::pin(async move {
-                let old_status = *cx.status_rx.borrow();
                 let next_run = l
                     .next_tick_for_job(video_task_id)
                     .await
                     .ok()
                     .flatten()
                     .map(|dt| dt.with_timezone(&chrono::Local));
-                let _ = cx.status_tx.send(TaskStatus { next_run, ..old_status });
+                cx.status_tx.send_modify(|status| status.next_run = next_run);
             })
         }
     }
 
-    fn download_video_task(
-        cx: Arc<TaskContext>,
-    ) -> impl FnMut(uuid::Uuid, JobScheduler) -> Pin<Box<dyn Future<Output = ()> + Send>> {
-        move |uuid, mut l| {
+    /// 新一轮任务会处理范围内的全部内容，移除之前为该范围计划的自动重试任务，全量任务会移除所有的自动重试任务
+    async fn clear_retry(cx: &TaskContext, sched: &JobScheduler, scope: TaskScope, current: uuid::Uuid) {
+        let mut retry_task_ids = cx.retry_task_ids.lock().await;
+        let scopes = match scope {
+            TaskScope::All => retry_task_ids.keys().copied().collect(),
+            scope => vec![scope],
+        };
+        for scope in scopes {
+            // 当前执行的可能正是该自动重试任务，一次性任务执行后会被自动移除
+            if let Some(retry_task_id) = retry_task_ids.remove(&scope)
+                && retry_task_id != current
+                && let Err(e) = sched.remove(&retry_task_id).await
+            {
+                warn!("移除自动重试任务失败：{:#}", e);
+            }
+        }
+        if retry_task_ids.is_empty() {
+            risk_control::clear_retry();
+        }
+    }
+
+    /// 触发风控后会以相同的参数重新添加该任务，因此返回值需要使用具体的类型
+    fn download_video_task(cx: Arc<TaskContext>, scope: TaskScope) -> JobRun {
+        Box::new(move |uuid, mut l| {
             let cx = cx.clone();
             Box::pin(async move {
-                let Ok(_lock) = cx.running.try_lock() else {
-                    warn!("上一次视频下载任务尚未结束，跳过本次执行..");
+                // 下载任务互斥执行，正在运行时等待其结束，避免视频源单独的任务在长时间的下载中被丢弃
+                // 相同范围的任务只需要保留一个在等待，它执行时会处理该范围内的全部内容
+                if !cx.waiting.lock().insert(scope) {
+                    info!("相同范围的视频下载任务已在等待执行，跳过本次执行..");
                     return;
-                };
-                let _ = cx.status_tx.send(TaskStatus {
-                    is_running: true,
-                    last_run: Some(chrono::Local::now()),
-                    last_finish: None,
-                    next_run: None,
+                }
+                let _lock = cx.running.lock().await;
+                cx.waiting.lock().remove(&scope);
+                cx.status_tx.send_modify(|status| {
+                    status.is_running = true;
+                    status.source = match scope {
+                        TaskScope::Source(source_type, id) => Some(SourceKey { source_type, id }),
+                        _ => None,
+                    };
+                    status.last_run = Some(chrono::Local::now());
+                    status.last_finish = None;
+                    status.next_run = None;
                 });
                 info!("开始执行本轮视频下载任务..");
+                DownloadTaskManager::clear_retry(&cx, &l, scope, uuid).await;
                 let mut config = VersionedConfig::get().snapshot();
-                match download_video(&cx.connection, &cx.bili_client, &mut config).await {
-                    Ok(_) => info!("本轮视频下载任务执行完毕"),
+                let control = TaskControl::new();
+                *cx.control.lock() = Some(control.clone());
+                let res = download_video(&cx.connection, &cx.bili_client, &mut config, scope, &control).await;
+                *cx.control.lock() = None;
+                let retry_after = match res {
+                    Ok(round) => {
+                        info!("本轮视频下载任务执行完毕");
+                        round.retry_after
+                    }
                     Err(e) => {
                         error_and_notify(
                             &config,
                             &cx.bili_client,
                             format!("本轮视频下载任务执行遇到错误：{:#}", e),
                         );
+                        None
+                    }
+                };
+                // 触发风控或下载时间段结束后，在等待一段时间后自动重新执行本轮任务
+                let mut retry_at = None;
+                if let Some(retry_after) = retry_after {
+                    let job = Job::new_one_shot_async(
+                        retry_after,
+                        DownloadTaskManager::download_video_task(cx.clone(), scope),
+                    );
+                    let res = match job {
+                        Ok(job) => l.add(job).await.map_err(anyhow::Error::from),
+                        Err(e) => Err(e.into()),
+                    };
+                    match res {
+                        Ok(retry_task_id) => {
+                            // 每个范围最多保留一个自动重试任务，替换掉之前计划的任务
+                            if let Some(old_task_id) = cx.retry_task_ids.lock().await.insert(scope, retry_task_id)
+                                && old_task_id != uuid
+                                && let Err(e) = l.remove(&old_task_id).await
+                            {
+                                warn!("移除自动重试任务失败：{:#}", e);
+                            }
+                            info!(
+                                "将在 {} 分钟后重新执行本轮视频下载任务",
+                                retry_after.as_secs().div_ceil(60)
+                            );
+                            retry_at = Some(chrono::Local::now() + retry_after);
+                        }
+                        Err(e) => error!("添加自动重试任务失败：{:#}", e),
                     }
                 }
                 // 注意此处尽量从 updating 中读取 uuid，因为当前任务可能是不存在 next_tick 的 oneshot 任务
@@ -296,15 +609,19 @@ impl DownloadTaskManager {
                     .ok()
                     .flatten()
                     .map(|dt| dt.with_timezone(&chrono::Local));
-                let last_status = *cx.status_rx.borrow();
-                let _ = cx.status_tx.send(TaskStatus {
-                    is_running: false,
-                    last_run: last_status.last_run,
-                    last_finish: Some(chrono::Local::now()),
-                    next_run,
+                let next_run = match (next_run, retry_at) {
+                    (Some(next_run), Some(retry_at)) => Some(next_run.min(retry_at)),
+                    (next_run, retry_at) => next_run.or(retry_at),
+                };
+                cx.status_tx.send_modify(|status| {
+                    status.is_running = false;
+                    status.is_paused = false;
+                    status.source = None;
+                    status.last_finish = Some(chrono::Local::now());
+                    status.next_run = next_run;
                 });
             })
-        }
+        })
     }
 }
 
@@ -332,11 +649,46 @@ async fn check_and_refresh_credential(
     Ok(())
 }
 
+/// 一轮视频下载任务的执行结果
+#[derive(Default)]
+pub struct RoundResult {
+    pub sources: Vec<SourceSummary>,
+    /// 需要在多久之后自动重新执行本轮任务
+    pub retry_after: Option<Duration>,
+}
+
+/// 不启动任务调度器，直接执行一轮全部视频源的下载任务
+pub async fn run_once(
+    connection: &DatabaseConnection,
+    bili_client: &BiliClient,
+    control: &TaskControl,
+) -> Result<RoundResult> {
+    reset_running_items(connection).await.context("恢复下载队列失败")?;
+    refresh_queue_stats(connection).await?;
+    if !ARGS.disable_credential_refresh {
+        let config = VersionedConfig::get().snapshot();
+        if let Err(e) = check_and_refresh_credential(connection, bili_client, &config).await {
+            error!("凭据检查与刷新遇到错误：{:#}", e);
+            notify(
+                &config,
+                bili_client,
+                NotificationEvent::CredentialRefreshFailed {
+                    error: format!("{:#}", e),
+                },
+            );
+        }
+    }
+    let mut config = VersionedConfig::get().snapshot();
+    download_video(connection, bili_client, &mut config, TaskScope::All, control).await
+}
+
 async fn download_video(
     connection: &DatabaseConnection,
     bili_client: &BiliClient,
     config: &mut Arc<Config>,
-) -> Result<()> {
+    scope: TaskScope,
+    control: &TaskControl,
+) -> Result<RoundResult> {
     config.check().context("配置检查失败")?;
     let mixin_key = bili_client
         .wbi_img(&config.credential)
@@ -353,21 +705,187 @@ async fn download_video(
     if video_sources.is_empty() {
         bail!("没有可用的视频源");
     }
-    for video_source in video_sources {
-        let display_name = video_source.display_name();
-        if let Err(e) = process_video_source(video_source, &bili_client, connection, &template, config).await {
-            error_and_notify(
-                config,
-                &bili_client,
-                format!("处理 {} 时遇到错误：{:#}，跳过该视频源", display_name, e),
-            );
-            if let Ok(e) = e.downcast::<BiliError>()
-                && e.is_risk_control_related()
-            {
-                warn!("检测到风控，终止此轮视频下载任务..");
-                break;
+    let video_sources = video_sources
+        .into_iter()
+        .filter(|video_source| scope.contains(video_source))
+        .collect::<Vec<_>>();
+    if video_sources.is_empty() {
+        info!("没有需要在本轮处理的视频源，跳过");
+        return Ok(RoundResult::default());
+    }
+    notify(config, &bili_client, NotificationEvent::RunStarted);
+    // 多个视频源并发扫描，共享同一个 BiliClient 的限流器；任一视频源触发风控时取消其余视频源
+    let token = control.token();
+    let config: &Config = config;
+    let bili_client = &bili_client;
+    // 扫描完成的视频源立即加入下载，不必等待其它视频源扫描结束
+    let (sources_tx, sources_rx) = watch::channel(Vec::new());
+    let scan = async move {
+        let mut results = futures::stream::iter(video_sources)
+            .map(|video_source| async move {
+                let display_name = video_source.display_name();
+                // 任务暂停期间不再开始扫描新的视频源
+                control.wait_resumed().await;
+                let result = process_video_source(video_source, bili_client, connection, config, token).await;
+                (display_name, result)
+            })
+            .buffer_unordered(config.concurrent_limit.source);
+        let (mut sources, mut scanned_sources, mut retry_after) = (Vec::new(), Vec::new(), None);
+        while let Some((display_name, result)) = results.next().await {
+            match result {
+                Ok((video_source, summary)) => {
+                    if summary.error.is_none() {
+                        let video_source = Arc::new(video_source);
+                        sources_tx.send_modify(|s| s.push(video_source.clone()));
+                        scanned_sources.push((video_source, sources.len()));
+                    }
+                    sources.push(summary);
+                }
+                Err(e) => {
+                    sources.push(SourceSummary {
+                        name: display_name.to_string(),
+                        new_videos: 0,
+                        downloaded: 0,
+                        failed: 0,
+                        error: Some(format!("{:#}", e)),
+                    });
+                    retry_after = retry_after.or(handle_source_error(config, bili_client, token, &display_name, &e));
+                }
             }
         }
+        // 丢弃发送端，通知下载队列不会再有新的视频源加入
+        drop(sources_tx);
+        (sources, scanned_sources, retry_after)
+    };
+    let window_state = download_window::window_state(&config.download_windows);
+    let download = async {
+        if ARGS.scan_only {
+            warn!("已开启仅扫描模式，跳过视频下载..");
+            return (HashMap::new(), None);
+        }
+        if let WindowState::Closed(_) = window_state {
+            info!("当前不在允许下载的时间段内，跳过视频下载..");
+            return (HashMap::new(), None);
+        }
+        // 所有扫描成功的视频源共享同一个下载队列，按照优先级与队列顺序下载
+        let download = download_queued_videos(sources_rx, bili_client, connection, &template, config, control);
+        let res = match window_state {
+            WindowState::Open(Some(window_end)) => until_window_end(download, window_end, control).await,
+            _ => download.await,
+        };
+        match res {
+            Ok(counts) => (counts, None),
+            // 下载时触发风控会立即取消本轮任务，停止扫描其余的视频源
+            Err(e) => (
+                HashMap::new(),
+                handle_source_error(config, bili_client, token, "下载队列", &e),
+            ),
+        }
+    };
+    let ((mut sources, scanned_sources, mut retry_after), (counts, download_retry_after)) =
+        tokio::join!(scan, download);
+    retry_after = retry_after.or(download_retry_after);
+    for (video_source, idx) in &scanned_sources {
+        if let Some((downloaded, failed)) = counts.get(&video_source.source_key()) {
+            (sources[*idx].downloaded, sources[*idx].failed) = (*downloaded, *failed);
+        }
     }
-    Ok(())
+    // 本轮处理的视频源仍有视频等待下载时，在下一个下载时间段开始时重新执行本轮任务
+    if !ARGS.scan_only
+        && !scanned_sources.is_empty()
+        && let WindowState::Closed(Some(next_start)) = download_window::window_state(&config.download_windows)
+    {
+        let source_keys = scanned_sources
+            .iter()
+            .map(|(video_source, _)| video_source.source_key())
+            .collect::<Vec<_>>();
+        if count_pending_items(&source_keys, connection).await.unwrap_or_default() > 0 {
+            info!(
+                "剩余的视频将在下一个下载时间段（{}）开始后继续下载",
+                next_start.format("%m-%d %H:%M")
+            );
+            retry_after = retry_after.or((next_start - chrono::Local::now()).to_std().ok());
+        }
+    }
+    if config.notification_digest == NotificationDigest::PerRun {
+        flush_digest(config, bili_client);
+    }
+    notify(
+        config,
+        bili_client,
+        NotificationEvent::RunFinished {
+            sources: sources.clone(),
+        },
+    );
+    Ok(RoundResult { sources, retry_after })
+}
+
+/// 执行视频下载，到达下载时间段的结束时间时取消本轮任务，正在下载的分页会在写入已完成的子任务后返回
+async fn until_window_end<T>(
+    download: impl Future<Output = T>,
+    window_end: chrono::DateTime<chrono::Local>,
+    control: &TaskControl,
+) -> T {
+    tokio::pin!(download);
+    let wait = (window_end - chrono::Local::now()).to_std().unwrap_or_default();
+    tokio::select! {
+        res = &mut download => res,
+        _ = tokio::time::sleep(wait) => {
+            info!("已到达下载时间段的结束时间，停止下载..");
+            control.cancel();
+            download.await
+        }
+    }
+}
+
+/// 处理视频源出现的错误，触发风控时取消本轮的其余任务并发送风控通知
+///
+/// 首次触发风控时记录风控事件，返回自动重试前需要等待的时间
+fn handle_source_error(
+    config: &Config,
+    bili_client: &BiliClient,
+    token: &CancellationToken,
+    display_name: &str,
+    e: &anyhow::Error,
+) -> Option<Duration> {
+    let error = format!("{:#}", e);
+    if let Some(e) = e.downcast_ref::<BiliError>()
+        && e.is_risk_control_related()
+    {
+        error!("处理 {} 时遇到错误：{}，跳过该视频源", display_name, error);
+        // 多个视频源可能先后触发风控，只在第一次时终止任务并发送通知
+        if token.is_cancelled() {
+            return None;
+        }
+        warn!("检测到风控，终止此轮视频下载任务..");
+        token.cancel();
+        let retry_after = risk_control::record(display_name, &error, &config.risk_control);
+        notify(
+            config,
+            bili_client,
+            NotificationEvent::RiskControl {
+                source: display_name.to_string(),
+                error,
+                level: risk_control::state().level,
+                retry_after: retry_after.map(|d| d.as_secs()),
+            },
+        );
+        return retry_after;
+    }
+    error_and_notify(
+        config,
+        bili_client,
+        format!("处理 {} 时遇到错误：{}，跳过该视频源", display_name, error),
+    );
+    None
+}
+
+fn new_job(
+    trigger: &Trigger,
+    job_run: impl FnMut(uuid::Uuid, JobScheduler) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync + 'static,
+) -> Result<Job> {
+    Ok(match trigger {
+        Trigger::Interval(interval) => Job::new_repeated_async(Duration::from_secs(*interval), job_run)?,
+        Trigger::Cron(cron) => Job::new_async_tz(cron.as_str(), chrono::Local, job_run)?,
+    })
 }
@@ -183,6 +183,8 @@ impl VideoInfo {
                 is_upower_exclusive,
                 is_upower_play,
                 redirect_url,
+                tname,
+                stat,
                 ..
             } => bili_sync_entity::video::ActiveModel {
                 bvid: Set(bvid),
@@ -210,6 +212,11 @@ impl VideoInfo {
                 upper_id: Set(upper.mid),
                 upper_name: Set(upper.name),
                 upper_face: Set(upper.face),
+                play_count: Set(stat.view),
+                like_count: Set(stat.like),
+                coin_count: Set(stat.coin),
+                favorite_count: Set(stat.favorite),
+                partition_name: Set((!tname.is_empty()).then_some(tname)),
                 ..base_model.into_active_model()
             },
             _ => unreachable!(),
@@ -1,4 +1,5 @@
 use sea_orm::DatabaseConnection;
+use tokio_util::sync::CancellationToken;
 
 use crate::adapter::VideoSourceEnum;
 use crate::bilibili::BiliClient;
@@ -13,6 +14,8 @@ pub struct DownloadContext<'a> {
     pub connection: &'a DatabaseConnection,
     pub downloader: &'a Downloader,
     pub config: &'a Config,
+    /// 被取消时中断正在执行的子任务
+    pub token: &'a CancellationToken,
 }
 
 impl<'a> DownloadContext<'a> {
@@ -23,6 +26,7 @@ impl<'a> DownloadContext<'a> {
         connection: &'a DatabaseConnection,
         downloader: &'a Downloader,
         config: &'a Config,
+        token: &'a CancellationToken,
     ) -> Self {
         Self {
             bili_client,
@@ -31,6 +35,7 @@ impl<'a> DownloadContext<'a> {
             connection,
             downloader,
             config,
+            token,
         }
     }
 }
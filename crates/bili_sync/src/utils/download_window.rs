@@ -0,0 +1,115 @@
+use chrono::{DateTime, Datelike, Local, NaiveDateTime, TimeDelta};
+
+use crate::config::DownloadWindow;
+
+/// 当前时间相对于下载时间段的状态
+#[derive(Debug, PartialEq)]
+pub enum WindowState {
+    /// 未配置下载时间段，随时可以下载
+    Unrestricted,
+    /// 处于下载时间段内，包含当前时间段的结束时间
+    Open(Option<DateTime<Local>>),
+    /// 不在下载时间段内，包含下一个时间段的开始时间
+    Closed(Option<DateTime<Local>>),
+}
+
+/// 获取当前时间相对于下载时间段的状态
+pub fn window_state(windows: &[DownloadWindow]) -> WindowState {
+    if windows.is_empty() {
+        return WindowState::Unrestricted;
+    }
+    let now = Local::now().naive_local();
+    let to_local = |dt: NaiveDateTime| dt.and_local_timezone(Local).earliest();
+    match window_end_at(windows, now) {
+        Some(end) => WindowState::Open(to_local(end)),
+        None => WindowState::Closed(next_start_at(windows, now).and_then(to_local)),
+    }
+}
+
+/// 展开 now 前后一周内所有时间段的起止时间，按照开始时间排序
+fn intervals(windows: &[DownloadWindow], now: NaiveDateTime) -> Vec<(NaiveDateTime, NaiveDateTime)> {
+    let mut intervals = Vec::new();
+    for offset in -1..=7 {
+        let day = now.date() + TimeDelta::days(offset);
+        for window in windows.iter().filter(|window| window.weekdays.contains(&day.weekday())) {
+            let end_day = if window.end > window.start {
+                day
+            } else {
+                day + TimeDelta::days(1)
+            };
+            intervals.push((day.and_time(window.start), end_day.and_time(window.end)));
+        }
+    }
+    intervals.sort_unstable();
+    intervals
+}
+
+/// 当前所在时间段的结束时间，首尾相接或重叠的时间段视为同一个，不在任何时间段内时返回 None
+fn window_end_at(windows: &[DownloadWindow], now: NaiveDateTime) -> Option<NaiveDateTime> {
+    let mut window_end = None;
+    for (start, end) in intervals(windows, now) {
+        match window_end {
+            None if start <= now && now < end => window_end = Some(end),
+            Some(current) if start <= current => window_end = Some(end.max(current)),
+            Some(_) => break,
+            None => {}
+        }
+    }
+    window_end
+}
+
+/// 下一个时间段的开始时间
+fn next_start_at(windows: &[DownloadWindow], now: NaiveDateTime) -> Option<NaiveDateTime> {
+    intervals(windows, now)
+        .into_iter()
+        .map(|(start, _)| start)
+        .find(|start| *start > now)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, NaiveTime, Weekday};
+
+    use super::*;
+
+    fn time(hour: u32, min: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, min, 0).unwrap()
+    }
+
+    fn datetime(day: u32, hour: u32, min: u32) -> NaiveDateTime {
+        // 2025-09-01 为星期一
+        NaiveDate::from_ymd_opt(2025, 9, day).unwrap().and_time(time(hour, min))
+    }
+
+    #[test]
+    fn test_download_window() {
+        let windows = vec![
+            // 工作日夜间，跨越午夜
+            DownloadWindow {
+                weekdays: vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri],
+                start: time(22, 0),
+                end: time(7, 0),
+            },
+            // 周末全天
+            DownloadWindow {
+                weekdays: vec![Weekday::Sat, Weekday::Sun],
+                start: time(0, 0),
+                end: time(0, 0),
+            },
+        ];
+        // 设置页面提交的时间不包含秒
+        let window: DownloadWindow =
+            serde_json::from_str(r#"{"weekdays": ["Mon", "Tue"], "start": "22:00", "end": "07:00"}"#).unwrap();
+        assert_eq!(window.weekdays, [Weekday::Mon, Weekday::Tue]);
+        assert_eq!((window.start, window.end), (time(22, 0), time(7, 0)));
+        // 星期一白天不允许下载，等待当晚的时间段
+        assert_eq!(window_end_at(&windows, datetime(1, 12, 0)), None);
+        assert_eq!(next_start_at(&windows, datetime(1, 12, 0)), Some(datetime(1, 22, 0)));
+        // 星期二凌晨仍处于星期一夜间的时间段内
+        assert_eq!(window_end_at(&windows, datetime(2, 3, 0)), Some(datetime(2, 7, 0)));
+        assert_eq!(window_end_at(&windows, datetime(2, 7, 0)), None);
+        // 星期五夜间与周末首尾相接，一直持续到星期一零点
+        assert_eq!(window_end_at(&windows, datetime(5, 23, 0)), Some(datetime(8, 0, 0)));
+        assert_eq!(window_end_at(&windows, datetime(7, 12, 0)), Some(datetime(8, 0, 0)));
+    }
+}
@@ -1,6 +1,8 @@
 use chrono::Datelike;
 use serde_json::json;
 
+use crate::bilibili::bvid_to_aid;
+
 /// 完全基于API的番剧标题提取，无硬编码回退逻辑
 fn extract_series_title_with_context(
     video_model: &bili_sync_entity::video::Model,
@@ -41,34 +43,54 @@ fn extract_series_title_with_context(
     Some(name.to_string())
 }
 
-pub fn video_format_args(video_model: &bili_sync_entity::video::Model, time_format: &str) -> serde_json::Value {
+pub fn video_format_args(
+    video_model: &bili_sync_entity::video::Model,
+    page_models: &[bili_sync_entity::page::Model],
+    time_format: &str,
+) -> serde_json::Value {
     json!({
         "bvid": &video_model.bvid,
+        "avid": bvid_to_aid(&video_model.bvid).ok(),
         "title": &video_model.name,
         "upper_name": &video_model.upper_name,
         "upper_mid": &video_model.upper_id,
+        "tags": video_model.tags.as_ref().map(|tags| tags.0.as_slice()).unwrap_or_default(),
+        "partition": video_model.partition_name.as_deref().unwrap_or(""),
+        "duration": page_models.iter().map(|p| p.duration).sum::<u32>(),
+        "page_count": page_models.len(),
         "pubtime": &video_model.pubtime.and_utc().format(time_format).to_string(),
         "fav_time": &video_model.favtime.and_utc().format(time_format).to_string(),
+        "pubtime_ts": video_model.pubtime.and_utc().timestamp(),
+        "fav_time_ts": video_model.favtime.and_utc().timestamp(),
     })
 }
 
 pub fn page_format_args(
     video_model: &bili_sync_entity::video::Model,
     page_model: &bili_sync_entity::page::Model,
+    page_count: usize,
     time_format: &str,
 ) -> serde_json::Value {
     // 优先使用 show_title，如果没有则使用 name
     let display_title = video_model.show_title.as_ref().unwrap_or(&video_model.name);
     json!({
         "bvid": &video_model.bvid,
+        "avid": bvid_to_aid(&video_model.bvid).ok(),
+        "cid": page_model.cid,
         "title": display_title,
         "name": &video_model.name,
         "upper_name": &video_model.upper_name,
         "upper_mid": &video_model.upper_id,
+        "tags": video_model.tags.as_ref().map(|tags| tags.0.as_slice()).unwrap_or_default(),
+        "partition": video_model.partition_name.as_deref().unwrap_or(""),
         "ptitle": &page_model.name,
         "pid": page_model.pid,
+        "duration": page_model.duration,
+        "page_count": page_count,
         "pubtime": video_model.pubtime.and_utc().format(time_format).to_string(),
         "fav_time": video_model.favtime.and_utc().format(time_format).to_string(),
+        "pubtime_ts": video_model.pubtime.and_utc().timestamp(),
+        "fav_time_ts": video_model.favtime.and_utc().timestamp(),
     })
 }
 
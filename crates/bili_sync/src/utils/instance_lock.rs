@@ -0,0 +1,43 @@
+use std::fs::{File, OpenOptions, TryLockError};
+
+use anyhow::{Result, bail};
+
+use crate::config::CONFIG_DIR;
+
+/// 配置目录的独占锁，持有期间其它进程无法获取，进程退出时由系统自动释放
+pub struct InstanceLock {
+    _file: File,
+}
+
+impl InstanceLock {
+    /// 获取配置目录的独占锁，Web 服务与会修改数据的子命令不能同时运行，避免互相覆盖对方的修改
+    ///
+    /// 仅在另一个进程持有锁时返回错误，文件系统不支持加锁等其它情况下打印警告并返回 None，不影响程序运行
+    pub fn acquire() -> Result<Option<Self>> {
+        let path = CONFIG_DIR.join("bili-sync.lock");
+        let file = match std::fs::create_dir_all(&*CONFIG_DIR)
+            .and_then(|_| OpenOptions::new().create(true).truncate(false).write(true).open(&path))
+        {
+            Ok(file) => file,
+            Err(e) => {
+                warn!(
+                    "打开锁文件 {} 失败，将在不锁定配置目录的情况下运行：{:#}",
+                    path.display(),
+                    e
+                );
+                return Ok(None);
+            }
+        };
+        match file.try_lock() {
+            Ok(()) => Ok(Some(Self { _file: file })),
+            Err(TryLockError::WouldBlock) => bail!(
+                "配置目录 {} 正在被另一个 bili-sync 进程使用，请先停止正在运行的服务",
+                CONFIG_DIR.display()
+            ),
+            Err(TryLockError::Error(e)) => {
+                warn!("锁定 {} 失败，将在不锁定配置目录的情况下运行：{:#}", path.display(), e);
+                Ok(None)
+            }
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use bili_sync_entity::{page, video};
+
+use crate::config::PathSafeTemplate;
+use crate::utils::format_arg::{bangumi_page_format_args, page_format_args, video_format_args};
+
+/// 与分页视频文件同名的附属文件后缀，均由视频文件路径推导而来
+const PAGE_SIDECAR_SUFFIXES: [&str; 8] = [
+    ".mp4",
+    ".nfo",
+    "-poster.jpg",
+    "-thumb.jpg",
+    "-fanart.jpg",
+    ".zh-CN.default.ass",
+    ".danmaku.pb",
+    ".chapters.xml",
+];
+
+/// 多页视频存放在视频目录中的文件
+pub const VIDEO_LEVEL_FILES: [&str; 5] = [
+    "poster.jpg",
+    "fanart.jpg",
+    "tvshow.nfo",
+    "comments.json",
+    "comments.html",
+];
+
+/// 使用当前模板计算出的视频目录与各分页的视频文件路径
+pub struct VideoLayout {
+    pub base_path: PathBuf,
+    pub pages: Vec<(i32, PathBuf)>,
+}
+
+/// 是否为番剧中的视频
+pub fn is_bangumi(video_model: &video::Model) -> bool {
+    video_model.bangumi_id.is_some() || (video_model.source_id.is_some() && video_model.source_type == Some(1))
+}
+
+/// 番剧中的花絮、PV、预告等非正片内容，使用分页模板命名且不放入 Season 1 目录
+pub fn is_bangumi_extra(video_model: &video::Model) -> bool {
+    is_bangumi(video_model)
+        && (section_title(video_model).is_some()
+            || video_model.episode_number.is_none()
+            || video_model.episode_number == Some(0))
+}
+
+fn section_title(video_model: &video::Model) -> Option<&str> {
+    video_model.section_title.as_deref().filter(|s| !s.is_empty())
+}
+
+/// 计算视频目录，番剧花絮直接存放在视频源目录下
+pub fn video_base_path(
+    template: &handlebars::Handlebars<'_>,
+    source_path: &Path,
+    video_model: &video::Model,
+    page_models: &[page::Model],
+    time_format: &str,
+    api_title: Option<&str>,
+) -> Result<PathBuf> {
+    Ok(if is_bangumi(video_model) && section_title(video_model).is_some() {
+        source_path.to_path_buf()
+    } else if is_bangumi(video_model) {
+        let first_page = page_models.first().context("视频缺少分页信息")?;
+        source_path.join(template.path_safe_render(
+            "bangumi",
+            &bangumi_page_format_args(video_model, first_page, time_format, api_title),
+        )?)
+    } else {
+        source_path.join(template.path_safe_render("video", &video_format_args(video_model, page_models, time_format))?)
+    })
+}
+
+/// 计算分页的视频文件路径，其余附属文件的路径均由该路径推导而来
+pub fn page_video_path(
+    template: &handlebars::Handlebars<'_>,
+    base_path: &Path,
+    video_model: &video::Model,
+    page_model: &page::Model,
+    page_count: usize,
+    time_format: &str,
+    api_title: Option<&str>,
+) -> Result<PathBuf> {
+    let is_bangumi_extra = is_bangumi_extra(video_model);
+    let base_name = if is_bangumi(video_model) && !is_bangumi_extra {
+        template.path_safe_render(
+            "bangumi",
+            &bangumi_page_format_args(video_model, page_model, time_format, api_title),
+        )?
+    } else {
+        template.path_safe_render(
+            "page",
+            &page_format_args(video_model, page_model, page_count, time_format),
+        )?
+    };
+    let page_base_path = match section_title(video_model) {
+        Some(section_title) => base_path.join(section_title),
+        None => base_path.to_path_buf(),
+    };
+    Ok(if video_model.single_page.unwrap_or(true) || is_bangumi_extra {
+        page_base_path.join(format!("{}.mp4", base_name))
+    } else {
+        page_base_path
+            .join("Season 1")
+            .join(format!("{} - S01E{:0>2}.mp4", base_name, page_model.pid))
+    })
+}
+
+/// 按照下载时的规则拼接视频与分页的路径
+pub fn compute_layout(
+    template: &handlebars::Handlebars<'_>,
+    source_path: &Path,
+    video_model: &video::Model,
+    page_models: &[page::Model],
+    time_format: &str,
+    api_title: Option<&str>,
+) -> Result<VideoLayout> {
+    let base_path = video_base_path(template, source_path, video_model, page_models, time_format, api_title)?;
+    let pages = page_models
+        .iter()
+        .map(|page_model| {
+            let path = page_video_path(
+                template,
+                &base_path,
+                video_model,
+                page_model,
+                page_models.len(),
+                time_format,
+                api_title,
+            )?;
+            Ok((page_model.id, path))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(VideoLayout { base_path, pages })
+}
+
+/// 如果 file_name 是视频文件 `{stem}.mp4` 的附属文件，返回去掉 stem 后的后缀
+pub fn page_sidecar_suffix<'a>(file_name: &'a str, stem: &str) -> Option<&'a str> {
+    let suffix = file_name.strip_prefix(stem)?;
+    if PAGE_SIDECAR_SUFFIXES.contains(&suffix) {
+        return Some(suffix);
+    }
+    // 字幕文件形如 {stem}.{lan}.srt
+    let lan = suffix.strip_prefix('.')?.strip_suffix(".srt")?;
+    (!lan.is_empty() && !lan.contains(['.', '/', '\\'])).then_some(suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use bili_sync_entity::string_vec::StringVec;
+
+    use super::*;
+    use crate::config::register_helpers;
+
+    fn template() -> handlebars::Handlebars<'static> {
+        let mut template = handlebars::Handlebars::new();
+        register_helpers(&mut template);
+        template
+            .path_safe_register("video", "{{upper_name}}/{{title}}")
+            .unwrap();
+        template.path_safe_register("page", "{{ptitle}}").unwrap();
+        template
+            .path_safe_register("bangumi", "{{series_title}}/S{{season_pad}}E{{pid_pad}}")
+            .unwrap();
+        template
+    }
+
+    #[test]
+    fn test_compute_layout() {
+        let template = template();
+        let video = video::Model {
+            name: "视频".to_owned(),
+            upper_name: "UP".to_owned(),
+            bvid: "BV1b5411h7g7".to_owned(),
+            single_page: Some(false),
+            tags: Some(StringVec(vec![])),
+            ..Default::default()
+        };
+        let pages = (1..=2)
+            .map(|pid| page::Model {
+                id: pid,
+                pid,
+                name: format!("P{}", pid),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        let layout = compute_layout(&template, Path::new("/source"), &video, &pages, "%Y", None).unwrap();
+        assert_eq!(layout.base_path, Path::new("/source/UP/视频"));
+        assert_eq!(
+            layout.pages,
+            vec![
+                (1, PathBuf::from("/source/UP/视频/Season 1/P1 - S01E01.mp4")),
+                (2, PathBuf::from("/source/UP/视频/Season 1/P2 - S01E02.mp4")),
+            ]
+        );
+        let extra = video::Model {
+            bangumi_id: Some(1),
+            section_title: Some("PV".to_owned()),
+            single_page: Some(true),
+            ..video
+        };
+        let layout = compute_layout(&template, Path::new("/source"), &extra, &pages[..1], "%Y", None).unwrap();
+        assert_eq!(layout.base_path, Path::new("/source"));
+        assert_eq!(layout.pages, vec![(1, PathBuf::from("/source/PV/P1.mp4"))]);
+    }
+
+    #[test]
+    fn test_page_sidecar_suffix() {
+        let stem = "视频 - S01E01";
+        for (file_name, expected) in [
+            ("视频 - S01E01.mp4", Some(".mp4")),
+            ("视频 - S01E01-thumb.jpg", Some("-thumb.jpg")),
+            ("视频 - S01E01.zh-CN.default.ass", Some(".zh-CN.default.ass")),
+            ("视频 - S01E01.ai-zh.srt", Some(".ai-zh.srt")),
+            ("视频 - S01E01.srt", None),
+            ("视频 - S01E010.mp4", None),
+            ("视频 - S01E01.part.mp4", None),
+            ("tvshow.nfo", None),
+        ] {
+            assert_eq!(page_sidecar_suffix(file_name, stem), expected, "{}", file_name);
+        }
+    }
+}
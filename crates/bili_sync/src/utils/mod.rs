@@ -1,13 +1,20 @@
 pub mod convert;
 pub mod download_context;
+pub mod download_window;
 pub mod filenamify;
 pub mod format_arg;
+pub mod instance_lock;
+pub mod layout;
 pub mod model;
 pub mod nfo;
 pub mod notify;
+pub mod progress;
+pub mod queue;
+pub mod risk_control;
 pub mod rule;
 pub mod signal;
 pub mod status;
+pub mod task_control;
 pub mod validation;
 use tracing_subscriber::fmt;
 use tracing_subscriber::layer::SubscriberExt;
@@ -41,3 +48,18 @@ pub fn init_logger(log_level: &str, log_writer: Option<LogHelper>) {
         log.try_init().expect("初始化日志失败");
     }
 }
+
+/// 命令行子命令使用的日志，输出到标准错误，避免与命令本身的输出混在一起
+pub fn init_cli_logger(log_level: &str) {
+    tracing_subscriber::fmt::Subscriber::builder()
+        .compact()
+        .with_env_filter(tracing_subscriber::EnvFilter::builder().parse_lossy(log_level))
+        .with_target(false)
+        .with_timer(tracing_subscriber::fmt::time::ChronoLocal::new(
+            "%b %d %H:%M:%S".to_owned(),
+        ))
+        .with_writer(std::io::stderr)
+        .finish()
+        .try_init()
+        .expect("初始化日志失败");
+}
@@ -2,13 +2,14 @@ use anyhow::{Context, Result, anyhow};
 use bili_sync_entity::*;
 use rand::seq::SliceRandom;
 use sea_orm::ActiveValue::Set;
-use sea_orm::DatabaseTransaction;
 use sea_orm::entity::prelude::*;
 use sea_orm::sea_query::{OnConflict, SimpleExpr};
+use sea_orm::{DatabaseTransaction, QuerySelect, QueryTrait, TransactionTrait};
 
 use crate::adapter::{VideoSource, VideoSourceEnum};
-use crate::bilibili::VideoInfo;
-use crate::config::Config;
+use crate::bilibili::{BangumiList, BiliClient, Collection, CollectionItem, FavoriteList, Submission, VideoInfo};
+use crate::config::{Config, VersionedConfig};
+use crate::utils::queue::refresh_queue_stats;
 use crate::utils::status::STATUS_COMPLETED;
 
 /// 筛选未填充的视频
@@ -30,25 +31,53 @@ pub async fn filter_unfilled_videos(
         .context("filter unfilled videos failed")
 }
 
+/// 未处理完成、需要下载的视频的筛选条件
+pub fn unhandled_videos_expr() -> SimpleExpr {
+    video::Column::Valid
+        .eq(true)
+        .and(video::Column::DownloadStatus.lt(STATUS_COMPLETED))
+        .and(video::Column::Category.eq(2))
+        .and(video::Column::SinglePage.is_not_null())
+        .and(video::Column::ShouldDownload.eq(true))
+}
+
 /// 筛选未处理完成的视频和视频页
 pub async fn filter_unhandled_video_pages(
     additional_expr: SimpleExpr,
     connection: &DatabaseConnection,
 ) -> Result<Vec<(video::Model, Vec<page::Model>)>> {
     video::Entity::find()
+        .filter(unhandled_videos_expr().and(additional_expr))
+        .find_with_related(page::Entity)
+        .all(connection)
+        .await
+        .context("filter unhandled video pages failed")
+}
+
+/// 筛选已经下载、创建时间在 after 与 before 之间且弹幕快照次数少于 max_snapshot 的视频页
+pub async fn filter_danmaku_snapshot_pages(
+    additional_expr: SimpleExpr,
+    (after, before): (&str, &str),
+    max_snapshot: i32,
+    connection: &DatabaseConnection,
+) -> Result<Vec<(page::Model, video::Model)>> {
+    Ok(page::Entity::find()
+        .find_also_related(video::Entity)
         .filter(
             video::Column::Valid
                 .eq(true)
-                .and(video::Column::DownloadStatus.lt(STATUS_COMPLETED))
-                .and(video::Column::Category.eq(2))
-                .and(video::Column::SinglePage.is_not_null())
-                .and(video::Column::ShouldDownload.eq(true))
+                .and(page::Column::Path.is_not_null())
+                .and(page::Column::DanmakuSnapshot.lt(max_snapshot))
+                .and(page::Column::CreatedAt.gt(after))
+                .and(page::Column::CreatedAt.lte(before))
                 .and(additional_expr),
         )
-        .find_with_related(page::Entity)
         .all(connection)
         .await
-        .context("filter unhandled video pages failed")
+        .context("filter danmaku snapshot pages failed")?
+        .into_iter()
+        .filter_map(|(page, video)| Some((page, video?)))
+        .collect())
 }
 
 /// 尝试创建 Video Model，如果发生冲突则忽略
@@ -145,6 +174,194 @@ pub async fn get_enabled_video_sources(connection: &DatabaseConnection) -> Resul
     Ok(sources)
 }
 
+/// 获取全部视频源，包括未启用的视频源
+pub async fn get_all_video_sources(connection: &DatabaseConnection) -> Result<Vec<VideoSourceEnum>> {
+    let (favorite, watch_later, submission, collection, bangumi) = tokio::try_join!(
+        favorite::Entity::find().all(connection),
+        watch_later::Entity::find().all(connection),
+        submission::Entity::find().all(connection),
+        collection::Entity::find().all(connection),
+        bangumi::Entity::find().all(connection),
+    )?;
+    Ok(favorite
+        .into_iter()
+        .map(VideoSourceEnum::from)
+        .chain(watch_later.into_iter().map(VideoSourceEnum::from))
+        .chain(submission.into_iter().map(VideoSourceEnum::from))
+        .chain(collection.into_iter().map(VideoSourceEnum::from))
+        .chain(bangumi.into_iter().map(VideoSourceEnum::from))
+        .collect())
+}
+
+/// 待新增的视频源
+pub enum NewVideoSource {
+    Favorite {
+        fid: i64,
+    },
+    Collection(CollectionItem),
+    Submission {
+        upper_id: i64,
+    },
+    Bangumi {
+        season_id: i64,
+        selected_section_ids: String,
+    },
+}
+
+/// 从 b 站获取视频源的信息并写入数据库，返回视频源的名称
+pub async fn insert_video_source(
+    new_source: NewVideoSource,
+    path: String,
+    enabled: bool,
+    bili_client: &BiliClient,
+    connection: &DatabaseConnection,
+) -> Result<String> {
+    let (name, fetched) = FetchedVideoSource::fetch(new_source, path, enabled, bili_client).await?;
+    fetched.insert(connection).await?;
+    Ok(name)
+}
+
+/// 已经从 b 站获取到信息、尚未写入数据库的视频源
+pub enum FetchedVideoSource {
+    Favorite(favorite::ActiveModel),
+    Collection(collection::ActiveModel),
+    Submission(submission::ActiveModel),
+    Bangumi(bangumi::ActiveModel),
+}
+
+impl FetchedVideoSource {
+    /// 从 b 站获取视频源的信息，返回视频源的名称与待写入的记录
+    pub async fn fetch(
+        new_source: NewVideoSource,
+        path: String,
+        enabled: bool,
+        bili_client: &BiliClient,
+    ) -> Result<(String, Self)> {
+        let credential = &VersionedConfig::get().read().credential;
+        Ok(match new_source {
+            NewVideoSource::Favorite { fid } => {
+                let favorite_info = FavoriteList::new(bili_client, fid.to_string(), credential)
+                    .get_info()
+                    .await?;
+                (
+                    favorite_info.title.clone(),
+                    Self::Favorite(favorite::ActiveModel {
+                        f_id: Set(favorite_info.id),
+                        name: Set(favorite_info.title),
+                        path: Set(path),
+                        enabled: Set(enabled),
+                        ..Default::default()
+                    }),
+                )
+            }
+            NewVideoSource::Collection(collection_item) => {
+                let collection_info = Collection::new(bili_client, collection_item, credential)
+                    .get_info()
+                    .await?;
+                (
+                    collection_info.name.clone(),
+                    Self::Collection(collection::ActiveModel {
+                        s_id: Set(collection_info.sid),
+                        m_id: Set(collection_info.mid),
+                        r#type: Set(collection_info.collection_type.into()),
+                        name: Set(collection_info.name),
+                        path: Set(path),
+                        enabled: Set(enabled),
+                        ..Default::default()
+                    }),
+                )
+            }
+            NewVideoSource::Submission { upper_id } => {
+                let upper = Submission::new(bili_client, upper_id.to_string(), credential)
+                    .get_info()
+                    .await?;
+                (
+                    upper.name.clone(),
+                    Self::Submission(submission::ActiveModel {
+                        upper_id: Set(upper.mid.parse()?),
+                        upper_name: Set(upper.name),
+                        path: Set(path),
+                        enabled: Set(enabled),
+                        ..Default::default()
+                    }),
+                )
+            }
+            NewVideoSource::Bangumi {
+                season_id,
+                selected_section_ids,
+            } => {
+                let bangumi_info = BangumiList::new(bili_client, season_id, credential).get_info().await?;
+                (
+                    bangumi_info.title.clone(),
+                    Self::Bangumi(bangumi::ActiveModel {
+                        season_id: Set(bangumi_info.season_id),
+                        media_id: Set(bangumi_info.media_id),
+                        title: Set(bangumi_info.title),
+                        cover: Set(bangumi_info.cover),
+                        evaluate: Set(bangumi_info.evaluate),
+                        total: Set(bangumi_info.total),
+                        is_finish: Set(bangumi_info.is_finish),
+                        season_type: Set(bangumi_info.season_type),
+                        selected_section_ids: Set(selected_section_ids),
+                        path: Set(path),
+                        enabled: Set(enabled),
+                        ..Default::default()
+                    }),
+                )
+            }
+        })
+    }
+
+    pub async fn insert(self, connection: &impl ConnectionTrait) -> Result<()> {
+        match self {
+            Self::Favorite(model) => {
+                favorite::Entity::insert(model).exec(connection).await?;
+            }
+            Self::Collection(model) => {
+                collection::Entity::insert(model).exec(connection).await?;
+            }
+            Self::Submission(model) => {
+                submission::Entity::insert(model).exec(connection).await?;
+            }
+            Self::Bangumi(model) => {
+                bangumi::Entity::insert(model).exec(connection).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 删除视频源，连同其下的视频、分页与下载队列中的任务
+pub async fn delete_video_source(video_source: VideoSourceEnum, connection: &DatabaseConnection) -> Result<()> {
+    let txn = connection.begin().await?;
+    page::Entity::delete_many()
+        .filter(
+            page::Column::VideoId.in_subquery(
+                video::Entity::find()
+                    .filter(video_source.filter_expr())
+                    .select_only()
+                    .column(video::Column::Id)
+                    .as_query()
+                    .to_owned(),
+            ),
+        )
+        .exec(&txn)
+        .await?;
+    video::Entity::delete_many()
+        .filter(video_source.filter_expr())
+        .exec(&txn)
+        .await?;
+    let (source_type, source_id) = video_source.source_key();
+    download_queue::Entity::delete_many()
+        .filter(download_queue::Column::SourceType.eq(source_type))
+        .filter(download_queue::Column::SourceId.eq(source_id))
+        .exec(&txn)
+        .await?;
+    video_source.delete_from_db(&txn).await?;
+    txn.commit().await?;
+    refresh_queue_stats(connection).await
+}
+
 /// 从数据库中加载配置
 pub async fn load_db_config(connection: &DatabaseConnection) -> Result<Option<Result<Config>>> {
     Ok(bili_sync_entity::config::Entity::find_by_id(1)
@@ -156,7 +373,7 @@ pub async fn load_db_config(connection: &DatabaseConnection) -> Result<Option<Re
 }
 
 /// 保存配置到数据库
-pub async fn save_db_config(config: &Config, connection: &DatabaseConnection) -> Result<()> {
+pub async fn save_db_config(config: &Config, connection: &impl ConnectionTrait) -> Result<()> {
     let data = serde_json::to_string(config).context("Failed to serialize config data")?;
     let model = bili_sync_entity::config::ActiveModel {
         id: Set(1),
@@ -6,6 +6,7 @@ use quick_xml::events::{BytesCData, BytesText};
 use quick_xml::writer::Writer;
 use tokio::io::{AsyncWriteExt, BufWriter};
 
+use crate::bilibili::Chapter;
 use crate::config::NFOTimeType;
 
 #[allow(clippy::upper_case_acronyms)]
@@ -26,6 +27,12 @@ pub struct Movie<'a> {
     pub upper_thumb: &'a str,
     pub premiered: NaiveDateTime,
     pub tags: Option<Vec<String>>,
+    pub partition: Option<&'a str>,
+    pub stat: Stat,
+    pub actors: Option<&'a str>,
+    /// 视频时长，单位为秒
+    pub runtime: Option<u32>,
+    pub chapters: &'a [Chapter],
 }
 
 pub struct TVShow<'a> {
@@ -37,6 +44,18 @@ pub struct TVShow<'a> {
     pub upper_thumb: &'a str,
     pub premiered: NaiveDateTime,
     pub tags: Option<Vec<String>>,
+    pub partition: Option<&'a str>,
+    pub stat: Stat,
+    pub actors: Option<&'a str>,
+}
+
+/// 视频的播放、点赞、投币与收藏数
+#[derive(Clone, Copy, Default)]
+pub struct Stat {
+    pub play: i64,
+    pub like: i64,
+    pub coin: i64,
+    pub favorite: i64,
 }
 
 pub struct Upper {
@@ -47,6 +66,10 @@ pub struct Upper {
 pub struct Episode<'a> {
     pub name: &'a str,
     pub pid: String,
+    pub cid: i64,
+    /// 分页时长，单位为秒
+    pub runtime: u32,
+    pub chapters: &'a [Chapter],
 }
 
 pub struct Bangumi<'a> {
@@ -102,6 +125,7 @@ impl NFO<'_> {
                     .create_element("title")
                     .write_text_content_async(BytesText::new(movie.name))
                     .await?;
+                Self::write_ratings(writer, movie.stat).await?;
                 writer
                     .create_element("actor")
                     .write_inner_content_async::<_, _, Error>(|writer| async move {
@@ -120,10 +144,17 @@ impl NFO<'_> {
                         Ok(writer)
                     })
                     .await?;
+                Self::write_people(writer, movie.upper_id, movie.upper_name, movie.actors).await?;
                 writer
                     .create_element("year")
                     .write_text_content_async(BytesText::new(&movie.premiered.format("%Y").to_string()))
                     .await?;
+                if let Some(partition) = movie.partition {
+                    writer
+                        .create_element("genre")
+                        .write_text_content_async(BytesText::new(partition))
+                        .await?;
+                }
                 if let Some(tags) = movie.tags {
                     for tag in tags {
                         writer
@@ -132,6 +163,9 @@ impl NFO<'_> {
                             .await?;
                     }
                 }
+                if let Some(runtime) = movie.runtime {
+                    Self::write_runtime(writer, runtime).await?;
+                }
                 writer
                     .create_element("uniqueid")
                     .with_attribute(("type", "bilibili"))
@@ -141,6 +175,7 @@ impl NFO<'_> {
                     .create_element("premiered")
                     .write_text_content_async(BytesText::new(&movie.premiered.format("%Y-%m-%d").to_string()))
                     .await?;
+                Self::write_chapters(writer, movie.chapters).await?;
                 Ok(writer)
             })
             .await?;
@@ -160,6 +195,7 @@ impl NFO<'_> {
                     .create_element("title")
                     .write_text_content_async(BytesText::new(tvshow.name))
                     .await?;
+                Self::write_ratings(writer, tvshow.stat).await?;
                 writer
                     .create_element("actor")
                     .write_inner_content_async::<_, _, Error>(|writer| async move {
@@ -178,10 +214,17 @@ impl NFO<'_> {
                         Ok(writer)
                     })
                     .await?;
+                Self::write_people(writer, tvshow.upper_id, tvshow.upper_name, tvshow.actors).await?;
                 writer
                     .create_element("year")
                     .write_text_content_async(BytesText::new(&tvshow.premiered.format("%Y").to_string()))
                     .await?;
+                if let Some(partition) = tvshow.partition {
+                    writer
+                        .create_element("genre")
+                        .write_text_content_async(BytesText::new(partition))
+                        .await?;
+                }
                 if let Some(tags) = tvshow.tags {
                     for tag in tags {
                         writer
@@ -325,12 +368,142 @@ impl NFO<'_> {
                     .create_element("episode")
                     .write_text_content_async(BytesText::new(&episode.pid))
                     .await?;
+                Self::write_runtime(writer, episode.runtime).await?;
+                writer
+                    .create_element("uniqueid")
+                    .with_attribute(("type", "bilibili"))
+                    .write_text_content_async(BytesText::new(&episode.cid.to_string()))
+                    .await?;
+                Self::write_chapters(writer, episode.chapters).await?;
                 Ok(writer)
             })
             .await?;
         Ok(())
     }
 
+    async fn write_chapters(
+        writer: &mut Writer<&mut BufWriter<&mut Vec<u8>>>,
+        chapters: &[Chapter],
+    ) -> Result<(), Error> {
+        for chapter in chapters {
+            writer
+                .create_element("chapter")
+                .write_inner_content_async::<_, _, Error>(|writer| async move {
+                    writer
+                        .create_element("title")
+                        .write_text_content_async(BytesText::new(chapter.content.trim()))
+                        .await?;
+                    writer
+                        .create_element("start")
+                        .write_text_content_async(BytesText::new(&chapter.start_timestamp()))
+                        .await?;
+                    writer
+                        .create_element("end")
+                        .write_text_content_async(BytesText::new(&chapter.end_timestamp()))
+                        .await?;
+                    Ok(writer)
+                })
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// 将播放、点赞、投币、收藏数作为各项评分的投票数写入，b 站没有评分，因此不写入分值
+    async fn write_ratings(writer: &mut Writer<&mut BufWriter<&mut Vec<u8>>>, stat: Stat) -> Result<(), Error> {
+        if stat.play <= 0 {
+            return Ok(());
+        }
+        writer
+            .create_element("ratings")
+            .write_inner_content_async::<_, _, Error>(|writer| async move {
+                for (name, votes) in [
+                    ("bilibili", stat.play),
+                    ("like", stat.like),
+                    ("coin", stat.coin),
+                    ("favorite", stat.favorite),
+                ] {
+                    let mut element = writer.create_element("rating").with_attribute(("name", name));
+                    if name == "bilibili" {
+                        element = element.with_attribute(("default", "true"));
+                    }
+                    element
+                        .write_inner_content_async::<_, _, Error>(|writer| async move {
+                            writer
+                                .create_element("votes")
+                                .write_text_content_async(BytesText::new(&votes.to_string()))
+                                .await?;
+                            Ok(writer)
+                        })
+                        .await?;
+                }
+                Ok(writer)
+            })
+            .await?;
+        Ok(())
+    }
+
+    /// UP 主作为导演与工作室，番剧额外写入声优信息
+    async fn write_people(
+        writer: &mut Writer<&mut BufWriter<&mut Vec<u8>>>,
+        upper_id: i64,
+        upper_name: &str,
+        actors: Option<&str>,
+    ) -> Result<(), Error> {
+        for (name, role) in actors.map(Self::parse_actors).unwrap_or_default() {
+            writer
+                .create_element("actor")
+                .write_inner_content_async::<_, _, Error>(|writer| async move {
+                    writer
+                        .create_element("name")
+                        .write_text_content_async(BytesText::new(name))
+                        .await?;
+                    if let Some(role) = role {
+                        writer
+                            .create_element("role")
+                            .write_text_content_async(BytesText::new(role))
+                            .await?;
+                    }
+                    Ok(writer)
+                })
+                .await?;
+        }
+        // 番剧的 upper 是占位信息，不作为导演写入
+        if upper_id != 0 && !upper_name.is_empty() {
+            for element in ["director", "studio"] {
+                writer
+                    .create_element(element)
+                    .write_text_content_async(BytesText::new(upper_name))
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn write_runtime(writer: &mut Writer<&mut BufWriter<&mut Vec<u8>>>, seconds: u32) -> Result<(), Error> {
+        if seconds == 0 {
+            return Ok(());
+        }
+        // kodi 中的 runtime 以分钟为单位
+        writer
+            .create_element("runtime")
+            .write_text_content_async(BytesText::new(&seconds.div_ceil(60).to_string()))
+            .await?;
+        Ok(())
+    }
+
+    /// 番剧的 actors 形如 "角色1：声优1\n角色2：声优2"，返回 (声优, 角色)
+    fn parse_actors(actors: &str) -> Vec<(&str, Option<&str>)> {
+        actors
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| match line.split_once(['：', ':']) {
+                Some((role, name)) if !name.trim().is_empty() => (name.trim(), Some(role.trim())),
+                _ => (line, None),
+            })
+            .collect()
+    }
+
     #[inline]
     fn format_plot(bvid: &str, intro: &str) -> String {
         format!(
@@ -362,6 +535,11 @@ mod tests {
             ),
             bvid: "BV1nWcSeeEkV".to_string(),
             tags: Some(vec!["tag1".to_owned(), "tag2".to_owned()].into()),
+            play_count: 1000,
+            like_count: 50,
+            coin_count: 200,
+            favorite_count: 30,
+            partition_name: Some("动画".to_string()),
             ..Default::default()
         };
         assert_eq!(
@@ -374,12 +552,29 @@ mod tests {
     <plot><![CDATA[原始视频：<a href="https://www.bilibili.com/video/BV1nWcSeeEkV/">BV1nWcSeeEkV</a><br/><br/>intro]]></plot>
     <outline/>
     <title>name</title>
+    <ratings>
+        <rating name="bilibili" default="true">
+            <votes>1000</votes>
+        </rating>
+        <rating name="like">
+            <votes>50</votes>
+        </rating>
+        <rating name="coin">
+            <votes>200</votes>
+        </rating>
+        <rating name="favorite">
+            <votes>30</votes>
+        </rating>
+    </ratings>
     <actor>
         <name>1</name>
         <role>upper_name</role>
         <thumb>https://i1.hdslb.com/bfs/face/72e8f33cadc72e022fc34624cc69e1b12ebb72c0.jpg</thumb>
     </actor>
+    <director>upper_name</director>
+    <studio>upper_name</studio>
     <year>2022</year>
+    <genre>动画</genre>
     <genre>tag1</genre>
     <genre>tag2</genre>
     <uniqueid type="bilibili">BV1nWcSeeEkV</uniqueid>
@@ -396,12 +591,29 @@ mod tests {
     <plot><![CDATA[原始视频：<a href="https://www.bilibili.com/video/BV1nWcSeeEkV/">BV1nWcSeeEkV</a><br/><br/>intro]]></plot>
     <outline/>
     <title>name</title>
+    <ratings>
+        <rating name="bilibili" default="true">
+            <votes>1000</votes>
+        </rating>
+        <rating name="like">
+            <votes>50</votes>
+        </rating>
+        <rating name="coin">
+            <votes>200</votes>
+        </rating>
+        <rating name="favorite">
+            <votes>30</votes>
+        </rating>
+    </ratings>
     <actor>
         <name>1</name>
         <role>upper_name</role>
         <thumb>https://i1.hdslb.com/bfs/face/72e8f33cadc72e022fc34624cc69e1b12ebb72c0.jpg</thumb>
     </actor>
+    <director>upper_name</director>
+    <studio>upper_name</studio>
     <year>2022</year>
+    <genre>动画</genre>
     <genre>tag1</genre>
     <genre>tag2</genre>
     <uniqueid type="bilibili">BV1nWcSeeEkV</uniqueid>
@@ -426,6 +638,8 @@ mod tests {
         let page = page::Model {
             name: "name".to_string(),
             pid: 3,
+            cid: 1234,
+            duration: 185,
             ..Default::default()
         };
         assert_eq!(
@@ -440,8 +654,71 @@ mod tests {
     <title>name</title>
     <season>1</season>
     <episode>3</episode>
+    <runtime>4</runtime>
+    <uniqueid type="bilibili">1234</uniqueid>
 </episodedetails>"#,
         );
+        let chapters: Vec<Chapter> = serde_json::from_value(serde_json::json!([
+            { "from": 0, "to": 95, "content": "开场" },
+            { "from": 95, "to": 3725, "content": "正文" }
+        ]))
+        .unwrap();
+        let page_ref = &page;
+        let mut episode: Episode = page_ref.to_nfo(NFOTimeType::FavTime);
+        episode.chapters = &chapters;
+        assert_eq!(
+            NFO::Episode(episode).generate_nfo().await.unwrap(),
+            r#"<?xml version="1.0" encoding="utf-8" standalone="yes"?>
+<episodedetails>
+    <plot/>
+    <outline/>
+    <title>name</title>
+    <season>1</season>
+    <episode>3</episode>
+    <runtime>4</runtime>
+    <uniqueid type="bilibili">1234</uniqueid>
+    <chapter>
+        <title>开场</title>
+        <start>00:00:00.000</start>
+        <end>00:01:35.000</end>
+    </chapter>
+    <chapter>
+        <title>正文</title>
+        <start>00:01:35.000</start>
+        <end>01:02:05.000</end>
+    </chapter>
+</episodedetails>"#,
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bangumi_actors() {
+        let video = video::Model {
+            name: "name".to_string(),
+            upper_name: "番剧".to_string(),
+            actors: Some("阿库娅：雨宫天\n惠惠:高桥李依\n\n旁白".to_string()),
+            ..Default::default()
+        };
+        let nfo = NFO::TVShow((&video).to_nfo(NFOTimeType::FavTime))
+            .generate_nfo()
+            .await
+            .unwrap();
+        assert!(nfo.contains("<actor>\n        <name>雨宫天</name>\n        <role>阿库娅</role>\n    </actor>"));
+        assert!(nfo.contains("<actor>\n        <name>高桥李依</name>\n        <role>惠惠</role>\n    </actor>"));
+        assert!(nfo.contains("<actor>\n        <name>旁白</name>\n    </actor>"));
+        assert!(!nfo.contains("<director>"));
+        assert!(!nfo.contains("<ratings>"));
+    }
+}
+
+impl From<&video::Model> for Stat {
+    fn from(video: &video::Model) -> Self {
+        Self {
+            play: video.play_count,
+            like: video.like_count,
+            coin: video.coin_count,
+            favorite: video.favorite_count,
+        }
     }
 }
 
@@ -463,6 +740,11 @@ impl<'a> ToNFO<'a, Movie<'a>> for &'a video::Model {
                 NFOTimeType::PubTime => self.pubtime,
             },
             tags: self.tags.as_ref().map(|tags| tags.clone().into()),
+            partition: self.partition_name.as_deref(),
+            stat: Stat::from(*self),
+            actors: self.actors.as_deref(),
+            runtime: None,
+            chapters: &[],
         }
     }
 }
@@ -481,6 +763,9 @@ impl<'a> ToNFO<'a, TVShow<'a>> for &'a video::Model {
                 NFOTimeType::PubTime => self.pubtime,
             },
             tags: self.tags.as_ref().map(|tags| tags.clone().into()),
+            partition: self.partition_name.as_deref(),
+            stat: Stat::from(*self),
+            actors: self.actors.as_deref(),
         }
     }
 }
@@ -499,6 +784,9 @@ impl<'a> ToNFO<'a, Episode<'a>> for &'a page::Model {
         Episode {
             name: &self.name,
             pid: self.pid.to_string(),
+            cid: self.cid,
+            runtime: self.duration,
+            chapters: &[],
         }
     }
 }
@@ -1,13 +1,59 @@
+use std::sync::LazyLock;
+
+use tokio_util::task::TaskTracker;
+
 use crate::bilibili::BiliClient;
-use crate::config::Config;
-use crate::notifier::NotifierAllExt;
+use crate::config::{CONFIG_DIR, Config, NotificationDigest};
+use crate::notifier::{DigestBuffer, NotificationEvent, NotificationEventKind, NotifierAllExt};
 
-pub fn error_and_notify(config: &Config, bili_client: &BiliClient, msg: String) {
-    error!("{msg}");
+/// 开启通知汇总时暂存的视频下载事件，保存在配置目录中以便重启后继续汇总
+static DIGEST: LazyLock<DigestBuffer> = LazyLock::new(|| DigestBuffer::load(CONFIG_DIR.join("digest.json")));
+
+/// 正在发送的通知，命令行执行结束前需要等待它们发送完毕
+static PENDING: LazyLock<TaskTracker> = LazyLock::new(TaskTracker::new);
+
+/// 将事件推送给所有订阅了该事件类型的通知器，开启汇总时视频下载事件会被暂存，等待 flush_digest 统一发送
+pub fn notify(config: &Config, bili_client: &BiliClient, event: NotificationEvent) {
+    let kind = NotificationEventKind::from(&event);
+    let Some(notifiers) = &config.notifiers else {
+        return;
+    };
+    if !notifiers.iter().any(|notifier| notifier.subscribes(kind)) {
+        return;
+    }
+    let event = match config.notification_digest {
+        NotificationDigest::Off => event,
+        _ => match DIGEST.push(event) {
+            Some(event) => event,
+            None => return,
+        },
+    };
+    let (notifiers, inner_client) = (notifiers.clone(), bili_client.inner_client().clone());
+    PENDING.spawn(async move { notifiers.notify_all(&inner_client, &event).await });
+}
+
+/// 将暂存的视频下载事件合并为一条汇总通知发送
+pub fn flush_digest(config: &Config, bili_client: &BiliClient) {
+    let Some(event) = DIGEST.take() else {
+        return;
+    };
     if let Some(notifiers) = &config.notifiers
-        && !notifiers.is_empty()
+        && notifiers
+            .iter()
+            .any(|notifier| notifier.subscribes(NotificationEventKind::Digest))
     {
         let (notifiers, inner_client) = (notifiers.clone(), bili_client.inner_client().clone());
-        tokio::spawn(async move { notifiers.notify_all(&inner_client, msg.as_str()).await });
+        PENDING.spawn(async move { notifiers.notify_all(&inner_client, &event).await });
     }
 }
+
+pub fn error_and_notify(config: &Config, bili_client: &BiliClient, msg: String) {
+    error!("{msg}");
+    notify(config, bili_client, NotificationEvent::Error { message: msg });
+}
+
+/// 等待所有已经发出的通知发送完毕
+pub async fn wait_pending_notifications() {
+    PENDING.close();
+    PENDING.wait().await;
+}
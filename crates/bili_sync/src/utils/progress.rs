@@ -0,0 +1,244 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant};
+
+use bili_sync_entity::{page, video};
+use parking_lot::Mutex;
+use serde::Serialize;
+
+/// 计算下载速度的最小采样间隔
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+static REGISTRY: LazyLock<ProgressRegistry> = LazyLock::new(ProgressRegistry::default);
+
+#[derive(Default)]
+struct ProgressRegistry {
+    next_id: AtomicU64,
+    entries: Mutex<BTreeMap<u64, Arc<Progress>>>,
+}
+
+/// 分页下载所处的阶段
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProgressStage {
+    /// 正在下载视频流或音频流
+    Downloading,
+    /// 正在使用 ffmpeg 合并音视频
+    Merging,
+}
+
+/// 某个分页视频的下载进度快照
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct DownloadProgress {
+    pub id: u64,
+    pub video_id: i32,
+    pub bvid: String,
+    pub video_name: String,
+    pub page_id: i32,
+    pub pid: i32,
+    pub page_name: String,
+    pub stage: ProgressStage,
+    /// 已下载的字节数，同时下载视频流与音频流时为两者之和
+    pub downloaded: u64,
+    /// 需要下载的总字节数，服务端未返回文件大小时为 0
+    pub total: u64,
+    /// 最近一段时间的下载速度，单位为字节每秒
+    pub speed: u64,
+    /// 预计剩余的秒数，无法估计时为空
+    pub eta: Option<u64>,
+}
+
+/// 正在下载的分页视频的进度，由下载器在接收数据时更新
+pub struct Progress {
+    id: u64,
+    video_id: i32,
+    bvid: String,
+    video_name: String,
+    page_id: i32,
+    pid: i32,
+    page_name: String,
+    merging: AtomicBool,
+    downloaded: AtomicU64,
+    total: AtomicU64,
+    /// 上次采样的时间、已下载字节数与算得的速度
+    sample: Mutex<(Instant, u64, u64)>,
+}
+
+impl Progress {
+    /// 为一次下载创建独立的字节计数器，下载失败时可以单独撤销该次下载计入的字节数
+    pub fn counter(self: &Arc<Self>) -> Arc<ByteCounter> {
+        Arc::new(ByteCounter {
+            progress: self.clone(),
+            downloaded: AtomicU64::new(0),
+            total: AtomicU64::new(0),
+        })
+    }
+
+    fn advance(&self, bytes: u64) {
+        let downloaded = self.downloaded.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        let mut sample = self.sample.lock();
+        let elapsed = sample.0.elapsed();
+        if elapsed >= SAMPLE_INTERVAL {
+            *sample = (
+                Instant::now(),
+                downloaded,
+                rate(downloaded.saturating_sub(sample.1), elapsed),
+            );
+        }
+    }
+
+    pub fn set_merging(&self) {
+        self.merging.store(true, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> DownloadProgress {
+        let downloaded = self.downloaded.load(Ordering::Relaxed);
+        let total = self.total.load(Ordering::Relaxed);
+        let (sampled_at, sampled_bytes, sampled_speed) = *self.sample.lock();
+        let elapsed = sampled_at.elapsed();
+        // 长时间没有收到数据时采样不会更新，此时根据上次采样至今的数据重新估算
+        let speed = if elapsed >= SAMPLE_INTERVAL * 2 {
+            rate(downloaded.saturating_sub(sampled_bytes), elapsed)
+        } else {
+            sampled_speed
+        };
+        let merging = self.merging.load(Ordering::Relaxed);
+        let eta = (!merging && total > downloaded && speed > 0).then(|| (total - downloaded).div_ceil(speed));
+        DownloadProgress {
+            id: self.id,
+            video_id: self.video_id,
+            bvid: self.bvid.clone(),
+            video_name: self.video_name.clone(),
+            page_id: self.page_id,
+            pid: self.pid,
+            page_name: self.page_name.clone(),
+            stage: if merging {
+                ProgressStage::Merging
+            } else {
+                ProgressStage::Downloading
+            },
+            downloaded,
+            total,
+            speed,
+            eta,
+        }
+    }
+}
+
+/// 单次下载的字节计数器，计入的字节数会同时累加到所属的分页进度中
+pub struct ByteCounter {
+    progress: Arc<Progress>,
+    downloaded: AtomicU64,
+    total: AtomicU64,
+}
+
+impl ByteCounter {
+    /// 记录新接收到的字节数
+    pub fn advance(&self, bytes: u64) {
+        self.downloaded.fetch_add(bytes, Ordering::Relaxed);
+        self.progress.advance(bytes);
+    }
+
+    /// 增加需要下载的总字节数
+    pub fn add_total(&self, bytes: u64) {
+        self.total.fetch_add(bytes, Ordering::Relaxed);
+        self.progress.total.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// 撤销本次下载计入的全部字节数，用于下载失败后更换地址重新下载
+    pub fn rollback(&self) {
+        let downloaded = self.downloaded.swap(0, Ordering::Relaxed);
+        let total = self.total.swap(0, Ordering::Relaxed);
+        self.progress.downloaded.fetch_sub(downloaded, Ordering::Relaxed);
+        self.progress.total.fetch_sub(total, Ordering::Relaxed);
+    }
+}
+
+fn rate(bytes: u64, elapsed: Duration) -> u64 {
+    (bytes as f64 / elapsed.as_secs_f64()) as u64
+}
+
+/// 开始跟踪某个分页视频的下载进度，返回的 guard 被 drop 时停止跟踪
+pub fn track(video_model: &video::Model, page_model: &page::Model) -> ProgressGuard {
+    let id = REGISTRY.next_id.fetch_add(1, Ordering::Relaxed);
+    let progress = Arc::new(Progress {
+        id,
+        video_id: video_model.id,
+        bvid: video_model.bvid.clone(),
+        video_name: video_model.name.clone(),
+        page_id: page_model.id,
+        pid: page_model.pid,
+        page_name: page_model.name.clone(),
+        merging: AtomicBool::new(false),
+        downloaded: AtomicU64::new(0),
+        total: AtomicU64::new(0),
+        sample: Mutex::new((Instant::now(), 0, 0)),
+    });
+    REGISTRY.entries.lock().insert(id, progress.clone());
+    ProgressGuard(progress)
+}
+
+/// 获取所有正在下载的分页视频的进度
+pub fn snapshot() -> Vec<DownloadProgress> {
+    REGISTRY
+        .entries
+        .lock()
+        .values()
+        .map(|progress| progress.snapshot())
+        .collect()
+}
+
+pub struct ProgressGuard(Arc<Progress>);
+
+impl ProgressGuard {
+    pub fn progress(&self) -> &Arc<Progress> {
+        &self.0
+    }
+}
+
+impl Drop for ProgressGuard {
+    fn drop(&mut self) {
+        REGISTRY.entries.lock().remove(&self.0.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress() {
+        let video_model = video::Model {
+            id: 1,
+            bvid: "BV1".to_owned(),
+            name: "视频".to_owned(),
+            ..Default::default()
+        };
+        let page_model = page::Model {
+            id: 2,
+            pid: 1,
+            name: "分页".to_owned(),
+            ..Default::default()
+        };
+        let guard = track(&video_model, &page_model);
+        let id = guard.progress().id;
+        let (video, audio) = (guard.progress().counter(), guard.progress().counter());
+        video.add_total(100);
+        video.advance(30);
+        audio.add_total(50);
+        audio.advance(20);
+        // 音频下载失败，撤销后重新下载
+        audio.rollback();
+        audio.add_total(40);
+        audio.advance(10);
+        let progress = snapshot().into_iter().find(|p| p.id == id).unwrap();
+        assert_eq!((progress.downloaded, progress.total), (40, 140));
+        assert_eq!(progress.stage, ProgressStage::Downloading);
+        guard.progress().set_merging();
+        let progress = snapshot().into_iter().find(|p| p.id == id).unwrap();
+        assert_eq!((progress.stage, progress.eta), (ProgressStage::Merging, None));
+        drop(guard);
+        assert!(snapshot().into_iter().all(|p| p.id != id));
+    }
+}
@@ -0,0 +1,341 @@
+use std::sync::LazyLock;
+
+use anyhow::{Context, Result};
+use bili_sync_entity::download_queue::{self, QueueStatus};
+use bili_sync_entity::*;
+use sea_orm::ActiveValue::Set;
+use sea_orm::entity::prelude::*;
+use sea_orm::sea_query::{Condition, Expr, OnConflict, Query};
+use sea_orm::{QueryOrder, QuerySelect, TransactionTrait, TryInsertResult};
+use serde::Serialize;
+use tokio::sync::watch;
+
+use crate::adapter::{VideoSource, VideoSourceEnum};
+use crate::utils::model::{filter_unhandled_video_pages, unhandled_videos_expr};
+
+/// 下载队列中各个状态的任务数量
+#[derive(Serialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QueueStats {
+    pub pending: u64,
+    pub running: u64,
+    pub paused: u64,
+    pub cancelled: u64,
+    /// 任务的优先级或顺序发生变化时递增，订阅者可以据此重新获取队列
+    pub revision: u64,
+}
+
+static QUEUE_STATS: LazyLock<watch::Sender<QueueStats>> = LazyLock::new(|| watch::Sender::new(QueueStats::default()));
+
+/// 订阅下载队列的统计信息
+pub fn subscribe_queue_stats() -> watch::Receiver<QueueStats> {
+    QUEUE_STATS.subscribe()
+}
+
+/// 重新统计下载队列中各个状态的任务数量，并推送给订阅者
+pub async fn refresh_queue_stats(connection: &DatabaseConnection) -> Result<()> {
+    let counts: Vec<(QueueStatus, i64)> = download_queue::Entity::find()
+        .select_only()
+        .column(download_queue::Column::Status)
+        .column_as(download_queue::Column::Id.count(), "count")
+        .group_by(download_queue::Column::Status)
+        .into_tuple()
+        .all(connection)
+        .await
+        .context("count download queue failed")?;
+    let mut stats = QueueStats::default();
+    for (status, count) in counts {
+        let count = count as u64;
+        match status {
+            QueueStatus::Pending => stats.pending = count,
+            QueueStatus::Running => stats.running = count,
+            QueueStatus::Paused => stats.paused = count,
+            QueueStatus::Cancelled => stats.cancelled = count,
+        }
+    }
+    QUEUE_STATS.send_if_modified(|old| {
+        stats.revision = old.revision;
+        std::mem::replace(old, stats) != stats
+    });
+    Ok(())
+}
+
+/// 记录下载队列的顺序发生了变化，并推送给订阅者
+pub fn mark_queue_reordered() {
+    QUEUE_STATS.send_modify(|stats| stats.revision += 1);
+}
+
+/// 将视频源中所有待下载且不在队列中的视频追加到下载队列末尾，返回新加入的视频数量
+pub async fn enqueue_videos(video_source: &VideoSourceEnum, connection: &DatabaseConnection) -> Result<usize> {
+    let video_ids: Vec<i32> = video::Entity::find()
+        .select_only()
+        .column(video::Column::Id)
+        .filter(
+            unhandled_videos_expr().and(video_source.filter_expr()).and(
+                video::Column::Id.not_in_subquery(
+                    Query::select()
+                        .column(download_queue::Column::VideoId)
+                        .from(download_queue::Entity)
+                        .to_owned(),
+                ),
+            ),
+        )
+        .order_by_asc(video::Column::Id)
+        .into_tuple()
+        .all(connection)
+        .await
+        .context("filter videos to enqueue failed")?;
+    if video_ids.is_empty() {
+        return Ok(0);
+    }
+    let (source_type, source_id) = video_source.source_key();
+    let txn = connection.begin().await?;
+    let max_position: Option<i64> = download_queue::Entity::find()
+        .select_only()
+        .column_as(download_queue::Column::Position.max(), "position")
+        .into_tuple()
+        .one(&txn)
+        .await?
+        .flatten();
+    let start = max_position.map_or(0, |position| position + 1);
+    let items = video_ids
+        .iter()
+        .enumerate()
+        .map(|(idx, video_id)| download_queue::ActiveModel {
+            video_id: Set(*video_id),
+            source_type: Set(source_type.to_owned()),
+            source_id: Set(source_id),
+            priority: Set(0),
+            position: Set(start + idx as i64),
+            status: Set(QueueStatus::Pending),
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+    // 其它视频源可能已经将同一视频加入队列，冲突时跳过，仅统计实际插入的数量
+    let mut inserted = 0;
+    for chunk in items.chunks(200) {
+        if let TryInsertResult::Inserted(rows) = download_queue::Entity::insert_many(chunk.to_vec())
+            .on_conflict(
+                OnConflict::column(download_queue::Column::VideoId)
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .do_nothing()
+            .exec_without_returning(&txn)
+            .await?
+        {
+            inserted += rows as usize;
+        }
+    }
+    txn.commit().await?;
+    Ok(inserted)
+}
+
+/// 将上次运行中断时仍处于运行状态的任务恢复为等待状态
+pub async fn reset_running_items(connection: &DatabaseConnection) -> Result<()> {
+    download_queue::Entity::update_many()
+        .col_expr(download_queue::Column::Status, Expr::value(QueueStatus::Pending))
+        .filter(download_queue::Column::Status.eq(QueueStatus::Running))
+        .exec(connection)
+        .await?;
+    Ok(())
+}
+
+/// 从给定的视频源中取出优先级最高、排序最靠前的等待中任务并标记为运行中，skipped 为本轮已经处理过的任务
+///
+/// 视频已经不再需要下载（如被手动标记为完成、不再满足筛选规则）的任务会被直接移出队列
+pub async fn claim_next_item(
+    sources: &[(&'static str, i32)],
+    skipped: &[i32],
+    connection: &DatabaseConnection,
+) -> Result<Option<(download_queue::Model, video::Model, Vec<page::Model>)>> {
+    let source_condition = source_condition(sources);
+    loop {
+        let Some(item) = download_queue::Entity::find()
+            .filter(download_queue::Column::Status.eq(QueueStatus::Pending))
+            .filter(download_queue::Column::Id.is_not_in(skipped.iter().copied()))
+            .filter(source_condition.clone())
+            .order_by_desc(download_queue::Column::Priority)
+            .order_by_asc(download_queue::Column::Position)
+            .order_by_asc(download_queue::Column::Id)
+            .one(connection)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let Some((video_model, pages_model)) =
+            filter_unhandled_video_pages(video::Column::Id.eq(item.video_id), connection)
+                .await?
+                .pop()
+        else {
+            download_queue::Entity::delete_by_id(item.id).exec(connection).await?;
+            continue;
+        };
+        // 仅当任务仍处于等待状态时才领取，避免与接口中的暂停、取消操作冲突
+        let res = download_queue::Entity::update_many()
+            .col_expr(download_queue::Column::Status, Expr::value(QueueStatus::Running))
+            .filter(download_queue::Column::Id.eq(item.id))
+            .filter(download_queue::Column::Status.eq(QueueStatus::Pending))
+            .exec(connection)
+            .await?;
+        if res.rows_affected == 1 {
+            return Ok(Some((item, video_model, pages_model)));
+        }
+    }
+}
+
+/// 统计给定的视频源在下载队列中等待下载的任务数量
+pub async fn count_pending_items(sources: &[(&'static str, i32)], connection: &DatabaseConnection) -> Result<u64> {
+    Ok(download_queue::Entity::find()
+        .filter(download_queue::Column::Status.eq(QueueStatus::Pending))
+        .filter(source_condition(sources))
+        .count(connection)
+        .await?)
+}
+
+fn source_condition(sources: &[(&'static str, i32)]) -> Condition {
+    sources
+        .iter()
+        .fold(Condition::any(), |condition, (source_type, source_id)| {
+            condition.add(
+                download_queue::Column::SourceType
+                    .eq(*source_type)
+                    .and(download_queue::Column::SourceId.eq(*source_id)),
+            )
+        })
+}
+
+/// 任务执行结束，视频已经处理完成时移出队列，否则恢复为等待状态留待下一轮处理
+///
+/// 执行期间被暂停或取消的任务保持对应的状态
+pub async fn finish_item(item_id: i32, completed: bool, connection: &DatabaseConnection) -> Result<()> {
+    if completed {
+        download_queue::Entity::delete_by_id(item_id).exec(connection).await?;
+    } else {
+        download_queue::Entity::update_many()
+            .col_expr(download_queue::Column::Status, Expr::value(QueueStatus::Pending))
+            .filter(download_queue::Column::Id.eq(item_id))
+            .filter(download_queue::Column::Status.eq(QueueStatus::Running))
+            .exec(connection)
+            .await?;
+    }
+    Ok(())
+}
+
+/// 按照 ids 的顺序重新排列这些任务，它们原本占据的位置保持不变，不影响其它任务的顺序
+pub async fn reorder_items(ids: &[i32], connection: &DatabaseConnection) -> Result<()> {
+    let txn = connection.begin().await?;
+    let items = download_queue::Entity::find()
+        .filter(download_queue::Column::Id.is_in(ids.iter().copied()))
+        .all(&txn)
+        .await?;
+    let mut positions = items.iter().map(|item| item.position).collect::<Vec<_>>();
+    positions.sort_unstable();
+    let existing_ids = ids
+        .iter()
+        .filter(|id| items.iter().any(|item| item.id == **id))
+        .collect::<Vec<_>>();
+    for (id, position) in existing_ids.into_iter().zip(positions) {
+        download_queue::Entity::update_many()
+            .col_expr(download_queue::Column::Position, Expr::value(position))
+            .filter(download_queue::Column::Id.eq(*id))
+            .exec(&txn)
+            .await?;
+    }
+    txn.commit().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use bili_sync_migration::{Migrator, MigratorTrait, SchemaManager};
+    use sea_orm::Database;
+
+    use super::*;
+
+    async fn setup() -> DatabaseConnection {
+        let connection = Database::connect("sqlite::memory:").await.unwrap();
+        // 仅执行创建下载队列的迁移，保证测试使用的表结构与实际一致
+        Migrator::migrations()
+            .into_iter()
+            .find(|migration| migration.name() == "m20251110_120000_add_download_queue")
+            .unwrap()
+            .up(&SchemaManager::new(&connection))
+            .await
+            .unwrap();
+        for (video_id, position) in [(1, 0), (2, 1), (3, 2), (4, 3)] {
+            download_queue::ActiveModel {
+                video_id: Set(video_id),
+                source_type: Set("favorites".to_owned()),
+                source_id: Set(1),
+                priority: Set(0),
+                position: Set(position),
+                status: Set(QueueStatus::Pending),
+                ..Default::default()
+            }
+            .insert(&connection)
+            .await
+            .unwrap();
+        }
+        connection
+    }
+
+    async fn ordered_video_ids(connection: &DatabaseConnection) -> Vec<i32> {
+        download_queue::Entity::find()
+            .order_by_desc(download_queue::Column::Priority)
+            .order_by_asc(download_queue::Column::Position)
+            .all(connection)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|item| item.video_id)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_reorder_and_finish_items() {
+        let connection = setup().await;
+        // 只交换 4 与 2 的位置，其余任务保持原有顺序
+        reorder_items(&[4, 2, 100], &connection).await.unwrap();
+        assert_eq!(ordered_video_ids(&connection).await, vec![1, 4, 3, 2]);
+
+        reset_running_items(&connection).await.unwrap();
+        download_queue::Entity::update_many()
+            .col_expr(download_queue::Column::Status, Expr::value(QueueStatus::Running))
+            .filter(download_queue::Column::Id.is_in([1, 2]))
+            .exec(&connection)
+            .await
+            .unwrap();
+        refresh_queue_stats(&connection).await.unwrap();
+        assert_eq!(
+            *subscribe_queue_stats().borrow(),
+            QueueStats {
+                pending: 2,
+                running: 2,
+                ..Default::default()
+            }
+        );
+        // 执行期间被暂停的任务在结束后保持暂停
+        download_queue::Entity::update_many()
+            .col_expr(download_queue::Column::Status, Expr::value(QueueStatus::Paused))
+            .filter(download_queue::Column::Id.eq(2))
+            .exec(&connection)
+            .await
+            .unwrap();
+        finish_item(1, true, &connection).await.unwrap();
+        finish_item(2, false, &connection).await.unwrap();
+        refresh_queue_stats(&connection).await.unwrap();
+        assert_eq!(
+            *subscribe_queue_stats().borrow(),
+            QueueStats {
+                pending: 2,
+                paused: 1,
+                ..Default::default()
+            }
+        );
+        // 顺序的变化在重新统计后依然保留
+        let revision = subscribe_queue_stats().borrow().revision;
+        mark_queue_reordered();
+        refresh_queue_stats(&connection).await.unwrap();
+        assert_eq!(subscribe_queue_stats().borrow().revision, revision + 1);
+    }
+}
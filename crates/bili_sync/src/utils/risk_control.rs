@@ -0,0 +1,147 @@
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use chrono::{DateTime, Local};
+use parking_lot::Mutex;
+use serde::Serialize;
+
+use crate::config::{RateLimit, RiskControlOption};
+
+/// 退避等级的上限，对应请求间隔放大 2^MAX_LEVEL 倍
+const MAX_LEVEL: u32 = 4;
+
+static STATE: LazyLock<Mutex<RiskControlState>> = LazyLock::new(Default::default);
+
+/// 风控退避的当前状态
+#[derive(Serialize, Clone, Default, Debug, PartialEq)]
+pub struct RiskControlState {
+    /// 退避等级，0 表示正常速率，每次触发风控加一，请求间隔随等级翻倍
+    pub level: u32,
+    /// 程序启动以来触发风控的次数
+    pub count: u64,
+    pub last_source: Option<String>,
+    pub last_error: Option<String>,
+    pub last_triggered: Option<DateTime<Local>>,
+    /// 计划自动重试的时间
+    pub retry_at: Option<DateTime<Local>>,
+    /// 预计恢复正常速率的时间，期间再次触发风控会重新计时
+    pub recover_at: Option<DateTime<Local>>,
+}
+
+impl RiskControlState {
+    /// 超过恢复时间后回到正常速率
+    fn recover(&mut self, now: DateTime<Local>) {
+        if self.level > 0 && self.recover_at.is_some_and(|recover_at| now >= recover_at) {
+            info!("距上次触发风控已超过恢复期，恢复正常请求速率");
+            self.level = 0;
+            self.recover_at = None;
+        }
+    }
+
+    fn record(
+        &mut self,
+        source: &str,
+        error: &str,
+        option: &RiskControlOption,
+        now: DateTime<Local>,
+    ) -> Option<Duration> {
+        self.recover(now);
+        self.count += 1;
+        self.last_source = Some(source.to_owned());
+        self.last_error = Some(error.to_owned());
+        self.last_triggered = Some(now);
+        if !option.enabled {
+            return None;
+        }
+        self.level = (self.level + 1).min(MAX_LEVEL);
+        let backoff = backoff(option, self.level);
+        let retry_at = now + backoff;
+        self.retry_at = Some(retry_at);
+        self.recover_at = Some(retry_at + Duration::from_secs(option.recovery_period));
+        Some(backoff)
+    }
+}
+
+/// 第 level 次连续触发风控后需要等待的时间
+fn backoff(option: &RiskControlOption, level: u32) -> Duration {
+    let secs = option
+        .base_backoff
+        .saturating_mul(1 << level.saturating_sub(1))
+        .min(option.max_backoff);
+    Duration::from_secs(secs)
+}
+
+/// 获取风控退避的当前状态
+pub fn state() -> RiskControlState {
+    let mut state = STATE.lock();
+    state.recover(Local::now());
+    state.clone()
+}
+
+/// 记录一次风控事件并提升退避等级，返回自动重试前需要等待的时间，未启用自适应退避时返回 None
+pub fn record(source: &str, error: &str, option: &RiskControlOption) -> Option<Duration> {
+    STATE.lock().record(source, error, option, Local::now())
+}
+
+/// 新一轮任务开始时清除待执行的自动重试
+pub fn clear_retry() {
+    STATE.lock().retry_at = None;
+}
+
+/// 根据当前的退避等级放慢请求速率，处于正常速率时返回 None
+///
+/// 未设置请求频率限制时以默认的限速配置为基准
+pub fn slowed_rate_limit(rate_limit: Option<&RateLimit>) -> Option<RateLimit> {
+    let level = state().level;
+    if level == 0 {
+        return None;
+    }
+    let rate_limit = rate_limit.cloned().unwrap_or_default();
+    Some(RateLimit {
+        limit: rate_limit.limit,
+        duration: rate_limit.duration << level,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_and_recover() {
+        let option = RiskControlOption {
+            enabled: true,
+            base_backoff: 600,
+            max_backoff: 3000,
+            recovery_period: 3600,
+        };
+        let backoffs = (1..=5)
+            .map(|level| backoff(&option, level).as_secs())
+            .collect::<Vec<_>>();
+        assert_eq!(backoffs, [600, 1200, 2400, 3000, 3000]);
+
+        let now = Local::now();
+        let mut state = RiskControlState::default();
+        assert_eq!(
+            state.record("收藏夹", "风控", &option, now),
+            Some(Duration::from_secs(600))
+        );
+        assert_eq!(
+            state.record("收藏夹", "风控", &option, now),
+            Some(Duration::from_secs(1200))
+        );
+        assert_eq!((state.level, state.count), (2, 2));
+        // 恢复期从自动重试的时间开始计算
+        state.recover(now + Duration::from_secs(1200 + 3599));
+        assert_eq!(state.level, 2);
+        state.recover(now + Duration::from_secs(1200 + 3600));
+        assert_eq!((state.level, state.recover_at), (0, None));
+
+        let disabled = RiskControlOption {
+            enabled: false,
+            ..option
+        };
+        assert_eq!(state.record("收藏夹", "风控", &disabled, now), None);
+        assert_eq!((state.level, state.count), (0, 3));
+    }
+}
@@ -1,6 +1,9 @@
-use bili_sync_entity::rule::{AndGroup, Condition, Rule, RuleTarget};
+use bili_sync_entity::rule::{Condition, Rule, RuleTarget};
 use bili_sync_entity::{page, video};
 use chrono::{Local, NaiveDateTime};
+use serde::Serialize;
+
+use crate::config::GlobalRule;
 
 pub(crate) trait Evaluatable<T> {
     fn evaluate(&self, value: T) -> bool;
@@ -37,6 +40,18 @@ impl Evaluatable<usize> for Condition<usize> {
     }
 }
 
+impl Evaluatable<i64> for Condition<i64> {
+    fn evaluate(&self, value: i64) -> bool {
+        match self {
+            Condition::Equals(expected) => *expected == value,
+            Condition::GreaterThan(threshold) => value > *threshold,
+            Condition::LessThan(threshold) => value < *threshold,
+            Condition::Between(start, end) => value > *start && value < *end,
+            _ => false,
+        }
+    }
+}
+
 impl Evaluatable<&NaiveDateTime> for Condition<NaiveDateTime> {
     fn evaluate(&self, value: &NaiveDateTime) -> bool {
         match self {
@@ -72,6 +87,33 @@ impl FieldEvaluatable for RuleTarget {
                 .map(|pub_time| pub_time.and_utc().with_timezone(&Local).naive_local())
                 .is_some_and(|pub_time| cond.evaluate(&pub_time)),
             RuleTarget::PageCount(cond) => cond.evaluate(pages.len()),
+            RuleTarget::Duration(cond) => cond.evaluate(
+                pages
+                    .iter()
+                    .filter_map(|page| page.duration.try_as_ref())
+                    .map(|duration| *duration as usize)
+                    .sum(),
+            ),
+            RuleTarget::PageDuration(cond) => pages
+                .iter()
+                .filter_map(|page| page.duration.try_as_ref())
+                .any(|duration| cond.evaluate(*duration as usize)),
+            RuleTarget::UpperId(cond) => video.upper_id.try_as_ref().is_some_and(|id| cond.evaluate(*id)),
+            RuleTarget::UpperName(cond) => video.upper_name.try_as_ref().is_some_and(|name| cond.evaluate(name)),
+            RuleTarget::Partition(cond) => video
+                .partition_name
+                .try_as_ref()
+                .and_then(|p| p.as_deref())
+                .is_some_and(|partition| cond.evaluate(partition)),
+            RuleTarget::Intro(cond) => video.intro.try_as_ref().is_some_and(|intro| cond.evaluate(intro)),
+            RuleTarget::ViewCount(cond) => video
+                .play_count
+                .try_as_ref()
+                .is_some_and(|count| cond.evaluate(count_to_usize(*count))),
+            RuleTarget::LikeCount(cond) => video
+                .like_count
+                .try_as_ref()
+                .is_some_and(|count| cond.evaluate(count_to_usize(*count))),
             RuleTarget::Not(inner) => !inner.evaluate(video, pages),
         }
     }
@@ -89,34 +131,81 @@ impl FieldEvaluatable for RuleTarget {
             RuleTarget::FavTime(cond) => cond.evaluate(&video.favtime.and_utc().with_timezone(&Local).naive_local()),
             RuleTarget::PubTime(cond) => cond.evaluate(&video.pubtime.and_utc().with_timezone(&Local).naive_local()),
             RuleTarget::PageCount(cond) => cond.evaluate(pages.len()),
+            RuleTarget::Duration(cond) => cond.evaluate(pages.iter().map(|page| page.duration as usize).sum()),
+            RuleTarget::PageDuration(cond) => pages.iter().any(|page| cond.evaluate(page.duration as usize)),
+            RuleTarget::UpperId(cond) => cond.evaluate(video.upper_id),
+            RuleTarget::UpperName(cond) => cond.evaluate(&video.upper_name),
+            RuleTarget::Partition(cond) => video
+                .partition_name
+                .as_deref()
+                .is_some_and(|partition| cond.evaluate(partition)),
+            RuleTarget::Intro(cond) => cond.evaluate(&video.intro),
+            RuleTarget::ViewCount(cond) => cond.evaluate(count_to_usize(video.play_count)),
+            RuleTarget::LikeCount(cond) => cond.evaluate(count_to_usize(video.like_count)),
             RuleTarget::Not(inner) => !inner.evaluate_model(video, pages),
         }
     }
 }
 
-impl FieldEvaluatable for AndGroup {
-    fn evaluate(&self, video: &video::ActiveModel, pages: &[page::ActiveModel]) -> bool {
-        self.iter().all(|target| target.evaluate(video, pages))
-    }
-
-    fn evaluate_model(&self, video: &video::Model, pages: &[page::Model]) -> bool {
-        self.iter().all(|target| target.evaluate_model(video, pages))
-    }
+/// 数据库中的计数使用 i64 存储，异常的负数按 0 处理
+#[inline]
+fn count_to_usize(count: i64) -> usize {
+    count.max(0) as usize
 }
 
 impl FieldEvaluatable for Rule {
     fn evaluate(&self, video: &video::ActiveModel, pages: &[page::ActiveModel]) -> bool {
-        if self.0.is_empty() {
-            return true;
+        match self {
+            Rule::And(children) => children.iter().all(|child| child.evaluate(video, pages)),
+            Rule::Or(children) => children.iter().any(|child| child.evaluate(video, pages)),
+            Rule::Not(inner) => !inner.evaluate(video, pages),
+            Rule::Target(target) => target.evaluate(video, pages),
         }
-        self.0.iter().any(|group| group.evaluate(video, pages))
     }
 
     fn evaluate_model(&self, video: &video::Model, pages: &[page::Model]) -> bool {
-        if self.0.is_empty() {
-            return true;
+        match self {
+            Rule::And(children) => children.iter().all(|child| child.evaluate_model(video, pages)),
+            Rule::Or(children) => children.iter().any(|child| child.evaluate_model(video, pages)),
+            Rule::Not(inner) => !inner.evaluate_model(video, pages),
+            Rule::Target(target) => target.evaluate_model(video, pages),
         }
-        self.0.iter().any(|group| group.evaluate_model(video, pages))
+    }
+}
+
+/// 规则在某个视频上的评估过程，每个节点记录自身是否满足，用于预览规则效果
+#[derive(Serialize)]
+pub struct RuleExplanation {
+    pub rule: String,
+    pub matched: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<RuleExplanation>,
+}
+
+/// 与 evaluate_model 的结果一致，但不短路求值，以便展示每个子规则的结果
+pub(crate) fn explain_rule(rule: &Rule, video: &video::Model, pages: &[page::Model]) -> RuleExplanation {
+    let explain_children = |children: &[Rule]| -> Vec<RuleExplanation> {
+        children.iter().map(|c| explain_rule(c, video, pages)).collect()
+    };
+    let (matched, children) = match rule {
+        Rule::And(children) => {
+            let children = explain_children(children);
+            (children.iter().all(|c| c.matched), children)
+        }
+        Rule::Or(children) => {
+            let children = explain_children(children);
+            (children.iter().any(|c| c.matched), children)
+        }
+        Rule::Not(inner) => {
+            let inner = explain_rule(inner, video, pages);
+            (!inner.matched, vec![inner])
+        }
+        Rule::Target(target) => (target.evaluate_model(video, pages), Vec::new()),
+    };
+    RuleExplanation {
+        rule: rule.to_string(),
+        matched,
+        children,
     }
 }
 
@@ -131,11 +220,42 @@ impl FieldEvaluatable for Option<Rule> {
     }
 }
 
+impl GlobalRule {
+    /// 将全局规则与视频源规则组合评估，命中 deny 时总是不下载，否则命中 allow 时覆盖视频源规则直接下载
+    pub(crate) fn evaluate(
+        &self,
+        source_rule: &Option<Rule>,
+        video: &video::ActiveModel,
+        pages: &[page::ActiveModel],
+    ) -> bool {
+        if self.deny.as_ref().is_some_and(|rule| rule.evaluate(video, pages)) {
+            return false;
+        }
+        self.allow.as_ref().is_some_and(|rule| rule.evaluate(video, pages)) || source_rule.evaluate(video, pages)
+    }
+
+    pub(crate) fn evaluate_model(
+        &self,
+        source_rule: &Option<Rule>,
+        video: &video::Model,
+        pages: &[page::Model],
+    ) -> bool {
+        if self.deny.as_ref().is_some_and(|rule| rule.evaluate_model(video, pages)) {
+            return false;
+        }
+        self.allow
+            .as_ref()
+            .is_some_and(|rule| rule.evaluate_model(video, pages))
+            || source_rule.evaluate_model(video, pages)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use bili_sync_entity::page;
     use chrono::NaiveDate;
     use sea_orm::ActiveValue::Set;
+    use sea_orm::IntoActiveModel;
 
     use super::*;
 
@@ -143,18 +263,18 @@ mod tests {
     fn test_display() {
         let test_cases = vec![
             (
-                Rule(vec![vec![RuleTarget::Title(Condition::Contains("唐氏".to_string()))]]),
+                Rule::from(vec![vec![RuleTarget::Title(Condition::Contains("唐氏".to_string()))]]),
                 "「（标题包含“唐氏”）」",
             ),
             (
-                Rule(vec![vec![
+                Rule::from(vec![vec![
                     RuleTarget::Title(Condition::Prefix("街霸".to_string())),
                     RuleTarget::Tags(Condition::Contains("套路".to_string())),
                 ]]),
                 "「（标题以“街霸”开头）且（标签包含“套路”）」",
             ),
             (
-                Rule(vec![
+                Rule::from(vec![
                     vec![
                         RuleTarget::Title(Condition::Contains("Rust".to_string())),
                         RuleTarget::PageCount(Condition::GreaterThan(5)),
@@ -172,14 +292,14 @@ mod tests {
                 "「（标题包含“Rust”）且（视频分页数量大于“5”）」或「（标签以“入门”结尾）且（发布时间大于“2023-01-01 00:00:00”）」",
             ),
             (
-                Rule(vec![vec![
+                Rule::from(vec![vec![
                     RuleTarget::Not(Box::new(RuleTarget::Title(Condition::Contains("广告".to_string())))),
                     RuleTarget::PageCount(Condition::LessThan(10)),
                 ]]),
                 "「（标题不包含“广告”）且（视频分页数量小于“10”）」",
             ),
             (
-                Rule(vec![vec![
+                Rule::from(vec![vec![
                     RuleTarget::FavTime(Condition::Between(
                         NaiveDate::from_ymd_opt(2023, 6, 1)
                             .unwrap()
@@ -199,6 +319,14 @@ mod tests {
                 "「（收藏时间在“2023-06-01 00:00:00”和“2023-12-31 23:59:59”之间）且（标签匹配“技术|教程”）」",
                 // autocorrect-enable
             ),
+            (
+                Rule::from(vec![vec![
+                    RuleTarget::Not(Box::new(RuleTarget::PageDuration(Condition::GreaterThan(7200)))),
+                    RuleTarget::UpperId(Condition::Equals(1)),
+                    RuleTarget::ViewCount(Condition::GreaterThan(100000)),
+                ]]),
+                "「（所有分页时长（秒）均不大于“7200”）且（UP 主 ID等于“1”）且（播放数大于“100000”）」",
+            ),
         ];
 
         for (rule, expected) in test_cases {
@@ -217,7 +345,7 @@ mod tests {
                     },
                     vec![],
                 ),
-                Rule(vec![vec![RuleTarget::Title(Condition::Contains("唐氏".to_string()))]]),
+                Rule::from(vec![vec![RuleTarget::Title(Condition::Contains("唐氏".to_string()))]]),
                 true,
             ),
             (
@@ -225,7 +353,7 @@ mod tests {
                     video::ActiveModel::default(),
                     vec![page::ActiveModel::default(); 2],
                 ),
-                Rule(vec![vec![RuleTarget::PageCount(Condition::Equals(1))]]),
+                Rule::from(vec![vec![RuleTarget::PageCount(Condition::Equals(1))]]),
                 false,
             ),
             (
@@ -236,7 +364,7 @@ mod tests {
                     },
                     vec![],
                 ),
-                Rule (vec![vec![RuleTarget::Not(Box::new(RuleTarget::Tags(Condition::Equals(
+                Rule::from(vec![vec![RuleTarget::Not(Box::new(RuleTarget::Tags(Condition::Equals(
                         "原神".to_string(),
                     ))))]],
                 ),
@@ -252,7 +380,7 @@ mod tests {
                     },
                     vec![],
                 ),
-                Rule(vec![vec![RuleTarget::Not(Box::new(RuleTarget::Title(Condition::MatchesRegex(
+                Rule::from(vec![vec![RuleTarget::Not(Box::new(RuleTarget::Title(Condition::MatchesRegex(
                         r"^\S+字(解析|怒扒|拆解)".to_owned(),
                         regex::Regex::new(r"^\S+字(解析|怒扒)").unwrap(),
                     ))))]],
@@ -265,4 +393,200 @@ mod tests {
             assert_eq!(rule.evaluate(&video, &pages), expected);
         }
     }
+
+    #[test]
+    fn test_empty_rule() {
+        let (video, pages) = (video::Model::default(), Vec::<page::Model>::new());
+        // 旧版的空规则不做筛选
+        let legacy: Rule = serde_json::from_str("[]").unwrap();
+        assert_eq!(legacy, Rule::And(Vec::new()));
+        assert!(legacy.evaluate_model(&video, &pages));
+        assert!(explain_rule(&legacy, &video, &pages).matched);
+        // 空的“或”恒为假，反序列化时直接拒绝
+        assert!(!Rule::Or(Vec::new()).evaluate_model(&video, &pages));
+        assert!(!explain_rule(&Rule::Or(Vec::new()), &video, &pages).matched);
+        assert!(serde_json::from_str::<Rule>(r#"{"or": []}"#).is_err());
+        let rule: Rule = serde_json::from_str(r#"{"and": []}"#).unwrap();
+        assert!(rule.evaluate_model(&video, &pages));
+        // 输出的 dsl 文本与评估结果保持一致
+        assert_eq!(Rule::Or(Vec::new()).to_dsl(), "not true");
+        let rule: Rule = Rule::Or(Vec::new()).to_dsl().parse().unwrap();
+        assert!(!rule.evaluate_model(&video, &pages));
+    }
+
+    #[test]
+    fn test_nested_rule() {
+        let rule: Rule = r#"(title contains "Rust" or tags == "教程") and not (pageCount > 1 and duration > 600)"#
+            .parse()
+            .unwrap();
+        let video = video::Model {
+            name: "Rust 入门".to_string(),
+            ..Default::default()
+        };
+        let short_pages = vec![page::Model {
+            duration: 300,
+            ..Default::default()
+        }];
+        let long_pages = vec![
+            page::Model {
+                duration: 300,
+                ..Default::default()
+            },
+            page::Model {
+                duration: 400,
+                ..Default::default()
+            },
+        ];
+        assert!(rule.evaluate_model(&video, &short_pages));
+        assert!(!rule.evaluate_model(&video, &long_pages));
+        assert!(!rule.evaluate_model(&video::Model::default(), &short_pages));
+    }
+
+    #[test]
+    fn test_explain() {
+        let rule: Rule = r#"(title contains "Rust" or tags == "教程") and not pageCount > 1"#
+            .parse()
+            .unwrap();
+        let video = video::Model {
+            name: "Rust 入门".to_string(),
+            ..Default::default()
+        };
+        let pages = vec![page::Model::default(); 2];
+        let explanation = explain_rule(&rule, &video, &pages);
+        assert_eq!(explanation.matched, rule.evaluate_model(&video, &pages));
+        assert!(!explanation.matched);
+        assert_eq!(
+            explanation
+                .children
+                .iter()
+                .map(|c| (c.rule.as_str(), c.matched))
+                .collect::<Vec<_>>(),
+            vec![
+                ("（标题包含“Rust”）或（标签等于“教程”）", true),
+                ("（视频分页数量不大于“1”）", false),
+            ]
+        );
+        assert_eq!(explanation.children[0].children.len(), 2);
+        assert!(explanation.children[0].children[0].matched);
+        assert!(!explanation.children[0].children[1].matched);
+    }
+
+    #[test]
+    fn test_global_rule() {
+        let global_rule = GlobalRule {
+            deny: Some(r#"tags contains "广告""#.parse().unwrap()),
+            allow: Some(r#"upperName == "官方""#.parse().unwrap()),
+        };
+        let source_rule: Option<Rule> = Some(r#"title contains "Rust""#.parse().unwrap());
+        let video = |name: &str, upper_name: &str, tags: &[&str]| video::Model {
+            name: name.to_string(),
+            upper_name: upper_name.to_string(),
+            tags: Some(tags.iter().map(|t| t.to_string()).collect::<Vec<_>>().into()),
+            ..Default::default()
+        };
+        let testcases = [
+            (video("Rust 入门", "某人", &[]), true),
+            (video("Rust 入门", "某人", &["广告"]), false),
+            (video("Go 入门", "某人", &[]), false),
+            (video("Go 入门", "官方", &[]), true),
+            (video("Go 入门", "官方", &["广告"]), false),
+        ];
+        for (video, expected) in testcases {
+            assert_eq!(global_rule.evaluate_model(&source_rule, &video, &[]), expected);
+            assert_eq!(
+                global_rule.evaluate(&source_rule, &video.clone().into_active_model(), &[]),
+                expected
+            );
+        }
+        assert!(GlobalRule::default().evaluate_model(&None, &video("Go", "某人", &[]), &[]));
+    }
+
+    #[test]
+    fn test_global_rule_serde() {
+        let global_rule: GlobalRule =
+            serde_json::from_str(r#"{"deny": "tags contains \"广告\" or duration > 14400", "allow": ""}"#).unwrap();
+        assert!(global_rule.deny.is_some());
+        assert!(global_rule.allow.is_none());
+        assert_eq!(
+            serde_json::to_value(&global_rule).unwrap(),
+            serde_json::json!({"deny": r#"tags contains "广告" or duration > 14400"#, "allow": null})
+        );
+        assert!(serde_json::from_str::<GlobalRule>(r#"{"deny": "title ==", "allow": null}"#).is_err());
+    }
+
+    #[test]
+    fn test_rule_serde_compatibility() {
+        let legacy = r#"[[{"field":"title","rule":{"operator":"contains","value":"a"}},{"field":"not","rule":{"field":"pageCount","rule":{"operator":"greaterThan","value":5}}}]]"#;
+        let rule: Rule = serde_json::from_str(legacy).unwrap();
+        assert_eq!(
+            rule,
+            Rule::Or(vec![Rule::And(vec![
+                Rule::Target(RuleTarget::Title(Condition::Contains("a".to_string()))),
+                Rule::Not(Box::new(Rule::Target(RuleTarget::PageCount(Condition::GreaterThan(5))))),
+            ])])
+        );
+        let json = serde_json::to_string(&rule).unwrap();
+        assert_eq!(
+            json,
+            r#"{"or":[{"and":[{"target":{"field":"title","rule":{"operator":"contains","value":"a"}}},{"not":{"target":{"field":"pageCount","rule":{"operator":"greaterThan","value":5}}}}]}]}"#
+        );
+        assert_eq!(serde_json::from_str::<Rule>(&json).unwrap(), rule);
+        let dsl: Rule = serde_json::from_str(r#""title contains \"a\" and not pageCount > 5""#).unwrap();
+        assert!(dsl.evaluate_model(
+            &video::Model {
+                name: "abc".to_string(),
+                ..Default::default()
+            },
+            &[]
+        ));
+        assert!(serde_json::from_str::<Rule>(r#""title ~ 1""#).is_err());
+    }
+
+    #[test]
+    fn test_evaluate_extended_targets() {
+        let video = video::Model {
+            upper_id: 1,
+            upper_name: "测试UP".to_string(),
+            intro: "一期关于 Rust 的视频".to_string(),
+            partition_name: Some("科技".to_string()),
+            play_count: 150000,
+            like_count: 8000,
+            ..Default::default()
+        };
+        let pages = vec![
+            page::Model {
+                duration: 3600,
+                ..Default::default()
+            },
+            page::Model {
+                duration: 5400,
+                ..Default::default()
+            },
+        ];
+        let test_cases = vec![
+            (RuleTarget::Duration(Condition::GreaterThan(7200)), true),
+            (RuleTarget::PageDuration(Condition::GreaterThan(7200)), false),
+            (RuleTarget::PageDuration(Condition::Between(5000, 6000)), true),
+            (RuleTarget::UpperId(Condition::Equals(2)), false),
+            (RuleTarget::UpperName(Condition::Prefix("测试".to_string())), true),
+            (RuleTarget::Partition(Condition::Equals("科技".to_string())), true),
+            (RuleTarget::Intro(Condition::IContains("rust".to_string())), true),
+            (RuleTarget::ViewCount(Condition::GreaterThan(100000)), true),
+            (RuleTarget::LikeCount(Condition::LessThan(5000)), false),
+        ];
+        for (target, expected) in test_cases {
+            assert_eq!(target.evaluate_model(&video, &pages), expected, "{}", target);
+            let active_pages = pages
+                .iter()
+                .cloned()
+                .map(Into::into)
+                .collect::<Vec<page::ActiveModel>>();
+            assert_eq!(
+                target.evaluate(&video.clone().into(), &active_pages),
+                expected,
+                "{}",
+                target
+            );
+        }
+    }
 }
@@ -1,7 +1,7 @@
 use crate::error::ExecutionStatus;
 
 pub static STATUS_NOT_STARTED: u32 = 0b000;
-pub(super) static STATUS_MAX_RETRY: u32 = 0b100;
+pub static STATUS_MAX_RETRY: u32 = 0b100;
 pub static STATUS_OK: u32 = 0b111;
 pub static STATUS_COMPLETED: u32 = 1 << 31;
 
@@ -20,6 +20,11 @@ impl<const N: usize> Status<N> {
         self.0 >> 31 == 1
     }
 
+    /// 是否存在达到最大重试次数的子任务，新增的子任务在历史数据中为 0，不会被视为失败
+    pub fn has_failed(&self) -> bool {
+        (0..N).any(|i| self.get_status(i) == STATUS_MAX_RETRY)
+    }
+
     /// 依次检查所有子任务是否还应该继续执行，返回一个 bool 数组
     pub fn should_run(&self) -> [bool; N] {
         let mut result = [false; N];
@@ -61,6 +66,11 @@ impl<const N: usize> Status<N> {
         changed
     }
 
+    /// 获取某个子任务的状态
+    pub fn get(&self, offset: usize) -> u32 {
+        self.get_status(offset)
+    }
+
     /// 覆盖某个子任务的状态
     pub fn set(&mut self, offset: usize, status: u32) {
         assert!(status < 0b1000, "status should be less than 0b1000");
@@ -172,8 +182,8 @@ impl<const N: usize> From<[u32; N]> for Status<N> {
     }
 }
 
-/// 包含五个子任务，从前到后依次是：视频封面、视频信息、Up 主头像、Up 主信息、分页下载
-pub type VideoStatus = Status<5>;
+/// 包含六个子任务，从前到后依次是：视频封面、视频信息、Up 主头像、Up 主信息、分页下载、视频评论
+pub type VideoStatus = Status<6>;
 
 /// 包含五个子任务，从前到后分别是：视频封面、视频内容、视频信息、视频弹幕、视频字幕
 pub type PageStatus = Status<5>;
@@ -265,6 +275,19 @@ mod tests {
         assert_eq!(<[u32; 3]>::from(status), [0, 0, 0]);
     }
 
+    #[test]
+    fn test_status_has_failed() {
+        assert!(Status::<3>::from([4, 7, 7]).has_failed());
+        assert!(Status::<3>::from([7, 4, 0]).has_failed());
+        assert!(!Status::<3>::from([7, 7, 7]).has_failed());
+        assert!(!Status::<3>::from([3, 1, 0]).has_failed());
+        // 升级前已完成的视频在新增的子任务上为 0，不应被视为失败
+        let mut status = Status::<3>::from([7, 7, 7]);
+        status.set_status(2, 0);
+        assert!(status.get_completed());
+        assert!(!status.has_failed());
+    }
+
     #[test]
     fn test_status_set() {
         // 设置子状态，从 completed 到 uncompleted
@@ -0,0 +1,131 @@
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+
+/// 控制正在运行的一轮下载任务的暂停、恢复与取消
+///
+/// 取消会终止整轮任务；暂停只会中断正在下载的视频，恢复后继续从下载队列中领取任务。
+/// 两者都会让正在下载的分页提前结束，已经完成的子任务照常写入数据库，未完成的子任务留待之后重新下载
+#[derive(Clone)]
+pub struct TaskControl {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    cancel: CancellationToken,
+    paused: watch::Sender<bool>,
+    /// 当前运行阶段的 token，暂停时取消，恢复时重新创建
+    segment: Mutex<CancellationToken>,
+}
+
+impl Default for TaskControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TaskControl {
+    pub fn new() -> Self {
+        let cancel = CancellationToken::new();
+        let segment = cancel.child_token();
+        Self {
+            inner: Arc::new(Inner {
+                cancel,
+                paused: watch::Sender::new(false),
+                segment: Mutex::new(segment),
+            }),
+        }
+    }
+
+    /// 整轮任务的 token，仅在取消任务时被取消
+    pub fn token(&self) -> &CancellationToken {
+        &self.inner.cancel
+    }
+
+    /// 当前运行阶段的 token，在暂停或取消任务时被取消
+    pub fn segment(&self) -> CancellationToken {
+        self.inner.segment.lock().clone()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        *self.inner.paused.borrow()
+    }
+
+    /// 暂停任务，返回值表示状态是否发生了变化
+    pub fn pause(&self) -> bool {
+        let segment = self.inner.segment.lock();
+        if self.inner.cancel.is_cancelled() || self.is_paused() {
+            return false;
+        }
+        segment.cancel();
+        self.inner.paused.send_replace(true);
+        true
+    }
+
+    /// 恢复暂停的任务，返回值表示状态是否发生了变化
+    pub fn resume(&self) -> bool {
+        let mut segment = self.inner.segment.lock();
+        if self.inner.cancel.is_cancelled() || !self.is_paused() {
+            return false;
+        }
+        *segment = self.inner.cancel.child_token();
+        self.inner.paused.send_replace(false);
+        true
+    }
+
+    /// 取消任务，返回值表示状态是否发生了变化
+    pub fn cancel(&self) -> bool {
+        if self.inner.cancel.is_cancelled() {
+            return false;
+        }
+        self.inner.cancel.cancel();
+        true
+    }
+
+    /// 等待任务恢复运行，任务被取消时返回 false
+    pub async fn wait_resumed(&self) -> bool {
+        let mut paused = self.inner.paused.subscribe();
+        tokio::select! {
+            biased;
+            _ = self.inner.cancel.cancelled() => false,
+            _ = paused.wait_for(|paused| !*paused) => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_task_control() {
+        let control = TaskControl::new();
+        let segment = control.segment();
+        assert!(control.wait_resumed().await);
+        assert!(control.pause());
+        assert!(!control.pause());
+        assert!(segment.is_cancelled() && !control.token().is_cancelled());
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), control.wait_resumed())
+                .await
+                .is_err()
+        );
+        assert!(control.resume());
+        assert!(!control.segment().is_cancelled());
+        assert!(control.wait_resumed().await);
+        // 暂停期间取消任务，等待恢复的 worker 直接退出
+        control.pause();
+        let waiter = tokio::spawn({
+            let control = control.clone();
+            async move { control.wait_resumed().await }
+        });
+        assert!(control.cancel());
+        assert!(!waiter.await.unwrap());
+        assert!(control.segment().is_cancelled());
+        assert!(!control.resume());
+    }
+}
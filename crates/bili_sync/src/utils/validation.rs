@@ -1,5 +1,7 @@
 use std::path::Path;
 
+use bili_sync_entity::trigger::Trigger;
+use croner::parser::{CronParser, Seconds};
 use validator::ValidationError;
 
 use crate::utils::status::{STATUS_NOT_STARTED, STATUS_OK};
@@ -21,3 +23,23 @@ pub fn validate_path(path: &str) -> Result<(), ValidationError> {
         Ok(())
     }
 }
+
+/// 校验 Cron 表达式，格式为“秒 分 时 日 月 周”
+pub fn is_valid_cron(cron: &str) -> bool {
+    CronParser::builder()
+        .seconds(Seconds::Required)
+        .dom_and_dow(true)
+        .build()
+        .parse(cron)
+        .is_ok()
+}
+
+pub fn validate_trigger(trigger: &Trigger) -> Result<(), ValidationError> {
+    match trigger {
+        Trigger::Interval(secs) if *secs <= 60 => Err(ValidationError::new("interval must be greater than 60 seconds")),
+        Trigger::Cron(cron) if !is_valid_cron(cron) => Err(ValidationError::new(
+            "cron expression must be in the format of \"sec min hour day month weekday\"",
+        )),
+        _ => Ok(()),
+    }
+}
@@ -11,23 +11,34 @@ use sea_orm::ActiveValue::Set;
 use sea_orm::TransactionTrait;
 use sea_orm::entity::prelude::*;
 use tokio::fs;
-use tokio::sync::Semaphore;
+use tokio::sync::{Semaphore, watch};
+use tokio_util::future::FutureExt;
 use tokio_util::sync::CancellationToken;
 
 use crate::adapter::{VideoSource, VideoSourceEnum};
-use crate::bilibili::{BestStream, BiliClient, BiliError, Dimension, PageInfo, Video, VideoInfo};
-use crate::config::{ARGS, Config, PathSafeTemplate};
+use crate::bilibili::{
+    BestStream, BiliClient, BiliError, Chapter, Dimension, PageInfo, Video, VideoInfo, chapters_ffmetadata,
+    chapters_xml, comments_html,
+};
+use crate::config::{ARGS, Config};
 use crate::downloader::Downloader;
 use crate::error::ExecutionStatus;
+use crate::notifier::{NotificationEvent, SourceSummary};
 use crate::utils::download_context::DownloadContext;
-use crate::utils::format_arg::{bangumi_page_format_args, page_format_args, video_format_args};
+use crate::utils::layout::{is_bangumi, is_bangumi_extra, page_video_path, video_base_path};
 use crate::utils::model::{
-    create_pages, create_videos, filter_unfilled_videos, filter_unhandled_video_pages, update_pages_model,
+    create_pages, create_videos, filter_danmaku_snapshot_pages, filter_unfilled_videos, update_pages_model,
     update_videos_model,
 };
-use crate::utils::nfo::{NFO, ToNFO};
-use crate::utils::rule::FieldEvaluatable;
+use crate::utils::nfo::{Episode, Movie, NFO, ToNFO};
+use crate::utils::notify::notify;
+use crate::utils::progress::{self, ProgressGuard};
+use crate::utils::queue::{claim_next_item, enqueue_videos, finish_item, refresh_queue_stats, reset_running_items};
 use crate::utils::status::{PageStatus, STATUS_OK, VideoStatus};
+use crate::utils::task_control::TaskControl;
+
+/// 错过最后一次弹幕快照的时间后仍会补抓的天数，更早下载的分页不再抓取，避免开启快照时重新抓取全部历史视频的弹幕
+const DANMAKU_SNAPSHOT_GRACE_DAYS: i64 = 7;
 
 // 全局番剧季度标题缓存
 fn season_title_cache() -> &'static Arc<Mutex<HashMap<String, String>>> {
@@ -36,7 +47,7 @@ fn season_title_cache() -> &'static Arc<Mutex<HashMap<String, String>>> {
 }
 
 /// 获取番剧季标题，优先从缓存获取，缓存未命中时从API获取
-async fn get_cached_season_title(
+pub(crate) async fn get_cached_season_title(
     bili_client: &BiliClient,
     season_id: &str,
     _token: CancellationToken,
@@ -52,6 +63,16 @@ async fn get_cached_season_title(
     get_season_title_from_api(bili_client, season_id).await
 }
 
+/// 番剧需要通过季标题渲染路径，其它视频无需请求
+async fn season_title_for(video_model: &video::Model, cx: DownloadContext<'_>) -> Option<String> {
+    match &video_model.season_id {
+        Some(season_id) if is_bangumi(video_model) => {
+            get_cached_season_title(cx.bili_client, season_id, CancellationToken::new()).await
+        }
+        _ => None,
+    }
+}
+
 async fn get_season_title_from_api(bili_client: &BiliClient, season_id: &str) -> Option<String> {
     let url = format!("https://api.bilibili.com/pgc/view/web/season?season_id={}", season_id);
     tracing::debug!("获取番剧标题: {}", url);
@@ -96,14 +117,26 @@ async fn get_season_title_from_api(bili_client: &BiliClient, season_id: &str) ->
     Some(normalized_title)
 }
 
-/// 完整地处理某个视频来源
+/// 扫描某个视频来源的新视频并补充详情，随后将待下载的视频加入下载队列，返回刷新后的视频来源与处理结果
+///
+/// token 被取消时会在当前阶段结束后（或中断正在进行的详情获取）提前返回，尚未处理的视频留待下一轮继续
 pub async fn process_video_source(
     video_source: VideoSourceEnum,
     bili_client: &BiliClient,
     connection: &DatabaseConnection,
-    template: &handlebars::Handlebars<'_>,
     config: &Config,
-) -> Result<()> {
+    token: &CancellationToken,
+) -> Result<(VideoSourceEnum, SourceSummary)> {
+    let mut summary = SourceSummary {
+        name: video_source.display_name().into(),
+        new_videos: 0,
+        downloaded: 0,
+        failed: 0,
+        error: None,
+    };
+    if token.is_cancelled() {
+        return Ok((video_source, cancelled(summary)));
+    }
     // 预创建视频源目录，提前检测目录是否可写
     video_source.create_dir_all().await?;
     // 从参数中获取视频列表的 Model 与视频流
@@ -111,16 +144,29 @@ pub async fn process_video_source(
         .refresh(bili_client, &config.credential, connection)
         .await?;
     // 从视频流中获取新视频的简要信息，写入数据库
-    refresh_video_source(&video_source, video_streams, connection).await?;
+    summary.new_videos = refresh_video_source(&video_source, video_streams, connection).await?;
     // 单独请求视频详情接口，获取视频的详情信息与所有的分页，写入数据库
-    fetch_video_details(bili_client, &video_source, connection, config).await?;
-    if ARGS.scan_only {
-        warn!("已开启仅扫描模式，跳过视频下载..");
-    } else {
-        // 从数据库中查找所有未下载的视频与分页，下载并处理
-        download_unprocessed_videos(bili_client, &video_source, connection, template, config).await?;
+    fetch_video_details(bili_client, &video_source, connection, config, token).await?;
+    if token.is_cancelled() {
+        return Ok((video_source, cancelled(summary)));
     }
-    Ok(())
+    // 将所有未下载的视频加入下载队列，由 download_queued_videos 统一调度下载
+    let enqueued = enqueue_videos(&video_source, connection).await?;
+    if enqueued > 0 {
+        info!("{}中的 {} 个视频已加入下载队列", video_source.display_name(), enqueued);
+        refresh_queue_stats(connection).await?;
+    }
+    if !ARGS.scan_only && video_source.danmaku_snapshot() && !config.skip_option.no_danmaku {
+        // 为已经下载的视频补抓弹幕快照
+        snapshot_danmaku(bili_client, &video_source, connection, config).await?;
+    }
+    Ok((video_source, summary))
+}
+
+fn cancelled(mut summary: SourceSummary) -> SourceSummary {
+    info!("{}的处理已被取消", summary.name);
+    summary.error = Some("本轮任务已取消，剩余内容将在下一轮继续处理".to_owned());
+    summary
 }
 
 /// 请求接口，获取视频列表中所有新添加的视频信息，将其写入数据库
@@ -128,7 +174,7 @@ pub async fn refresh_video_source<'a>(
     video_source: &VideoSourceEnum,
     video_streams: Pin<Box<dyn Stream<Item = Result<VideoInfo>> + 'a + Send>>,
     connection: &DatabaseConnection,
-) -> Result<()> {
+) -> Result<usize> {
     video_source.log_refresh_video_start();
     let latest_row_at = video_source.get_latest_row_at().and_utc();
     let mut max_datetime = latest_row_at;
@@ -176,7 +222,7 @@ pub async fn refresh_video_source<'a>(
             .await?;
     }
     video_source.log_refresh_video_end(count);
-    Ok(())
+    Ok(count)
 }
 
 /// 筛选出所有未获取到全部信息的视频，尝试补充其详细信息
@@ -185,6 +231,7 @@ pub async fn fetch_video_details(
     video_source: &VideoSourceEnum,
     connection: &DatabaseConnection,
     config: &Config,
+    token: &CancellationToken,
 ) -> Result<()> {
     video_source.log_fetch_video_start();
     let videos_model = filter_unfilled_videos(video_source.filter_expr(), connection).await?;
@@ -240,7 +287,10 @@ pub async fn fetch_video_details(
                     video_source.set_relation_id(&mut video_active_model);
                     video_active_model.single_page = Set(Some(pages.len() == 1 || is_bangumi_extra));
                     video_active_model.tags = Set(Some(tags.into()));
-                    video_active_model.should_download = Set(video_source.rule().evaluate(&video_active_model, &pages));
+                    video_active_model.should_download =
+                        Set(config
+                            .global_rule
+                            .evaluate(video_source.rule(), &video_active_model, &pages));
                     let txn = connection.begin().await?;
                     create_pages(pages, &txn).await?;
                     video_active_model.save(&txn).await?;
@@ -250,124 +300,197 @@ pub async fn fetch_video_details(
             Ok::<_, anyhow::Error>(())
         })
         .collect::<FuturesUnordered<_>>();
-    tasks.try_collect::<Vec<_>>().await?;
+    tasks.take_until(token.cancelled()).try_collect::<Vec<_>>().await?;
     video_source.log_fetch_video_end();
     Ok(())
 }
 
-/// 下载所有未处理成功的视频
-pub async fn download_unprocessed_videos(
+/// 按照优先级与队列顺序下载这些视频源在下载队列中的视频，所有视频源共享同一组下载并发
+///
+/// 视频源在扫描完成后通过 video_sources 陆续加入，无需等待其它视频源扫描结束即可开始下载；
+/// 队列中暂时没有可领取的任务时等待新的视频源加入，发送端被丢弃（全部视频源扫描结束）后退出。
+///
+/// 返回各视频源下载成功与失败的视频数量。任务被暂停时中断正在下载的视频，恢复后继续领取；
+/// 发生风控或任务被取消时中断正在下载的视频并提前返回，被中断的视频留在队列中等待下一轮
+pub async fn download_queued_videos(
+    video_sources: watch::Receiver<Vec<Arc<VideoSourceEnum>>>,
     bili_client: &BiliClient,
-    video_source: &VideoSourceEnum,
     connection: &DatabaseConnection,
     template: &handlebars::Handlebars<'_>,
     config: &Config,
-) -> Result<()> {
-    video_source.log_download_video_start();
+    control: &TaskControl,
+) -> Result<HashMap<(&'static str, i32), (usize, usize)>> {
     let semaphore = Semaphore::new(config.concurrent_limit.video);
     let downloader = Downloader::new(bili_client.client.clone());
-    let cx = DownloadContext::new(bili_client, video_source, template, connection, &downloader, config);
-    let unhandled_videos_pages = filter_unhandled_video_pages(video_source.filter_expr(), connection).await?;
-    let mut assigned_upper = HashSet::new();
-    let tasks = unhandled_videos_pages
-        .into_iter()
-        .map(|(video_model, pages_model)| {
-            let should_download_upper = !assigned_upper.contains(&video_model.upper_id);
-            assigned_upper.insert(video_model.upper_id);
-            download_video_pages(video_model, pages_model, &semaphore, should_download_upper, cx)
-        })
-        .collect::<FuturesUnordered<_>>();
-    let mut risk_control_related_error = None;
-    let mut stream = tasks
-        // 触发风控时设置 download_aborted 标记并终止流
-        .take_while(|res| {
-            if let Err(e) = res
-                && let Some(e) = e.downcast_ref::<BiliError>()
-                && e.is_risk_control_related()
-            {
-                risk_control_related_error = Some(e.clone());
-            }
-            if let Err(e) = res {
-                // 记录错误日志
-                tracing::error!("下载视频失败: {}", e);
-            }
-            futures::future::ready(risk_control_related_error.is_none())
-        })
-        // 过滤掉没有触发风控的普通 Err，只保留正确返回的 Model
-        .filter_map(|res| {
-            if res.is_err() {
-                tracing::warn!("跳过下载失败的视频（非风控错误）");
+    // 领取任务需要串行进行，避免多个 worker 领取到同一个任务
+    let claim_lock = tokio::sync::Mutex::new(());
+    let state = Mutex::new(QueueDrainState::default());
+    let (semaphore, downloader, claim_lock, state) = (&semaphore, &downloader, &claim_lock, &state);
+    let workers = (0..config.concurrent_limit.video).map(|_| {
+        let mut video_sources = video_sources.clone();
+        async move {
+            loop {
+                if state
+                    .lock()
+                    .expect("lock queue state failed")
+                    .risk_control_related_error
+                    .is_some()
+                    || !control.wait_resumed().await
+                {
+                    break;
+                }
+                // 暂停、取消或发生风控时取消该 token，正在下载的视频会在写入已完成的子任务后返回
+                let token = control.segment();
+                // 先标记为已读，领取期间加入的视频源会让之后的等待立即返回
+                let sources = video_sources.borrow_and_update().clone();
+                let keys = {
+                    let mut state = state.lock().expect("lock queue state failed");
+                    sources
+                        .iter()
+                        .map(|s| {
+                            if state.started.insert(s.source_key()) {
+                                s.log_download_video_start();
+                            }
+                            s.source_key()
+                        })
+                        .collect::<Vec<_>>()
+                };
+                let skipped = state.lock().expect("lock queue state failed").skipped.clone();
+                let claimed = if keys.is_empty() {
+                    None
+                } else {
+                    let _guard = claim_lock.lock().await;
+                    claim_next_item(&keys, &skipped, connection).await?
+                };
+                let Some((item, video_model, pages_model)) = claimed else {
+                    // 仍有视频源在扫描时等待其加入，暂停、取消或发生风控时重新检查状态
+                    tokio::select! {
+                        res = video_sources.changed() => {
+                            if res.is_err() {
+                                break;
+                            }
+                        }
+                        _ = token.cancelled() => {}
+                    }
+                    continue;
+                };
+                let key = (item.source_type.as_str(), item.source_id);
+                let video_source = sources
+                    .iter()
+                    .find(|s| s.source_key() == key)
+                    .expect("claimed item must belong to one of the video sources");
+                let should_download_upper = state
+                    .lock()
+                    .expect("lock queue state failed")
+                    .assigned_upper
+                    .insert(video_model.upper_id);
+                refresh_queue_stats(connection).await?;
+                let cx = DownloadContext::new(
+                    bili_client,
+                    video_source,
+                    template,
+                    connection,
+                    downloader,
+                    config,
+                    &token,
+                );
+                let res = download_video_pages(video_model, pages_model, semaphore, should_download_upper, cx).await;
+                let interrupted = token.is_cancelled();
+                let (completed, outcome) = match res {
+                    Ok((model, outcome)) => {
+                        let completed = match &model.download_status {
+                            Set(status) => VideoStatus::from(*status).get_completed(),
+                            _ => false,
+                        };
+                        update_videos_model(vec![model], connection).await?;
+                        (completed, outcome)
+                    }
+                    Err(e) => {
+                        error!("下载视频失败: {:#}", e);
+                        if let Some(e) = e.downcast_ref::<BiliError>()
+                            && e.is_risk_control_related()
+                        {
+                            // 触发风控时记录错误并终止所有 worker
+                            state
+                                .lock()
+                                .expect("lock queue state failed")
+                                .risk_control_related_error = Some(e.clone());
+                            token.cancel();
+                        }
+                        (false, Some(false))
+                    }
+                };
+                finish_item(item.id, completed, connection).await?;
+                // 被中断的视频不计入本轮结果，恢复后可以重新领取
+                if interrupted && !completed {
+                    continue;
+                }
+                let mut state = state.lock().expect("lock queue state failed");
+                if !completed {
+                    state.skipped.push(item.id);
+                }
+                let counts = state.counts.entry(video_source.source_key()).or_default();
+                match outcome {
+                    Some(true) => counts.0 += 1,
+                    Some(false) => counts.1 += 1,
+                    None => {}
+                }
             }
-            futures::future::ready(res.ok())
-        })
-        // 将成功返回的 Model 按十个一组合并
-        .chunks(10);
-    while let Some(models) = stream.next().await {
-        update_videos_model(models, connection).await?;
-    }
-    if let Some(e) = risk_control_related_error {
+            Ok::<_, anyhow::Error>(())
+        }
+    });
+    let res = futures::future::try_join_all(workers).await;
+    // worker 出错退出时可能遗留运行中的任务，统一恢复为等待状态
+    reset_running_items(connection).await?;
+    refresh_queue_stats(connection).await?;
+    res?;
+    let state = std::mem::take(&mut *state.lock().expect("lock queue state failed"));
+    if let Some(e) = state.risk_control_related_error {
         bail!(e);
     }
-    video_source.log_download_video_end();
-    Ok(())
+    video_sources.borrow().iter().for_each(|s| s.log_download_video_end());
+    Ok(state.counts)
 }
 
+#[derive(Default)]
+struct QueueDrainState {
+    /// 本轮已经处理过但未完成的任务，不再重复领取
+    skipped: Vec<i32>,
+    /// 本轮已经处理过头像与信息的 Up 主
+    assigned_upper: HashSet<i64>,
+    /// 已经开始下载的视频源
+    started: HashSet<(&'static str, i32)>,
+    /// 各视频源下载成功与失败的视频数量
+    counts: HashMap<(&'static str, i32), (usize, usize)>,
+    risk_control_related_error: Option<BiliError>,
+}
+
+/// 返回值中的 Option<bool> 表示本次下载的结果：Some(true) 为全部下载成功，Some(false) 为存在失败的子任务，
+/// None 为既未完成也没有失败（如下载被中断）
 pub async fn download_video_pages(
     video_model: video::Model,
     page_models: Vec<page::Model>,
     semaphore: &Semaphore,
     should_download_upper: bool,
     cx: DownloadContext<'_>,
-) -> Result<video::ActiveModel> {
+) -> Result<(video::ActiveModel, Option<bool>)> {
     let _permit = semaphore.acquire().await.context("acquire semaphore failed")?;
     let mut status = VideoStatus::from(video_model.download_status);
     let separate_status = status.should_run();
 
-    // 判断是否为番剧
-    let is_bangumi =
-        video_model.bangumi_id.is_some() || (video_model.source_id.is_some() && video_model.source_type == Some(1));
-
     // 未记录路径时填充，已经填充过路径时使用现有的
     let base_path = if !video_model.path.is_empty() {
         PathBuf::from(&video_model.path)
     } else {
-        // 对于番剧花絮（有非空 section_title），直接使用视频源路径
-        // 不使用 bangumi 模板，避免在 video.path 中存储剧集名目录
-        if is_bangumi
-            && video_model
-                .section_title
-                .as_ref()
-                .map(|s| !s.is_empty())
-                .unwrap_or(false)
-        {
-            cx.video_source.path().to_path_buf()
-        } else {
-            // 根据视频类型选择不同的模板
-            let (template_name, format_args) = if is_bangumi {
-                // 获取番剧标题（从缓存或API）
-                let api_title = if let Some(ref season_id) = video_model.season_id {
-                    get_cached_season_title(cx.bili_client, season_id, CancellationToken::new()).await
-                } else {
-                    None
-                };
-
-                (
-                    "bangumi",
-                    bangumi_page_format_args(
-                        &video_model,
-                        page_models.first().unwrap(),
-                        &cx.config.time_format,
-                        api_title.as_deref(),
-                    ),
-                )
-            } else {
-                ("video", video_format_args(&video_model, &cx.config.time_format))
-            };
-
-            cx.video_source
-                .path()
-                .join(cx.template.path_safe_render(template_name, &format_args)?)
-        }
+        let api_title = season_title_for(&video_model, cx).await;
+        video_base_path(
+            cx.template,
+            cx.video_source.path(),
+            &video_model,
+            &page_models,
+            &cx.config.time_format,
+            api_title.as_deref(),
+        )?
     };
     let upper_id = video_model.upper_id.to_string();
     let base_upper_path = cx
@@ -378,45 +501,78 @@ pub async fn download_video_pages(
     let is_single_page = video_model.single_page.context("single_page is null")?;
     // 对于单页视频，page 的下载已经足够
     // 对于多页视频，page 下载仅包含了分集内容，需要额外补上视频的 poster 的 tvshow.nfo
-    let (res_1, res_2, res_3, res_4, res_5) = tokio::join!(
+    let (res_1, res_2, res_3, res_4, res_5, res_6) = tokio::join!(
         // 下载视频封面
-        fetch_video_poster(
-            separate_status[0] && !is_single_page && !cx.config.skip_option.no_poster,
-            &video_model,
-            base_path.join("poster.jpg"),
-            base_path.join("fanart.jpg"),
-            cx
+        cancellable(
+            cx.token,
+            fetch_video_poster(
+                separate_status[0] && !is_single_page && !cx.config.skip_option.no_poster,
+                &video_model,
+                base_path.join("poster.jpg"),
+                base_path.join("fanart.jpg"),
+                cx
+            )
         ),
         // 生成视频信息的 nfo
-        generate_video_nfo(
-            separate_status[1] && !is_single_page && !cx.config.skip_option.no_video_nfo,
-            &video_model,
-            base_path.join("tvshow.nfo"),
-            cx
+        cancellable(
+            cx.token,
+            generate_video_nfo(
+                separate_status[1] && !is_single_page && !cx.config.skip_option.no_video_nfo,
+                &video_model,
+                base_path.join("tvshow.nfo"),
+                cx
+            )
         ),
         // 下载 Up 主头像
-        fetch_upper_face(
-            separate_status[2] && should_download_upper && !cx.config.skip_option.no_upper,
-            &video_model,
-            base_upper_path.join("folder.jpg"),
-            cx
+        cancellable(
+            cx.token,
+            fetch_upper_face(
+                separate_status[2] && should_download_upper && !cx.config.skip_option.no_upper,
+                &video_model,
+                base_upper_path.join("folder.jpg"),
+                cx
+            )
         ),
         // 生成 Up 主信息的 nfo
-        generate_upper_nfo(
-            separate_status[3] && should_download_upper && !cx.config.skip_option.no_upper,
-            &video_model,
-            base_upper_path.join("person.nfo"),
-            cx,
+        cancellable(
+            cx.token,
+            generate_upper_nfo(
+                separate_status[3] && should_download_upper && !cx.config.skip_option.no_upper,
+                &video_model,
+                base_upper_path.join("person.nfo"),
+                cx,
+            )
         ),
         // 分发并执行分页下载的任务
-        dispatch_download_page(separate_status[4], &video_model, page_models, &base_path, cx)
+        dispatch_download_page(separate_status[4], &video_model, page_models, &base_path, cx),
+        // 下载视频评论
+        cancellable(
+            cx.token,
+            fetch_video_comments(
+                separate_status[5] && cx.config.comment_option.enabled,
+                &video_model,
+                base_path.join("comments.json"),
+                base_path.join("comments.html"),
+                cx
+            )
+        )
     );
-    let results = [res_1.into(), res_2.into(), res_3.into(), res_4.into(), res_5.into()];
+    let results = [
+        res_1.into(),
+        res_2.into(),
+        res_3.into(),
+        res_4.into(),
+        res_5.into(),
+        res_6.into(),
+    ];
     status.update_status(&results);
+    // 分页下载的结果已经在分页中单独输出，此处跳过
     results
         .iter()
-        .take(4)
-        .zip(["封面", "详情", "作者头像", "作者详情"])
+        .enumerate()
+        .filter(|(idx, _)| *idx != 4)
+        .map(|(_, res)| res)
+        .zip(["封面", "详情", "作者头像", "作者详情", "评论"])
         .for_each(|(res, task_name)| match res {
             ExecutionStatus::Skipped => info!("处理视频「{}」{}已成功过，跳过", &video_model.name, task_name),
             ExecutionStatus::Succeeded => info!("处理视频「{}」{}成功", &video_model.name, task_name),
@@ -429,20 +585,82 @@ pub async fn download_video_pages(
             ExecutionStatus::Failed(e) => {
                 error!("处理视频「{}」{}失败：{:#}", &video_model.name, task_name, e)
             }
+            ExecutionStatus::Cancelled => {
+                info!("处理视频「{}」{}已中断，将在之后重新执行", &video_model.name, task_name)
+            }
             ExecutionStatus::Fixed(_) => unreachable!(),
         });
-    for result in results {
-        if let ExecutionStatus::Failed(e) = result
-            && let Ok(e) = e.downcast::<BiliError>()
-            && e.is_risk_control_related()
-        {
-            bail!(e);
+    let mut errors = Vec::new();
+    for (result, task_name) in results
+        .into_iter()
+        .zip(["封面", "详情", "作者头像", "作者详情", "分页", "评论"])
+    {
+        if let ExecutionStatus::Failed(e) = result {
+            match e.downcast::<BiliError>() {
+                Ok(e) if e.is_risk_control_related() => bail!(e),
+                Ok(e) => errors.push(format!("{}：{:#}", task_name, e)),
+                Err(e) => errors.push(format!("{}：{:#}", task_name, e)),
+            }
         }
     }
+    let outcome = if !errors.is_empty() {
+        Some(false)
+    } else {
+        status.get_completed().then_some(true)
+    };
+    // 失败的子任务会在之后的轮次中重试，仅在重试次数耗尽时发送一次失败通知
+    if !errors.is_empty() && status.get_completed() {
+        notify(
+            cx.config,
+            cx.bili_client,
+            NotificationEvent::DownloadFailed {
+                source: cx.video_source.display_name().to_string(),
+                bvid: video_model.bvid.clone(),
+                title: video_model.name.clone(),
+                upper: video_model.upper_name.clone(),
+                errors,
+            },
+        );
+    } else if outcome == Some(true) {
+        notify(
+            cx.config,
+            cx.bili_client,
+            NotificationEvent::VideoDownloaded {
+                source: cx.video_source.display_name().to_string(),
+                bvid: video_model.bvid.clone(),
+                title: video_model.name.clone(),
+                upper: video_model.upper_name.clone(),
+                cover: video_model.cover.clone(),
+                path: base_path.to_string_lossy().to_string(),
+            },
+        );
+    }
     let mut video_active_model: video::ActiveModel = video_model.into();
     video_active_model.download_status = Set(status.into());
     video_active_model.path = Set(base_path.to_string_lossy().to_string());
-    Ok(video_active_model)
+    Ok((video_active_model, outcome))
+}
+
+pub async fn fetch_video_comments(
+    should_run: bool,
+    video_model: &video::Model,
+    json_path: PathBuf,
+    html_path: PathBuf,
+    cx: DownloadContext<'_>,
+) -> Result<ExecutionStatus> {
+    if !should_run {
+        return Ok(ExecutionStatus::Skipped);
+    }
+    let bili_video = Video::new(cx.bili_client, video_model.bvid.clone(), &cx.config.credential);
+    let comments = bili_video.get_comments(&cx.config.comment_option).await?;
+    if let Some(parent) = json_path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+    tokio::try_join!(
+        fs::write(&json_path, serde_json::to_vec_pretty(&comments)?),
+        fs::write(&html_path, comments_html(&video_model.name, &comments))
+    )?;
+    Ok(ExecutionStatus::Succeeded)
 }
 
 /// 分发并执行分页下载任务，当且仅当所有分页成功下载或达到最大重试次数时返回 Ok，否则根据失败原因返回对应的错误
@@ -457,9 +675,10 @@ pub async fn dispatch_download_page(
         return Ok(ExecutionStatus::Skipped);
     }
     let child_semaphore = Semaphore::new(cx.config.concurrent_limit.page);
+    let page_count = page_models.len();
     let tasks = page_models
         .into_iter()
-        .map(|page_model| download_page(video_model, page_model, &child_semaphore, base_path, cx))
+        .map(|page_model| download_page(video_model, page_model, page_count, &child_semaphore, base_path, cx))
         .collect::<FuturesUnordered<_>>();
     let (mut risk_control_related_error, mut target_status) = (None, STATUS_OK);
     let mut stream = tasks
@@ -504,6 +723,7 @@ pub async fn dispatch_download_page(
 pub async fn download_page(
     video_model: &video::Model,
     page_model: page::Model,
+    page_count: usize,
     semaphore: &Semaphore,
     base_path: &Path,
     cx: DownloadContext<'_>,
@@ -513,249 +733,140 @@ pub async fn download_page(
     let separate_status = status.should_run();
     let is_single_page = video_model.single_page.context("single_page is null")?;
 
-    // 判断是否为番剧和花絮（提前计算，用于后续逻辑）
-    let is_bangumi =
-        video_model.bangumi_id.is_some() || (video_model.source_id.is_some() && video_model.source_type == Some(1));
-    // 检查 section_title 是否为非空字符串（花絮才有 section_title）
-    let has_section_title = video_model
-        .section_title
-        .as_ref()
-        .map(|s| !s.is_empty())
-        .unwrap_or(false);
-    let is_bangumi_extra = is_bangumi
-        && (has_section_title || video_model.episode_number.is_none() || video_model.episode_number == Some(0));
-
-    // 添加调试日志
-    tracing::info!(
-        "video {}: bangumi_id={:?}, source_id={:?}, source_type={:?}, section_title={:?}, episode_number={:?}, is_bangumi={}, is_bangumi_extra={}",
-        video_model.bvid,
-        video_model.bangumi_id,
-        video_model.source_id,
-        video_model.source_type,
-        video_model.section_title,
-        video_model.episode_number,
-        is_bangumi,
-        is_bangumi_extra
-    );
+    let is_bangumi_extra = is_bangumi_extra(video_model);
+    let old_video_path = page_model.path.as_deref().filter(|p| !p.is_empty());
 
     // 未记录路径时填充，已经填充过路径时使用现有的
-    let (base_path, base_name) = match &page_model.path {
-        Some(old_video_path) if !old_video_path.is_empty() => {
-            let old_video_path = Path::new(old_video_path);
-            let old_video_filename = old_video_path
-                .file_name()
-                .context("invalid page path format")?
-                .to_string_lossy();
-
-            // 对于番剧花絮，需要重新生成 base_name（使用 page 模板）
-            // 因为旧路径可能使用了 bangumi 模板（如 "3年Z组银八老师 - S01E01"）
-            // 同时需要使用视频源的基础路径，而不是 video_model.path（包含剧集名）
-            if is_bangumi_extra {
-                // 使用视频源的基础路径，而不是 video_model.path
-                let source_base_path = cx.video_source.path();
-                // 如果有 section_title，将其作为子目录加入 source_base_path
-                let base_path = if let Some(ref section_title) = video_model.section_title {
-                    source_base_path.join(section_title)
-                } else {
-                    source_base_path.to_path_buf()
-                };
-                let format_args = page_format_args(video_model, &page_model, &cx.config.time_format);
-                tracing::info!(
-                    "video {} (bangumi extra): show_title={:?}, name={:?}, format_args.title={:?}",
-                    video_model.bvid,
-                    video_model.show_title,
-                    video_model.name,
-                    format_args.get("title")
-                );
-                let base_name = cx.template.path_safe_render("page", &format_args)?;
-                tracing::info!(
-                    "video {} (bangumi extra): rendered base_name={}, source_base_path={}",
-                    video_model.bvid,
-                    base_name,
-                    source_base_path.display()
-                );
-                (base_path, base_name)
-            } else if is_single_page {
-                // 单页下的路径是 {base_path}/{base_name}.mp4
-                (
-                    old_video_path
-                        .parent()
-                        .context("invalid page path format")?
-                        .to_path_buf(),
-                    old_video_filename.trim_end_matches(".mp4").to_string(),
-                )
-            } else {
-                // 多页下的路径是 {base_path}/Season 1/{base_name} - S01Exx.mp4
-                (
-                    old_video_path
-                        .parent()
-                        .and_then(|p| p.parent())
-                        .context("invalid page path format")?
-                        .to_path_buf(),
-                    old_video_filename
-                        .rsplit_once(" - ")
-                        .context("invalid page path format")?
-                        .0
-                        .to_string(),
-                )
-            }
-        }
+    let video_path = match old_video_path {
+        Some(old_video_path) if !is_bangumi_extra => PathBuf::from(old_video_path),
         _ => {
-            // 对于番剧花絮，需要使用视频源的基础路径，而不是 video_model.path（包含剧集名）
-            let base_path = if is_bangumi
-                && video_model
-                    .section_title
-                    .as_ref()
-                    .map(|s| !s.is_empty())
-                    .unwrap_or(false)
+            // 番剧花絮的旧路径可能使用了 bangumi 模板，需要重新生成
+            // 同时使用视频源的基础路径，而不是可能包含剧集名的 video_model.path
+            let base_path = if is_bangumi_extra
+                && (old_video_path.is_some() || video_model.section_title.as_deref().is_some_and(|s| !s.is_empty()))
             {
-                // 番剧花絮：使用视频源的基础路径 + section_title
-                let source_base_path = cx.video_source.path();
-                if let Some(ref section_title) = video_model.section_title {
-                    source_base_path.join(section_title)
-                } else {
-                    source_base_path.to_path_buf()
-                }
+                cx.video_source.path()
             } else {
-                // 其他情况：使用 base_path，如果有 section_title 则作为子目录
-                if let Some(ref section_title) = video_model.section_title {
-                    base_path.join(section_title)
-                } else {
-                    base_path.to_path_buf()
-                }
-            };
-
-            let (template_name, format_args) = if is_bangumi_extra {
-                // 花絮/PV/预告使用 page 模板
-                let format_args = page_format_args(video_model, &page_model, &cx.config.time_format);
-                tracing::info!(
-                    "video {}: is_bangumi_extra=true. show_title={:?}, name={:?}, format_args.title={:?}, section_title={:?}",
-                    video_model.bvid,
-                    video_model.show_title,
-                    video_model.name,
-                    format_args.get("title"),
-                    video_model.section_title
-                );
-                ("page", format_args)
-            } else if is_bangumi {
-                // 正常番剧使用 bangumi 模板
-                tracing::debug!(
-                    "video {}: is_bangumi=true, using bangumi template. episode_number={:?}",
-                    video_model.bvid,
-                    video_model.episode_number
-                );
-                let api_title = if let Some(ref season_id) = video_model.season_id {
-                    get_cached_season_title(cx.bili_client, season_id, CancellationToken::new()).await
-                } else {
-                    None
-                };
-
-                (
-                    "bangumi",
-                    bangumi_page_format_args(video_model, &page_model, &cx.config.time_format, api_title.as_deref()),
-                )
-            } else {
-                // 普通视频使用 page 模板
-                tracing::debug!("video {}: using page template", video_model.bvid);
-                (
-                    "page",
-                    page_format_args(video_model, &page_model, &cx.config.time_format),
-                )
+                base_path
             };
-
-            let base_name = cx.template.path_safe_render(template_name, &format_args)?;
-            tracing::info!(
-                "video {}: rendered base_name={}, base_path={}",
-                video_model.bvid,
-                base_name,
-                base_path.display()
-            );
-            (base_path, base_name)
+            let api_title = season_title_for(video_model, cx).await;
+            page_video_path(
+                cx.template,
+                base_path,
+                video_model,
+                &page_model,
+                page_count,
+                &cx.config.time_format,
+                api_title.as_deref(),
+            )?
         }
     };
+    tracing::debug!(
+        "video {}: page {} path={}",
+        video_model.bvid,
+        page_model.pid,
+        video_path.display()
+    );
 
-    let (poster_path, video_path, nfo_path, danmaku_path, fanart_path, subtitle_path) =
-        if is_single_page || is_bangumi_extra {
-            // 单页视频或番剧花絮使用简单路径格式
-            (
-                base_path.join(format!("{}-poster.jpg", &base_name)),
-                base_path.join(format!("{}.mp4", &base_name)),
-                base_path.join(format!("{}.nfo", &base_name)),
-                base_path.join(format!("{}.zh-CN.default.ass", &base_name)),
-                Some(base_path.join(format!("{}-fanart.jpg", &base_name))),
-                base_path.join(format!("{}.srt", &base_name)),
-            )
+    // 其余文件与视频文件位于同一目录，以视频文件名为前缀
+    let stem = video_path
+        .file_stem()
+        .context("invalid page path format")?
+        .to_string_lossy()
+        .into_owned();
+    let sidecar_path = |suffix: &str| video_path.with_file_name(format!("{}{}", stem, suffix));
+    let (poster_path, nfo_path, danmaku_path, fanart_path, subtitle_path) = (
+        sidecar_path(if is_single_page || is_bangumi_extra {
+            "-poster.jpg"
         } else {
-            // 多页视频使用 Season 1 + S01Exx 格式
-            (
-                base_path
-                    .join("Season 1")
-                    .join(format!("{} - S01E{:0>2}-thumb.jpg", &base_name, page_model.pid)),
-                base_path
-                    .join("Season 1")
-                    .join(format!("{} - S01E{:0>2}.mp4", &base_name, page_model.pid)),
-                base_path
-                    .join("Season 1")
-                    .join(format!("{} - S01E{:0>2}.nfo", &base_name, page_model.pid)),
-                base_path
-                    .join("Season 1")
-                    .join(format!("{} - S01E{:0>2}.zh-CN.default.ass", &base_name, page_model.pid)),
-                // 对于多页视频，会在上一步 fetch_video_poster 中获取剧集的 fanart，无需在此处下载单集的
-                None,
-                base_path
-                    .join("Season 1")
-                    .join(format!("{} - S01E{:0>2}.srt", &base_name, page_model.pid)),
-            )
-        };
-    let dimension = match (page_model.width, page_model.height) {
-        (Some(width), Some(height)) => Some(Dimension {
-            width,
-            height,
-            rotate: 0,
+            "-thumb.jpg"
         }),
-        _ => None,
-    };
-    let page_info = PageInfo {
-        cid: page_model.cid,
-        duration: page_model.duration,
-        dimension,
-        ..Default::default()
+        sidecar_path(".nfo"),
+        sidecar_path(".zh-CN.default.ass"),
+        // 对于多页视频，会在上一步 fetch_video_poster 中获取剧集的 fanart，无需在此处下载单集的
+        (is_single_page || is_bangumi_extra).then(|| sidecar_path("-fanart.jpg")),
+        sidecar_path(".srt"),
+    );
+    let page_info = build_page_info(&page_model);
+    let should_generate_nfo = separate_status[2] && !cx.config.skip_option.no_video_nfo;
+    // 视频看点同时用于封装章节与生成 nfo，除风控外的获取失败不影响其它任务
+    let chapters = if separate_status[1] || should_generate_nfo {
+        match Video::new(cx.bili_client, video_model.bvid.clone(), &cx.config.credential)
+            .get_chapters(&page_info)
+            .await
+        {
+            Ok(chapters) => chapters,
+            Err(e) => {
+                if let Some(inner) = e.downcast_ref::<BiliError>()
+                    && inner.is_risk_control_related()
+                {
+                    bail!(inner.clone());
+                }
+                warn!(
+                    "获取视频「{}」第 {} 页的看点失败，将不写入章节：{:#}",
+                    &video_model.name, page_model.pid, e
+                );
+                Vec::new()
+            }
+        }
+    } else {
+        Vec::new()
     };
+    // 分页视频的下载进度，随视频下载任务结束而停止跟踪
+    let progress = separate_status[1].then(|| progress::track(video_model, &page_model));
     let (res_1, res_2, res_3, res_4, res_5) = tokio::join!(
         // 下载分页封面
-        fetch_page_poster(
-            separate_status[0] && !cx.config.skip_option.no_poster,
-            video_model,
-            &page_model,
-            poster_path,
-            fanart_path,
-            cx
+        cancellable(
+            cx.token,
+            fetch_page_poster(
+                separate_status[0] && !cx.config.skip_option.no_poster,
+                video_model,
+                &page_model,
+                poster_path,
+                fanart_path,
+                cx
+            )
         ),
         // 下载分页视频
-        fetch_page_video(separate_status[1], video_model, &page_info, &video_path, cx),
+        cancellable(
+            cx.token,
+            fetch_page_video(
+                separate_status[1],
+                video_model,
+                &page_info,
+                &chapters,
+                &video_path,
+                progress,
+                cx
+            )
+        ),
         // 生成分页视频信息的 nfo
-        generate_page_nfo(
-            separate_status[2] && !cx.config.skip_option.no_video_nfo,
-            video_model,
-            &page_model,
-            nfo_path,
-            cx,
+        cancellable(
+            cx.token,
+            generate_page_nfo(should_generate_nfo, video_model, &page_model, &chapters, nfo_path, cx)
         ),
         // 下载分页弹幕
-        fetch_page_danmaku(
-            separate_status[3] && !cx.config.skip_option.no_danmaku,
-            video_model,
-            &page_info,
-            danmaku_path,
-            cx,
+        cancellable(
+            cx.token,
+            fetch_page_danmaku(
+                separate_status[3] && !cx.config.skip_option.no_danmaku,
+                video_model,
+                &page_info,
+                danmaku_path,
+                &video_path,
+                cx,
+            )
         ),
         // 下载分页字幕
-        fetch_page_subtitle(
-            separate_status[4] && !cx.config.skip_option.no_subtitle,
-            video_model,
-            &page_info,
-            &subtitle_path,
-            cx
+        cancellable(
+            cx.token,
+            fetch_page_subtitle(
+                separate_status[4] && !cx.config.skip_option.no_subtitle,
+                video_model,
+                &page_info,
+                &subtitle_path,
+                cx
+            )
         )
     );
     let results = [res_1.into(), res_2.into(), res_3.into(), res_4.into(), res_5.into()];
@@ -782,6 +893,10 @@ pub async fn download_page(
                 "处理视频「{}」第 {} 页{}失败：{:#}",
                 &video_model.name, page_model.pid, task_name, e
             ),
+            ExecutionStatus::Cancelled => info!(
+                "处理视频「{}」第 {} 页{}已中断，将在之后重新执行",
+                &video_model.name, page_model.pid, task_name
+            ),
             ExecutionStatus::Fixed(_) => unreachable!(),
         });
     for result in results {
@@ -798,6 +913,16 @@ pub async fn download_page(
     Ok(page_active_model)
 }
 
+/// 执行子任务，token 被取消时中断执行并返回 ExecutionStatus::Cancelled，不计入失败次数
+async fn cancellable(
+    token: &CancellationToken,
+    task: impl Future<Output = Result<ExecutionStatus>>,
+) -> Result<ExecutionStatus> {
+    task.with_cancellation_token(token)
+        .await
+        .unwrap_or(Ok(ExecutionStatus::Cancelled))
+}
+
 pub async fn fetch_page_poster(
     should_run: bool,
     video_model: &video::Model,
@@ -833,12 +958,25 @@ pub async fn fetch_page_video(
     should_run: bool,
     video_model: &video::Model,
     page_info: &PageInfo,
+    chapters: &[Chapter],
     page_path: &Path,
+    progress: Option<ProgressGuard>,
     cx: DownloadContext<'_>,
 ) -> Result<ExecutionStatus> {
     if !should_run {
         return Ok(ExecutionStatus::Skipped);
     }
+    let progress = progress.as_ref().map(ProgressGuard::progress);
+    // 章节侧车文件 chapters.xml 供播放器读取，ffmetadata 仅用于封装时写入章节
+    let ffmetadata = if chapters.is_empty() {
+        None
+    } else {
+        if let Some(parent) = page_path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        fs::write(page_path.with_extension("chapters.xml"), chapters_xml(chapters)).await?;
+        Some(chapters_ffmetadata(chapters))
+    };
     let bili_video = Video::new(cx.bili_client, video_model.bvid.clone(), &cx.config.credential);
     let streams = bili_video
         .get_page_analyzer(page_info)
@@ -850,7 +988,9 @@ pub async fn fetch_page_video(
                 .multi_fetch(
                     &mix_stream.urls(cx.config.cdn_sorting),
                     page_path,
+                    ffmetadata.as_deref(),
                     &cx.config.concurrent_limit.download,
+                    progress,
                 )
                 .await?
         }
@@ -862,7 +1002,9 @@ pub async fn fetch_page_video(
                 .multi_fetch(
                     &video_stream.urls(cx.config.cdn_sorting),
                     page_path,
+                    ffmetadata.as_deref(),
                     &cx.config.concurrent_limit.download,
+                    progress,
                 )
                 .await?
         }
@@ -875,7 +1017,9 @@ pub async fn fetch_page_video(
                     &video_stream.urls(cx.config.cdn_sorting),
                     &audio_stream.urls(cx.config.cdn_sorting),
                     page_path,
+                    ffmetadata.as_deref(),
                     &cx.config.concurrent_limit.download,
+                    progress,
                 )
                 .await?
         }
@@ -888,20 +1032,122 @@ pub async fn fetch_page_danmaku(
     video_model: &video::Model,
     page_info: &PageInfo,
     danmaku_path: PathBuf,
+    video_path: &Path,
     cx: DownloadContext<'_>,
 ) -> Result<ExecutionStatus> {
     if !should_run {
         return Ok(ExecutionStatus::Skipped);
     }
     let bili_video = Video::new(cx.bili_client, video_model.bvid.clone(), &cx.config.credential);
-    bili_video
-        .get_danmaku_writer(page_info)
-        .await?
-        .write(danmaku_path, &cx.config.danmaku_option)
-        .await?;
+    let writer = if cx.video_source.danmaku_snapshot() {
+        // 开启弹幕快照时同时缓存原始弹幕，供后续快照合并
+        bili_video
+            .get_merged_danmaku_writer(page_info, &danmaku_cache_path(video_path))
+            .await?
+    } else {
+        bili_video.get_danmaku_writer(page_info).await?
+    };
+    writer.write(danmaku_path, &cx.config.danmaku_option).await?;
     Ok(ExecutionStatus::Succeeded)
 }
 
+/// 重新抓取到达快照时间的分页弹幕，与缓存的原始弹幕合并后重新生成弹幕文件
+pub async fn snapshot_danmaku(
+    bili_client: &BiliClient,
+    video_source: &VideoSourceEnum,
+    connection: &DatabaseConnection,
+    config: &Config,
+) -> Result<()> {
+    let now = chrono::Utc::now();
+    // 与数据库中 created_at 的格式保持一致，便于直接比较
+    let days_ago = |days: i64| {
+        (now - chrono::Duration::days(days))
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string()
+    };
+    let stages = config
+        .danmaku_snapshot_days
+        .iter()
+        .map(|days| days_ago(*days as i64))
+        .collect::<Vec<_>>();
+    let (Some(first_stage), Some(last_days)) = (stages.first(), config.danmaku_snapshot_days.last()) else {
+        return Ok(());
+    };
+    let pages = filter_danmaku_snapshot_pages(
+        video_source.filter_expr(),
+        (&days_ago(*last_days as i64 + DANMAKU_SNAPSHOT_GRACE_DAYS), first_stage),
+        stages.len() as i32,
+        connection,
+    )
+    .await?;
+    let semaphore = Semaphore::new(config.concurrent_limit.page);
+    let semaphore_ref = &semaphore;
+    let mut tasks = pages
+        .into_iter()
+        .filter_map(|(page_model, video_model)| {
+            // 弹幕没有下载成功的分页不参与快照
+            if PageStatus::from(page_model.download_status).get(3) != STATUS_OK {
+                return None;
+            }
+            // 计算已经到达的快照阶段，错过的多个阶段只补抓一次
+            let reached = stages.iter().filter(|stage| page_model.created_at <= **stage).count() as i32;
+            (reached > page_model.danmaku_snapshot).then_some((page_model, video_model, reached))
+        })
+        .map(|(page_model, video_model, reached)| async move {
+            let _permit = semaphore_ref.acquire().await.context("acquire semaphore failed")?;
+            let video_path = PathBuf::from(page_model.path.as_deref().context("page path is null")?);
+            let page_info = build_page_info(&page_model);
+            let res: Result<()> = async {
+                Video::new(bili_client, video_model.bvid.clone(), &config.credential)
+                    .get_merged_danmaku_writer(&page_info, &danmaku_cache_path(&video_path))
+                    .await?
+                    .write(video_path.with_extension("zh-CN.default.ass"), &config.danmaku_option)
+                    .await
+            }
+            .await;
+            res.with_context(|| format!("视频「{}」第 {} 页", video_model.name, page_model.pid))?;
+            info!("更新视频「{}」第 {} 页的弹幕快照成功", video_model.name, page_model.pid);
+            let mut page_active_model: page::ActiveModel = page_model.into();
+            page_active_model.danmaku_snapshot = Set(reached);
+            page_active_model.save(connection).await?;
+            Ok::<_, anyhow::Error>(())
+        })
+        .collect::<FuturesUnordered<_>>();
+    while let Some(res) = tasks.next().await {
+        if let Err(e) = res {
+            if let Some(inner) = e.downcast_ref::<BiliError>()
+                && inner.is_risk_control_related()
+            {
+                bail!(inner.clone());
+            }
+            error!("更新弹幕快照失败：{:#}", e);
+        }
+    }
+    Ok(())
+}
+
+/// 原始弹幕缓存与视频文件放在一起
+fn danmaku_cache_path(video_path: &Path) -> PathBuf {
+    video_path.with_extension("danmaku.pb")
+}
+
+fn build_page_info(page_model: &page::Model) -> PageInfo {
+    let dimension = match (page_model.width, page_model.height) {
+        (Some(width), Some(height)) => Some(Dimension {
+            width,
+            height,
+            rotate: 0,
+        }),
+        _ => None,
+    };
+    PageInfo {
+        cid: page_model.cid,
+        duration: page_model.duration,
+        dimension,
+        ..Default::default()
+    }
+}
+
 pub async fn fetch_page_subtitle(
     should_run: bool,
     video_model: &video::Model,
@@ -929,6 +1175,7 @@ pub async fn generate_page_nfo(
     should_run: bool,
     video_model: &video::Model,
     page_model: &page::Model,
+    chapters: &[Chapter],
     nfo_path: PathBuf,
     cx: DownloadContext<'_>,
 ) -> Result<ExecutionStatus> {
@@ -937,9 +1184,14 @@ pub async fn generate_page_nfo(
     }
     let single_page = video_model.single_page.context("single_page is null")?;
     let nfo = if single_page {
-        NFO::Movie(video_model.to_nfo(cx.config.nfo_time_type))
+        let mut movie: Movie = video_model.to_nfo(cx.config.nfo_time_type);
+        movie.runtime = Some(page_model.duration);
+        movie.chapters = chapters;
+        NFO::Movie(movie)
     } else {
-        NFO::Episode(page_model.to_nfo(cx.config.nfo_time_type))
+        let mut episode: Episode = page_model.to_nfo(cx.config.nfo_time_type);
+        episode.chapters = chapters;
+        NFO::Episode(episode)
     };
     generate_nfo(nfo, nfo_path).await?;
     Ok(ExecutionStatus::Succeeded)
@@ -1,2 +1,3 @@
 pub mod rule;
 pub mod string_vec;
+pub mod trigger;
@@ -5,6 +5,10 @@ use sea_orm::FromJsonQueryResult;
 use sea_orm::prelude::DateTime;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+pub use self::dsl::ParseRuleError;
+
+mod dsl;
+
 #[derive(Clone, Debug, Serialize, Deserialize, Derivative)]
 #[derivative(PartialEq, Eq)]
 #[serde(rename_all = "camelCase", tag = "operator", content = "value")]
@@ -30,13 +34,93 @@ pub enum RuleTarget {
     FavTime(Condition<DateTime>),
     PubTime(Condition<DateTime>),
     PageCount(Condition<usize>),
+    /// 全部分页的总时长，单位为秒
+    Duration(Condition<usize>),
+    /// 任意一个分页的时长，单位为秒
+    PageDuration(Condition<usize>),
+    UpperId(Condition<i64>),
+    UpperName(Condition<String>),
+    Partition(Condition<String>),
+    Intro(Condition<String>),
+    ViewCount(Condition<usize>),
+    LikeCount(Condition<usize>),
     Not(Box<RuleTarget>),
 }
 
 pub type AndGroup = Vec<RuleTarget>;
 
+/// 规则表达式树，序列化为 {"and": [...]}、{"or": [...]}、{"not": {...}}、{"target": {...}}
+///
+/// 反序列化时兼容旧版的 [[target, ...], ...]（规则组之间为或，组内为且）以及 dsl 字符串
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
-pub struct Rule(pub Vec<AndGroup>);
+#[serde(rename_all = "camelCase", try_from = "RuleRepr")]
+pub enum Rule {
+    And(Vec<Rule>),
+    Or(Vec<Rule>),
+    Not(Box<Rule>),
+    Target(RuleTarget),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RuleRepr {
+    Legacy(Vec<AndGroup>),
+    Dsl(String),
+    Node(RuleNode),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum RuleNode {
+    And(Vec<Rule>),
+    Or(Vec<Rule>),
+    Not(Box<Rule>),
+    Target(RuleTarget),
+}
+
+impl TryFrom<RuleRepr> for Rule {
+    type Error = ParseRuleError;
+
+    fn try_from(repr: RuleRepr) -> Result<Self, Self::Error> {
+        Ok(match repr {
+            RuleRepr::Legacy(groups) => groups.into(),
+            RuleRepr::Dsl(dsl) => dsl.parse()?,
+            RuleRepr::Node(RuleNode::And(children)) => Rule::And(children),
+            // 空的“或”恒为假，通常是误操作，直接拒绝
+            RuleRepr::Node(RuleNode::Or(children)) if children.is_empty() => {
+                return Err(ParseRuleError("“或”中至少需要包含一个条件".to_owned()));
+            }
+            RuleRepr::Node(RuleNode::Or(children)) => Rule::Or(children),
+            RuleRepr::Node(RuleNode::Not(inner)) => Rule::Not(inner),
+            RuleRepr::Node(RuleNode::Target(target)) => target.into(),
+        })
+    }
+}
+
+/// 旧版规则中的单层 Not 统一转换为表达式树中的 Not 节点
+impl From<RuleTarget> for Rule {
+    fn from(target: RuleTarget) -> Self {
+        match target {
+            RuleTarget::Not(inner) => Rule::Not(Box::new((*inner).into())),
+            target => Rule::Target(target),
+        }
+    }
+}
+
+impl From<Vec<AndGroup>> for Rule {
+    fn from(groups: Vec<AndGroup>) -> Self {
+        // 旧版的空规则表示不做筛选，转换为恒为真的空“且”
+        if groups.is_empty() {
+            return Rule::And(Vec::new());
+        }
+        Rule::Or(
+            groups
+                .into_iter()
+                .map(|group| Rule::And(group.into_iter().map(Rule::from).collect()))
+                .collect(),
+        )
+    }
+}
 
 impl<T: Serialize + Display> Display for Condition<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -54,54 +138,105 @@ impl<T: Serialize + Display> Display for Condition<T> {
     }
 }
 
+impl RuleTarget {
+    fn field_name(&self) -> &'static str {
+        match self {
+            RuleTarget::Title(_) => "标题",
+            RuleTarget::Tags(_) => "标签",
+            RuleTarget::FavTime(_) => "收藏时间",
+            RuleTarget::PubTime(_) => "发布时间",
+            RuleTarget::PageCount(_) => "视频分页数量",
+            RuleTarget::Duration(_) => "视频总时长（秒）",
+            RuleTarget::PageDuration(_) => "任一分页时长（秒）",
+            RuleTarget::UpperId(_) => "UP 主 ID",
+            RuleTarget::UpperName(_) => "UP 主名称",
+            RuleTarget::Partition(_) => "分区",
+            RuleTarget::Intro(_) => "简介",
+            RuleTarget::ViewCount(_) => "播放数",
+            RuleTarget::LikeCount(_) => "点赞数",
+            RuleTarget::Not(_) => "格式化失败",
+        }
+    }
+
+    fn condition(&self) -> Option<&dyn Display> {
+        match self {
+            RuleTarget::Title(cond)
+            | RuleTarget::Tags(cond)
+            | RuleTarget::UpperName(cond)
+            | RuleTarget::Partition(cond)
+            | RuleTarget::Intro(cond) => Some(cond),
+            RuleTarget::FavTime(cond) | RuleTarget::PubTime(cond) => Some(cond),
+            RuleTarget::PageCount(cond)
+            | RuleTarget::Duration(cond)
+            | RuleTarget::PageDuration(cond)
+            | RuleTarget::ViewCount(cond)
+            | RuleTarget::LikeCount(cond) => Some(cond),
+            RuleTarget::UpperId(cond) => Some(cond),
+            RuleTarget::Not(_) => None,
+        }
+    }
+}
+
 impl Display for RuleTarget {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        fn get_field_name(rt: &RuleTarget, depth: usize) -> &'static str {
-            match rt {
-                RuleTarget::Title(_) => "标题",
-                RuleTarget::Tags(_) => "标签",
-                RuleTarget::FavTime(_) => "收藏时间",
-                RuleTarget::PubTime(_) => "发布时间",
-                RuleTarget::PageCount(_) => "视频分页数量",
-                RuleTarget::Not(inner) => {
-                    if depth == 0 {
-                        get_field_name(inner, depth + 1)
-                    } else {
-                        "格式化失败"
-                    }
+        let (negated, target) = match self {
+            RuleTarget::Not(inner) => (true, inner.as_ref()),
+            _ => (false, self),
+        };
+        // 旧版的 Not 仅支持单层，多层嵌套应使用 Rule::Not 表达
+        let Some(cond) = target.condition() else {
+            return write!(f, "格式化失败");
+        };
+        match (negated, target) {
+            // 任一分页满足条件的否定是所有分页都不满足
+            (true, RuleTarget::PageDuration(_)) => write!(f, "所有分页时长（秒）均不{}", cond),
+            (negated, target) => write!(f, "{}{}{}", target.field_name(), if negated { "不" } else { "" }, cond),
+        }
+    }
+}
+
+impl Rule {
+    fn fmt_nested(&self, f: &mut std::fmt::Formatter<'_>, nested: bool) -> std::fmt::Result {
+        let join = |f: &mut std::fmt::Formatter<'_>, children: &[Rule], sep: &str| {
+            for (idx, child) in children.iter().enumerate() {
+                if idx > 0 {
+                    write!(f, "{}", sep)?;
                 }
+                child.fmt_nested(f, true)?;
             }
-        }
-        let field_name = get_field_name(self, 0);
+            Ok(())
+        };
         match self {
-            RuleTarget::Not(inner) => match inner.as_ref() {
-                RuleTarget::Title(cond) | RuleTarget::Tags(cond) => write!(f, "{}不{}", field_name, cond),
-                RuleTarget::FavTime(cond) | RuleTarget::PubTime(cond) => {
-                    write!(f, "{}不{}", field_name, cond)
+            Rule::Target(target) => write!(f, "（{}）", target),
+            // 对单个条件取反时沿用“标题不包含”的写法
+            Rule::Not(inner) => match inner.as_ref() {
+                Rule::Target(target) if !matches!(target, RuleTarget::Not(_)) => {
+                    write!(f, "（{}）", RuleTarget::Not(Box::new(target.clone())))
+                }
+                inner => {
+                    write!(f, "非")?;
+                    inner.fmt_nested(f, true)
                 }
-                RuleTarget::PageCount(cond) => write!(f, "{}不{}", field_name, cond),
-                RuleTarget::Not(_) => write!(f, "格式化失败"),
             },
-            RuleTarget::Title(cond) | RuleTarget::Tags(cond) => write!(f, "{}{}", field_name, cond),
-            RuleTarget::FavTime(cond) | RuleTarget::PubTime(cond) => {
-                write!(f, "{}{}", field_name, cond)
+            Rule::And(children) => {
+                write!(f, "「")?;
+                join(f, children, "且")?;
+                write!(f, "」")
+            }
+            // 顶层的“或”不加括号，与旧版的规则组展示保持一致
+            Rule::Or(children) if !nested => join(f, children, "或"),
+            Rule::Or(children) => {
+                write!(f, "「")?;
+                join(f, children, "或")?;
+                write!(f, "」")
             }
-            RuleTarget::PageCount(cond) => write!(f, "{}{}", field_name, cond),
         }
     }
 }
 
 impl Display for Rule {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let groups: Vec<String> = self
-            .0
-            .iter()
-            .map(|group| {
-                let conditions: Vec<String> = group.iter().map(|target| format!("（{}）", target)).collect();
-                format!("「{}」", conditions.join("且"))
-            })
-            .collect();
-        write!(f, "{}", groups.join("或"))
+        self.fmt_nested(f, false)
     }
 }
 
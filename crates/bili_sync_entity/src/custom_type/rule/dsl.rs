@@ -0,0 +1,508 @@
+//! 规则的文本表示，便于在配置中书写与版本管理，例如：
+//!
+//! ```text
+//! (title contains "Rust" or tags == "教程") and not (duration > 7200 and viewCount < 1000)
+//! ```
+//!
+//! - 逻辑运算：`and`、`or`、`not` 与括号，优先级 not > and > or，`true` 表示恒为真的空规则
+//! - 比较运算：`==`、`>`、`<`、`contains`、`icontains`、`prefix`、`suffix`、`matches`（仅文本字段）、`between a, b`
+//! - 字段名与 json 中的 field 一致，文本与时间使用双引号包裹，时间格式为 `%Y-%m-%d %H:%M:%S`
+use std::fmt::{Display, Write};
+use std::str::FromStr;
+
+use sea_orm::prelude::DateTime;
+use serde::Serialize;
+
+use super::{Condition, Rule, RuleTarget};
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseRuleError(pub(super) String);
+
+impl Display for ParseRuleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "规则解析失败：{}", self.0)
+    }
+}
+
+impl std::error::Error for ParseRuleError {}
+
+type Result<T> = std::result::Result<T, ParseRuleError>;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    Comma,
+    Eq,
+    Gt,
+    Lt,
+    Str(String),
+    Num(i64),
+    Ident(String),
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::LParen => write!(f, "("),
+            Token::RParen => write!(f, ")"),
+            Token::Comma => write!(f, ","),
+            Token::Eq => write!(f, "=="),
+            Token::Gt => write!(f, ">"),
+            Token::Lt => write!(f, "<"),
+            Token::Str(s) => write!(f, "{:?}", s),
+            Token::Num(n) => write!(f, "{}", n),
+            Token::Ident(ident) => write!(f, "{}", ident),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().enumerate().peekable();
+    while let Some((pos, ch)) = chars.next() {
+        let token = match ch {
+            c if c.is_whitespace() => continue,
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            ',' => Token::Comma,
+            '>' => Token::Gt,
+            '<' => Token::Lt,
+            '=' => match chars.next() {
+                Some((_, '=')) => Token::Eq,
+                _ => return Err(ParseRuleError(format!("第 {} 个字符处应为“==”", pos + 1))),
+            },
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, 'n')) => value.push('\n'),
+                            Some((_, c)) => value.push(c),
+                            None => break,
+                        },
+                        Some((_, c)) => value.push(c),
+                        None => return Err(ParseRuleError(format!("第 {} 个字符处的字符串未闭合", pos + 1))),
+                    }
+                }
+                Token::Str(value)
+            }
+            c if c.is_ascii_digit() || c == '-' => {
+                let mut literal = c.to_string();
+                while let Some((_, c)) = chars.next_if(|(_, c)| c.is_ascii_digit()) {
+                    literal.push(c);
+                }
+                Token::Num(
+                    literal
+                        .parse()
+                        .map_err(|_| ParseRuleError(format!("第 {} 个字符处的数字“{}”无效", pos + 1, literal)))?,
+                )
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut ident = c.to_string();
+                while let Some((_, c)) = chars.next_if(|(_, c)| c.is_alphanumeric() || *c == '_') {
+                    ident.push(c);
+                }
+                Token::Ident(ident)
+            }
+            c => return Err(ParseRuleError(format!("第 {} 个字符“{}”无法识别", pos + 1, c))),
+        };
+        tokens.push((pos, token));
+    }
+    Ok(tokens)
+}
+
+enum FieldKind {
+    Text(fn(Condition<String>) -> RuleTarget),
+    Time(fn(Condition<DateTime>) -> RuleTarget),
+    Count(fn(Condition<usize>) -> RuleTarget),
+    Id(fn(Condition<i64>) -> RuleTarget),
+}
+
+fn field_kind(name: &str) -> Option<FieldKind> {
+    Some(match name {
+        "title" => FieldKind::Text(RuleTarget::Title),
+        "tags" => FieldKind::Text(RuleTarget::Tags),
+        "upperName" => FieldKind::Text(RuleTarget::UpperName),
+        "partition" => FieldKind::Text(RuleTarget::Partition),
+        "intro" => FieldKind::Text(RuleTarget::Intro),
+        "favTime" => FieldKind::Time(RuleTarget::FavTime),
+        "pubTime" => FieldKind::Time(RuleTarget::PubTime),
+        "pageCount" => FieldKind::Count(RuleTarget::PageCount),
+        "duration" => FieldKind::Count(RuleTarget::Duration),
+        "pageDuration" => FieldKind::Count(RuleTarget::PageDuration),
+        "viewCount" => FieldKind::Count(RuleTarget::ViewCount),
+        "likeCount" => FieldKind::Count(RuleTarget::LikeCount),
+        "upperId" => FieldKind::Id(RuleTarget::UpperId),
+        _ => return None,
+    })
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    cursor: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.cursor).map(|(_, token)| token)
+    }
+
+    fn next(&mut self) -> Result<Token> {
+        let token = self
+            .tokens
+            .get(self.cursor)
+            .map(|(_, token)| token.clone())
+            .ok_or_else(|| ParseRuleError("规则意外结束".to_owned()))?;
+        self.cursor += 1;
+        Ok(token)
+    }
+
+    fn error(&self, expected: &str) -> ParseRuleError {
+        match self.tokens.get(self.cursor) {
+            Some((pos, token)) => ParseRuleError(format!("第 {} 个字符处应为{}，实际为“{}”", pos + 1, expected, token)),
+            None => ParseRuleError(format!("规则意外结束，应为{}", expected)),
+        }
+    }
+
+    /// 关键字不区分大小写
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Ident(ident)) if ident.eq_ignore_ascii_case(keyword)) {
+            self.cursor += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<()> {
+        if self.peek() == Some(&expected) {
+            self.cursor += 1;
+            return Ok(());
+        }
+        Err(self.error(&format!("“{}”", expected)))
+    }
+
+    fn parse_or(&mut self) -> Result<Rule> {
+        let mut children = vec![self.parse_and()?];
+        while self.eat_keyword("or") {
+            children.push(self.parse_and()?);
+        }
+        Ok(if children.len() == 1 {
+            children.remove(0)
+        } else {
+            Rule::Or(children)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<Rule> {
+        let mut children = vec![self.parse_unary()?];
+        while self.eat_keyword("and") {
+            children.push(self.parse_unary()?);
+        }
+        Ok(if children.len() == 1 {
+            children.remove(0)
+        } else {
+            Rule::And(children)
+        })
+    }
+
+    fn parse_unary(&mut self) -> Result<Rule> {
+        if self.eat_keyword("not") {
+            return Ok(Rule::Not(Box::new(self.parse_unary()?)));
+        }
+        if self.eat_keyword("true") {
+            return Ok(Rule::And(Vec::new()));
+        }
+        if self.peek() == Some(&Token::LParen) {
+            self.cursor += 1;
+            let rule = self.parse_or()?;
+            self.expect(Token::RParen)?;
+            return Ok(rule);
+        }
+        self.parse_target().map(Rule::Target)
+    }
+
+    fn parse_target(&mut self) -> Result<RuleTarget> {
+        let kind = match self.peek() {
+            Some(Token::Ident(ident)) => field_kind(ident),
+            _ => None,
+        }
+        .ok_or_else(|| self.error("字段名"))?;
+        self.cursor += 1;
+        Ok(match kind {
+            FieldKind::Text(build) => {
+                if self.eat_keyword("matches") {
+                    let pattern = self.parse_string()?;
+                    let regex = regex::Regex::new(&pattern)
+                        .map_err(|e| ParseRuleError(format!("正则表达式“{}”无效：{}", pattern, e)))?;
+                    build(Condition::MatchesRegex(pattern, regex))
+                } else {
+                    build(self.parse_condition(Self::parse_string)?)
+                }
+            }
+            FieldKind::Time(build) => build(self.parse_condition(|p| {
+                let value = p.parse_string()?;
+                parse_time(&value).ok_or_else(|| ParseRuleError(format!("时间“{}”格式无效", value)))
+            })?),
+            FieldKind::Count(build) => build(self.parse_condition(|p| {
+                let value = p.parse_number()?;
+                usize::try_from(value).map_err(|_| ParseRuleError(format!("数值“{}”不能为负数", value)))
+            })?),
+            FieldKind::Id(build) => build(self.parse_condition(Self::parse_number)?),
+        })
+    }
+
+    fn parse_condition<T: Serialize + Display>(
+        &mut self,
+        parse_value: impl Fn(&mut Self) -> Result<T>,
+    ) -> Result<Condition<T>> {
+        let condition = match self.peek() {
+            Some(Token::Eq) => Condition::Equals,
+            Some(Token::Gt) => Condition::GreaterThan,
+            Some(Token::Lt) => Condition::LessThan,
+            Some(Token::Ident(ident)) => match ident.to_ascii_lowercase().as_str() {
+                "contains" => Condition::Contains,
+                "icontains" => Condition::IContains,
+                "prefix" => Condition::Prefix,
+                "suffix" => Condition::Suffix,
+                "between" => {
+                    self.cursor += 1;
+                    let start = parse_value(self)?;
+                    self.expect(Token::Comma)?;
+                    let end = parse_value(self)?;
+                    return Ok(Condition::Between(start, end));
+                }
+                _ => return Err(self.error("比较运算符")),
+            },
+            _ => return Err(self.error("比较运算符")),
+        };
+        self.cursor += 1;
+        Ok(condition(parse_value(self)?))
+    }
+
+    fn parse_string(&mut self) -> Result<String> {
+        match self.peek() {
+            Some(Token::Str(_)) => match self.next()? {
+                Token::Str(value) => Ok(value),
+                _ => unreachable!(),
+            },
+            _ => Err(self.error("字符串")),
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<i64> {
+        match self.peek() {
+            Some(Token::Num(value)) => {
+                let value = *value;
+                self.cursor += 1;
+                Ok(value)
+            }
+            _ => Err(self.error("数字")),
+        }
+    }
+}
+
+fn parse_time(value: &str) -> Option<DateTime> {
+    DateTime::parse_from_str(value, TIME_FORMAT)
+        .or_else(|_| DateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S"))
+        .ok()
+}
+
+impl FromStr for Rule {
+    type Err = ParseRuleError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            cursor: 0,
+        };
+        let rule = parser.parse_or()?;
+        if parser.cursor < parser.tokens.len() {
+            return Err(parser.error("“and”、“or”或规则结尾"));
+        }
+        Ok(rule)
+    }
+}
+
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' | '\\' => {
+                quoted.push('\\');
+                quoted.push(ch);
+            }
+            '\n' => quoted.push_str("\\n"),
+            _ => quoted.push(ch),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn write_condition<T: Serialize + Display>(
+    out: &mut String,
+    field: &str,
+    condition: &Condition<T>,
+    fmt: impl Fn(&T) -> String,
+) {
+    let _ = match condition {
+        Condition::Equals(v) => write!(out, "{} == {}", field, fmt(v)),
+        Condition::Contains(v) => write!(out, "{} contains {}", field, fmt(v)),
+        Condition::IContains(v) => write!(out, "{} icontains {}", field, fmt(v)),
+        Condition::MatchesRegex(pattern, _) => write!(out, "{} matches {}", field, quote(pattern)),
+        Condition::Prefix(v) => write!(out, "{} prefix {}", field, fmt(v)),
+        Condition::Suffix(v) => write!(out, "{} suffix {}", field, fmt(v)),
+        Condition::GreaterThan(v) => write!(out, "{} > {}", field, fmt(v)),
+        Condition::LessThan(v) => write!(out, "{} < {}", field, fmt(v)),
+        Condition::Between(start, end) => write!(out, "{} between {}, {}", field, fmt(start), fmt(end)),
+    };
+}
+
+fn write_target(out: &mut String, target: &RuleTarget) {
+    let text = |v: &String| quote(v);
+    let time = |v: &DateTime| quote(&v.format(TIME_FORMAT).to_string());
+    let number = |v: &usize| v.to_string();
+    match target {
+        RuleTarget::Title(c) => write_condition(out, "title", c, text),
+        RuleTarget::Tags(c) => write_condition(out, "tags", c, text),
+        RuleTarget::UpperName(c) => write_condition(out, "upperName", c, text),
+        RuleTarget::Partition(c) => write_condition(out, "partition", c, text),
+        RuleTarget::Intro(c) => write_condition(out, "intro", c, text),
+        RuleTarget::FavTime(c) => write_condition(out, "favTime", c, time),
+        RuleTarget::PubTime(c) => write_condition(out, "pubTime", c, time),
+        RuleTarget::PageCount(c) => write_condition(out, "pageCount", c, number),
+        RuleTarget::Duration(c) => write_condition(out, "duration", c, number),
+        RuleTarget::PageDuration(c) => write_condition(out, "pageDuration", c, number),
+        RuleTarget::ViewCount(c) => write_condition(out, "viewCount", c, number),
+        RuleTarget::LikeCount(c) => write_condition(out, "likeCount", c, number),
+        RuleTarget::UpperId(c) => write_condition(out, "upperId", c, |v| v.to_string()),
+        RuleTarget::Not(inner) => {
+            out.push_str("not ");
+            write_target(out, inner);
+        }
+    }
+}
+
+impl Rule {
+    /// 输出为 dsl 文本，可以被 `str::parse` 重新解析
+    pub fn to_dsl(&self) -> String {
+        let mut out = String::new();
+        self.write_dsl(&mut out);
+        out
+    }
+
+    fn write_dsl(&self, out: &mut String) {
+        let write_children = |out: &mut String, children: &[Rule], sep: &str, wrap: fn(&Rule) -> bool| {
+            // 空的“且”恒为真，空的“或”恒为假
+            if children.is_empty() {
+                out.push_str(if sep == " and " { "true" } else { "not true" });
+                return;
+            }
+            for (idx, child) in children.iter().enumerate() {
+                if idx > 0 {
+                    out.push_str(sep);
+                }
+                if wrap(child) {
+                    out.push('(');
+                    child.write_dsl(out);
+                    out.push(')');
+                } else {
+                    child.write_dsl(out);
+                }
+            }
+        };
+        match self {
+            Rule::Target(target) => write_target(out, target),
+            Rule::Not(inner) => {
+                out.push_str("not ");
+                match inner.as_ref() {
+                    Rule::Target(_) | Rule::Not(_) => inner.write_dsl(out),
+                    _ => {
+                        out.push('(');
+                        inner.write_dsl(out);
+                        out.push(')');
+                    }
+                }
+            }
+            Rule::And(children) => write_children(
+                out,
+                children,
+                " and ",
+                |child| matches!(child, Rule::And(c) | Rule::Or(c) if !c.is_empty()),
+            ),
+            Rule::Or(children) => write_children(
+                out,
+                children,
+                " or ",
+                |child| matches!(child, Rule::Or(c) if !c.is_empty()),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_print() {
+        let rule: Rule = r#"(title contains "Rust" or tags == "教程") and not (duration > 7200 and viewCount < 1000) and upperId between -1, 100"#
+            .parse()
+            .unwrap();
+        assert_eq!(
+            rule,
+            Rule::And(vec![
+                Rule::Or(vec![
+                    Rule::Target(RuleTarget::Title(Condition::Contains("Rust".to_owned()))),
+                    Rule::Target(RuleTarget::Tags(Condition::Equals("教程".to_owned()))),
+                ]),
+                Rule::Not(Box::new(Rule::And(vec![
+                    Rule::Target(RuleTarget::Duration(Condition::GreaterThan(7200))),
+                    Rule::Target(RuleTarget::ViewCount(Condition::LessThan(1000))),
+                ]))),
+                Rule::Target(RuleTarget::UpperId(Condition::Between(-1, 100))),
+            ])
+        );
+        assert_eq!(
+            rule.to_dsl(),
+            r#"(title contains "Rust" or tags == "教程") and not (duration > 7200 and viewCount < 1000) and upperId between -1, 100"#
+        );
+        assert_eq!(
+            rule.to_string(),
+            "「「（标题包含“Rust”）或（标签等于“教程”）」且非「（视频总时长（秒）大于“7200”）且（播放数小于“1000”）」且（UP 主 ID在“-1”和“100”之间）」"
+        );
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        for dsl in [
+            r#"title matches "^\\S+字(解析|怒扒)" or not not intro icontains "a\"b""#,
+            r#"favTime between "2023-06-01 00:00:00", "2023-12-31 23:59:59" and (pageCount == 1 or pageDuration < 60)"#,
+            "true",
+        ] {
+            let rule = dsl.parse::<Rule>().unwrap();
+            assert_eq!(rule.to_dsl().parse::<Rule>().unwrap(), rule, "{}", dsl);
+        }
+    }
+
+    #[test]
+    fn test_parse_error() {
+        for dsl in [
+            r#"title contains"#,
+            r#"title > "#,
+            r#"pageCount == -1"#,
+            r#"favTime > "yesterday""#,
+            r#"(title == "a""#,
+            r#"title == "a" tags == "b""#,
+            r#"pageCount matches "1""#,
+            r#"unknown == "1""#,
+        ] {
+            assert!(dsl.parse::<Rule>().is_err(), "{}", dsl);
+        }
+    }
+}
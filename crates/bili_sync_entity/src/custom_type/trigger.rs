@@ -0,0 +1,32 @@
+use sea_orm::FromJsonQueryResult;
+use serde::{Deserialize, Serialize};
+
+/// 任务的触发条件，数字表示执行间隔的秒数，字符串表示 Cron 表达式
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+#[serde(untagged)]
+pub enum Trigger {
+    Interval(u64),
+    Cron(String),
+}
+
+impl Default for Trigger {
+    fn default() -> Self {
+        Trigger::Interval(1200)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trigger_serde() {
+        for (trigger, json) in [
+            (Trigger::Interval(600), "600"),
+            (Trigger::Cron("0 0 2 * * *".to_owned()), r#""0 0 2 * * *""#),
+        ] {
+            assert_eq!(serde_json::to_string(&trigger).unwrap(), json);
+            assert_eq!(serde_json::from_str::<Trigger>(json).unwrap(), trigger);
+        }
+    }
+}
@@ -3,6 +3,7 @@
 use sea_orm::entity::prelude::*;
 
 use crate::rule::Rule;
+use crate::trigger::Trigger;
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
 #[sea_orm(table_name = "collection")]
@@ -18,6 +19,8 @@ pub struct Model {
     pub latest_row_at: DateTime,
     pub rule: Option<Rule>,
     pub enabled: bool,
+    pub danmaku_snapshot: bool,
+    pub trigger: Option<Trigger>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
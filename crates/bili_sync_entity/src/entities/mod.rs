@@ -5,6 +5,7 @@ pub mod prelude;
 pub mod bangumi;
 pub mod collection;
 pub mod config;
+pub mod download_queue;
 pub mod favorite;
 pub mod page;
 pub mod submission;
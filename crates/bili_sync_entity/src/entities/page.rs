@@ -17,6 +17,7 @@ pub struct Model {
     pub path: Option<String>,
     pub image: Option<String>,
     pub download_status: u32,
+    pub danmaku_snapshot: i32,
     pub created_at: String,
 }
 
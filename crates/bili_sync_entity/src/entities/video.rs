@@ -45,6 +45,12 @@ pub struct Model {
     pub section_title: Option<String>,
     pub show_season_type: Option<i32>,
     pub actors: Option<String>,
+    // 视频详情中的统计信息与分区
+    pub play_count: i64,
+    pub like_count: i64,
+    pub coin_count: i64,
+    pub favorite_count: i64,
+    pub partition_name: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -18,6 +18,11 @@ mod m20250613_043257_add_config;
 mod m20250712_080013_add_video_created_at_index;
 mod m20250903_094454_add_rule_and_should_download;
 mod m20251009_123713_add_use_dynamic_api;
+mod m20251021_083012_add_danmaku_snapshot;
+mod m20251023_142530_add_video_stat;
+mod m20251025_101500_convert_rule_to_tree;
+mod m20251104_093000_add_source_trigger;
+mod m20251110_120000_add_download_queue;
 
 pub struct Migrator;
 
@@ -43,6 +48,11 @@ impl MigratorTrait for Migrator {
             Box::new(m20250108_000001_extend_video_for_bangumi::Migration),
             Box::new(m20250108_000002_add_video_source::Migration),
             Box::new(m20250108_000003_add_source_fields::Migration),
+            Box::new(m20251021_083012_add_danmaku_snapshot::Migration),
+            Box::new(m20251023_142530_add_video_stat::Migration),
+            Box::new(m20251025_101500_convert_rule_to_tree::Migration),
+            Box::new(m20251104_093000_add_source_trigger::Migration),
+            Box::new(m20251110_120000_add_download_queue::Migration),
         ]
     }
 }
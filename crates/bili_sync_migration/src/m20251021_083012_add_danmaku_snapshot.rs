@@ -0,0 +1,146 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::schema::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Page::Table)
+                    .add_column(integer(Page::DanmakuSnapshot).default(0))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Favorite::Table)
+                    .add_column(boolean(Favorite::DanmakuSnapshot).default(false))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Collection::Table)
+                    .add_column(boolean(Collection::DanmakuSnapshot).default(false))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Submission::Table)
+                    .add_column(boolean(Submission::DanmakuSnapshot).default(false))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(WatchLater::Table)
+                    .add_column(boolean(WatchLater::DanmakuSnapshot).default(false))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Bangumi::Table)
+                    .add_column(boolean(Bangumi::DanmakuSnapshot).default(false))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Page::Table)
+                    .drop_column(Page::DanmakuSnapshot)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Favorite::Table)
+                    .drop_column(Favorite::DanmakuSnapshot)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Collection::Table)
+                    .drop_column(Collection::DanmakuSnapshot)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Submission::Table)
+                    .drop_column(Submission::DanmakuSnapshot)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(WatchLater::Table)
+                    .drop_column(WatchLater::DanmakuSnapshot)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Bangumi::Table)
+                    .drop_column(Bangumi::DanmakuSnapshot)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Page {
+    Table,
+    DanmakuSnapshot,
+}
+
+#[derive(DeriveIden)]
+enum Favorite {
+    Table,
+    DanmakuSnapshot,
+}
+
+#[derive(DeriveIden)]
+enum Collection {
+    Table,
+    DanmakuSnapshot,
+}
+
+#[derive(DeriveIden)]
+enum Submission {
+    Table,
+    DanmakuSnapshot,
+}
+
+#[derive(DeriveIden)]
+enum WatchLater {
+    Table,
+    DanmakuSnapshot,
+}
+
+#[derive(DeriveIden)]
+enum Bangumi {
+    Table,
+    DanmakuSnapshot,
+}
@@ -0,0 +1,60 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::schema::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // sqlite 不支持在一条语句中添加多列，需要逐列添加
+        for column in [
+            Video::PlayCount,
+            Video::LikeCount,
+            Video::CoinCount,
+            Video::FavoriteCount,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Video::Table)
+                        .add_column(big_integer(column).default(0))
+                        .to_owned(),
+                )
+                .await?;
+        }
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Video::Table)
+                    .add_column(string_null(Video::PartitionName))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [
+            Video::PlayCount,
+            Video::LikeCount,
+            Video::CoinCount,
+            Video::FavoriteCount,
+            Video::PartitionName,
+        ] {
+            manager
+                .alter_table(Table::alter().table(Video::Table).drop_column(column).to_owned())
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden, Clone, Copy)]
+enum Video {
+    Table,
+    PlayCount,
+    LikeCount,
+    CoinCount,
+    FavoriteCount,
+    PartitionName,
+}
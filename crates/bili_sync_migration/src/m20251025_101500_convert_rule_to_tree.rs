@@ -0,0 +1,128 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, Statement};
+use serde_json::{Value, json};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const RULE_TABLES: [&str; 5] = ["favorite", "collection", "submission", "watch_later", "bangumi"];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        convert_rules(manager, legacy_to_tree).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        convert_rules(manager, tree_to_legacy).await
+    }
+}
+
+/// 逐行读取各视频源的 rule 列，转换格式后写回，无法转换的规则保持不变
+async fn convert_rules(manager: &SchemaManager<'_>, convert: fn(&Value) -> Option<Value>) -> Result<(), DbErr> {
+    let db = manager.get_connection();
+    let backend = db.get_database_backend();
+    for table in RULE_TABLES {
+        let rows = db
+            .query_all(Statement::from_string(
+                backend,
+                format!("SELECT `id`, `rule` FROM `{}` WHERE `rule` IS NOT NULL", table),
+            ))
+            .await?;
+        for row in rows {
+            let id: i32 = row.try_get("", "id")?;
+            let rule: String = row.try_get("", "rule")?;
+            let Some(converted) = serde_json::from_str(&rule).ok().as_ref().and_then(convert) else {
+                continue;
+            };
+            db.execute(Statement::from_sql_and_values(
+                backend,
+                format!("UPDATE `{}` SET `rule` = ? WHERE `id` = ?", table),
+                [converted.to_string().into(), id.into()],
+            ))
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+/// [[target, ...], ...] => {"or": [{"and": [{"target": target}, ...]}, ...]}
+fn legacy_to_tree(rule: &Value) -> Option<Value> {
+    fn convert_target(target: &Value) -> Value {
+        if target["field"] == "not" {
+            json!({ "not": convert_target(&target["rule"]) })
+        } else {
+            json!({ "target": target })
+        }
+    }
+    let groups = rule.as_array()?;
+    // 旧版的空规则表示不做筛选，转换为恒为真的空“且”，空的“或”恒为假且无法被反序列化
+    if groups.is_empty() {
+        return Some(json!({ "and": [] }));
+    }
+    let groups = groups
+        .iter()
+        .map(|group| Some(json!({ "and": group.as_array()?.iter().map(convert_target).collect::<Vec<_>>() })))
+        .collect::<Option<Vec<_>>>()?;
+    Some(json!({ "or": groups }))
+}
+
+/// 仅能回退“或”包含“且”、“且”中只有条件或单个条件取反的规则
+fn tree_to_legacy(rule: &Value) -> Option<Value> {
+    if rule.get("and").and_then(Value::as_array).is_some_and(Vec::is_empty) {
+        return Some(json!([]));
+    }
+    fn convert_target(node: &Value) -> Option<Value> {
+        if let Some(target) = node.get("target") {
+            return Some(target.clone());
+        }
+        let target = node.get("not")?.get("target")?;
+        Some(json!({ "field": "not", "rule": target }))
+    }
+    let groups = rule
+        .get("or")?
+        .as_array()?
+        .iter()
+        .map(|group| group.get("and")?.as_array()?.iter().map(convert_target).collect())
+        .collect::<Option<Vec<Vec<_>>>>()?;
+    Some(json!(groups))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_rule() {
+        let legacy = json!([
+            [
+                { "field": "title", "rule": { "operator": "contains", "value": "a" } },
+                { "field": "not", "rule": { "field": "pageCount", "rule": { "operator": "greaterThan", "value": 5 } } }
+            ],
+            []
+        ]);
+        let tree = legacy_to_tree(&legacy).unwrap();
+        assert_eq!(
+            tree,
+            json!({ "or": [
+                { "and": [
+                    { "target": { "field": "title", "rule": { "operator": "contains", "value": "a" } } },
+                    { "not": { "target": { "field": "pageCount", "rule": { "operator": "greaterThan", "value": 5 } } } }
+                ] },
+                { "and": [] }
+            ] })
+        );
+        assert_eq!(tree_to_legacy(&tree).unwrap(), legacy);
+        // 已经是新格式的规则不会被重复转换
+        assert!(legacy_to_tree(&tree).is_none());
+        assert!(tree_to_legacy(&json!({ "not": { "or": [] } })).is_none());
+    }
+
+    #[test]
+    fn test_convert_empty_rule() {
+        let tree = legacy_to_tree(&json!([])).unwrap();
+        assert_eq!(tree, json!({ "and": [] }));
+        assert_eq!(tree_to_legacy(&tree).unwrap(), json!([]));
+        assert!(tree_to_legacy(&json!({ "and": [{ "target": {} }] })).is_none());
+    }
+}
@@ -0,0 +1,67 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(DownloadQueue::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(DownloadQueue::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(DownloadQueue::VideoId).integer().not_null().unique_key())
+                    .col(ColumnDef::new(DownloadQueue::SourceType).string().not_null())
+                    .col(ColumnDef::new(DownloadQueue::SourceId).integer().not_null())
+                    .col(ColumnDef::new(DownloadQueue::Priority).integer().not_null().default(0))
+                    .col(ColumnDef::new(DownloadQueue::Position).big_integer().not_null())
+                    .col(ColumnDef::new(DownloadQueue::Status).integer().not_null().default(0))
+                    .col(
+                        ColumnDef::new(DownloadQueue::CreatedAt)
+                            .timestamp()
+                            .default(Expr::current_timestamp())
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .table(DownloadQueue::Table)
+                    .name("download_queue_order_index")
+                    .col(DownloadQueue::Status)
+                    .col(DownloadQueue::Priority)
+                    .col(DownloadQueue::Position)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(DownloadQueue::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum DownloadQueue {
+    Table,
+    Id,
+    VideoId,
+    SourceType,
+    SourceId,
+    Priority,
+    Position,
+    Status,
+    CreatedAt,
+}
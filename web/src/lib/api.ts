@@ -26,10 +26,17 @@ import type {
 	TaskStatus,
 	ResetVideoStatusRequest,
 	UpdateVideoSourceResponse,
+	RuleDryRunRequest,
+	RuleDryRunResponse,
 	Notifier,
+	TemplatePreviewRequest,
+	TemplatePreviewResponse,
+	RelayoutReport,
 	UpdateFilteredVideoStatusRequest,
 	UpdateFilteredVideoStatusResponse,
-	ResetFilteredVideoStatusRequest
+	ResetFilteredVideoStatusRequest,
+	QueueResponse,
+	DownloadProgress
 } from './types';
 import { wsManager } from './ws';
 
@@ -275,6 +282,18 @@ class ApiClient {
 		return this.post<boolean>(`/video-sources/${type}/${id}/evaluate`, null);
 	}
 
+	async syncVideoSource(type: string, id: number): Promise<ApiResponse<boolean>> {
+		return this.post<boolean>(`/video-sources/${type}/${id}/sync`, null);
+	}
+
+	async dryRunVideoSourceRule(
+		type: string,
+		id: number,
+		request: RuleDryRunRequest
+	): Promise<ApiResponse<RuleDryRunResponse>> {
+		return this.post<RuleDryRunResponse>(`/video-sources/${type}/${id}/dry-run`, request);
+	}
+
 	async getDefaultPath(type: string, name: string): Promise<ApiResponse<string>> {
 		return this.get<string>(`/video-sources/${type}/default-path`, { name });
 	}
@@ -283,6 +302,12 @@ class ApiClient {
 		return this.post<boolean>('/config/notifiers/ping', notifier);
 	}
 
+	async previewTemplate(
+		request: TemplatePreviewRequest
+	): Promise<ApiResponse<TemplatePreviewResponse>> {
+		return this.post<TemplatePreviewResponse>('/config/template/preview', request);
+	}
+
 	async getConfig(): Promise<ApiResponse<Config>> {
 		return this.get<Config>('/config');
 	}
@@ -299,6 +324,33 @@ class ApiClient {
 		return this.post<boolean>('/task/download');
 	}
 
+	async controlDownloadTask(action: 'pause' | 'resume' | 'cancel'): Promise<ApiResponse<boolean>> {
+		return this.post<boolean>(`/task/${action}`);
+	}
+
+	async relayoutFiles(dryRun: boolean): Promise<ApiResponse<RelayoutReport>> {
+		return this.post<RelayoutReport>('/task/relayout', { dry_run: dryRun });
+	}
+
+	async getQueue(): Promise<ApiResponse<QueueResponse>> {
+		return this.get<QueueResponse>('/queue');
+	}
+
+	async updateQueuePriority(id: number, priority: number): Promise<ApiResponse<boolean>> {
+		return this.put<boolean>(`/queue/${id}/priority`, { priority });
+	}
+
+	async reorderQueue(ids: number[]): Promise<ApiResponse<boolean>> {
+		return this.post<boolean>('/queue/reorder', { ids });
+	}
+
+	async updateQueueItem(
+		id: number,
+		action: 'pause' | 'resume' | 'cancel'
+	): Promise<ApiResponse<boolean>> {
+		return this.post<boolean>(`/queue/${id}/${action}`);
+	}
+
 	subscribeToLogs(onMessage: (data: string) => void) {
 		return wsManager.subscribeToLogs(onMessage);
 	}
@@ -308,6 +360,9 @@ class ApiClient {
 	subscribeToTasks(onMessage: (data: TaskStatus) => void) {
 		return wsManager.subscribeToTasks(onMessage);
 	}
+	subscribeToProgress(onMessage: (data: DownloadProgress[]) => void) {
+		return wsManager.subscribeToProgress(onMessage);
+	}
 }
 
 // 创建默认的 API 客户端实例
@@ -346,12 +401,25 @@ const api = {
 	removeVideoSource: (type: string, id: number) => apiClient.removeVideoSource(type, id),
 	evaluateVideoSourceRules: (type: string, id: number) =>
 		apiClient.evaluateVideoSourceRules(type, id),
+	syncVideoSource: (type: string, id: number) => apiClient.syncVideoSource(type, id),
+	dryRunVideoSourceRule: (type: string, id: number, request: RuleDryRunRequest) =>
+		apiClient.dryRunVideoSourceRule(type, id, request),
 	getDefaultPath: (type: string, name: string) => apiClient.getDefaultPath(type, name),
 	testNotifier: (notifier: Notifier) => apiClient.testNotifier(notifier),
+	previewTemplate: (request: TemplatePreviewRequest) => apiClient.previewTemplate(request),
 	getConfig: () => apiClient.getConfig(),
 	updateConfig: (config: Config) => apiClient.updateConfig(config),
 	getDashboard: () => apiClient.getDashboard(),
 	triggerDownloadTask: () => apiClient.triggerDownloadTask(),
+	controlDownloadTask: (action: 'pause' | 'resume' | 'cancel') =>
+		apiClient.controlDownloadTask(action),
+	relayoutFiles: (dryRun: boolean) => apiClient.relayoutFiles(dryRun),
+	getQueue: () => apiClient.getQueue(),
+	updateQueuePriority: (id: number, priority: number) =>
+		apiClient.updateQueuePriority(id, priority),
+	reorderQueue: (ids: number[]) => apiClient.reorderQueue(ids),
+	updateQueueItem: (id: number, action: 'pause' | 'resume' | 'cancel') =>
+		apiClient.updateQueueItem(id, action),
 	subscribeToSysInfo: (onMessage: (data: SysInfo) => void) =>
 		apiClient.subscribeToSysInfo(onMessage),
 
@@ -360,6 +428,9 @@ const api = {
 	subscribeToTasks: (onMessage: (data: TaskStatus) => void) =>
 		apiClient.subscribeToTasks(onMessage),
 
+	subscribeToProgress: (onMessage: (data: DownloadProgress[]) => void) =>
+		apiClient.subscribeToProgress(onMessage),
+
 	setAuthToken: (token: string) => apiClient.setAuthToken(token),
 	clearAuthToken: () => apiClient.clearAuthToken()
 };
@@ -35,7 +35,7 @@ export interface VideoInfo {
 	name: string;
 	upper_name: string;
 	should_download: boolean;
-	download_status: [number, number, number, number, number];
+	download_status: [number, number, number, number, number, number];
 }
 
 export interface VideosResponse {
@@ -223,8 +223,14 @@ export interface RuleTarget<T> {
 	rule: Condition<T> | RuleTarget<T>;
 }
 
-export type AndGroup = RuleTarget<string | number | Date>[];
-export type Rule = AndGroup[];
+// 规则表达式树
+export type RuleNode =
+	| { and: RuleNode[] }
+	| { or: RuleNode[] }
+	| { not: RuleNode }
+	| { target: RuleTarget<string | number | Date> };
+// 也可以直接提交表达式文本，由后端解析
+export type Rule = RuleNode | string;
 
 export interface VideoSourceDetail {
 	id: number;
@@ -232,8 +238,11 @@ export interface VideoSourceDetail {
 	path: string;
 	rule: Rule | null;
 	ruleDisplay: string | null;
+	ruleDsl: string | null;
 	useDynamicApi: boolean | null;
 	enabled: boolean;
+	danmakuSnapshot: boolean;
+	trigger: Trigger | null;
 	seasonType?: number | null;
 }
 
@@ -251,6 +260,8 @@ export interface UpdateVideoSourceRequest {
 	enabled: boolean;
 	rule?: Rule | null;
 	useDynamicApi?: boolean | null;
+	danmakuSnapshot?: boolean;
+	trigger?: Trigger | null;
 }
 
 export interface Credential {
@@ -296,6 +307,18 @@ export interface SkipOption {
 	no_subtitle: boolean;
 }
 
+export interface CommentOption {
+	enabled: boolean;
+	max_pages: number;
+	max_sub_replies: number;
+}
+
+// 全局规则以 dsl 文本表示
+export interface GlobalRule {
+	deny: string | null;
+	allow: string | null;
+}
+
 export interface RateLimit {
 	limit: number;
 	duration: number;
@@ -308,39 +331,130 @@ export interface ConcurrentDownloadLimit {
 }
 
 export interface ConcurrentLimit {
+	source: number;
 	video: number;
 	page: number;
 	rate_limit?: RateLimit;
 	download: ConcurrentDownloadLimit;
 }
 
+export type Weekday = 'Mon' | 'Tue' | 'Wed' | 'Thu' | 'Fri' | 'Sat' | 'Sun';
+
+// 结束时间不晚于开始时间时表示跨越午夜
+export interface DownloadWindow {
+	weekdays: Weekday[];
+	start: string;
+	end: string;
+}
+
+export interface RiskControlOption {
+	enabled: boolean;
+	base_backoff: number;
+	max_backoff: number;
+	recovery_period: number;
+}
+
+export type NotificationEventKind =
+	| 'run_started'
+	| 'run_finished'
+	| 'video_downloaded'
+	| 'download_failed'
+	| 'credential_refresh_failed'
+	| 'risk_control'
+	| 'error';
+
 export interface TelegramNotifier {
 	type: 'telegram';
 	bot_token: string;
 	chat_id: string;
+	events?: NotificationEventKind[];
 }
 
 export interface WebhookNotifier {
 	type: 'webhook';
 	url: string;
 	template?: string | null;
+	events?: NotificationEventKind[];
+}
+
+export interface NtfyNotifier {
+	type: 'ntfy';
+	server_url: string;
+	topic: string;
+	token?: string | null;
+	events?: NotificationEventKind[];
+}
+
+export interface GotifyNotifier {
+	type: 'gotify';
+	server_url: string;
+	token: string;
+	events?: NotificationEventKind[];
+}
+
+export interface BarkNotifier {
+	type: 'bark';
+	server_url: string;
+	device_key: string;
+	events?: NotificationEventKind[];
+}
+
+export interface ServerChanNotifier {
+	type: 'serverChan';
+	send_key: string;
+	server_url?: string | null;
+	events?: NotificationEventKind[];
+}
+
+export interface DiscordNotifier {
+	type: 'discord';
+	webhook_url: string;
+	events?: NotificationEventKind[];
+}
+
+export type SmtpSecurity = 'tls' | 'starttls' | 'none';
+
+export interface EmailNotifier {
+	type: 'email';
+	smtp_host: string;
+	smtp_port: number;
+	security: SmtpSecurity;
+	username?: string | null;
+	password?: string | null;
+	from: string;
+	to: string[];
+	events?: NotificationEventKind[];
 }
 
-export type Notifier = TelegramNotifier | WebhookNotifier;
+export type Notifier =
+	| TelegramNotifier
+	| WebhookNotifier
+	| NtfyNotifier
+	| GotifyNotifier
+	| BarkNotifier
+	| ServerChanNotifier
+	| DiscordNotifier
+	| EmailNotifier;
 
 export type Trigger = number | string;
 
+export type NotificationDigest = 'off' | 'per_run' | { cron: string };
+
 export interface Config {
 	auth_token: string;
 	bind_address: string;
 	credential: Credential;
 	filter_option: FilterOption;
 	danmaku_option: DanmakuOption;
+	danmaku_snapshot_days: number[];
 	skip_option: SkipOption;
+	comment_option: CommentOption;
+	global_rule: GlobalRule;
 	video_name: string;
 	page_name: string;
 	bangumi_name: string;
 	notifiers: Notifier[] | null;
+	notification_digest: NotificationDigest;
 	favorite_default_path: string;
 	collection_default_path: string;
 	submission_default_path: string;
@@ -349,6 +463,8 @@ export interface Config {
 	upper_path: string;
 	nfo_time_type: string;
 	concurrent_limit: ConcurrentLimit;
+	risk_control: RiskControlOption;
+	download_windows: DownloadWindow[];
 	time_format: string;
 	cdn_sorting: boolean;
 	version: number;
@@ -366,6 +482,17 @@ export interface DashBoardResponse {
 	enable_watch_later: boolean;
 	enabled_bangumi: number;
 	videos_by_day: DayCountPair[];
+	risk_control: RiskControlState;
+}
+
+export interface RiskControlState {
+	level: number;
+	count: number;
+	last_source: string | null;
+	last_error: string | null;
+	last_triggered: string | null;
+	retry_at: string | null;
+	recover_at: string | null;
 }
 
 export interface SysInfo {
@@ -380,13 +507,141 @@ export interface SysInfo {
 	uptime: number;
 }
 
+export interface QueueStats {
+	pending: number;
+	running: number;
+	paused: number;
+	cancelled: number;
+	revision: number;
+}
+
 export interface TaskStatus {
 	is_running: boolean;
+	is_paused: boolean;
 	last_run: Date | null;
 	last_finish: Date | null;
 	next_run: Date | null;
+	source: { source_type: string; id: number } | null;
+	queue: QueueStats;
+}
+
+export type ProgressStage = 'downloading' | 'merging';
+
+export interface DownloadProgress {
+	id: number;
+	video_id: number;
+	bvid: string;
+	video_name: string;
+	page_id: number;
+	pid: number;
+	page_name: string;
+	stage: ProgressStage;
+	downloaded: number;
+	total: number;
+	speed: number;
+	eta: number | null;
+}
+
+export type QueueStatus = 'pending' | 'running' | 'paused' | 'cancelled';
+
+export interface QueueItem {
+	id: number;
+	video_id: number;
+	bvid: string;
+	name: string;
+	upper_name: string;
+	source_type: string;
+	source_id: number;
+	priority: number;
+	position: number;
+	status: QueueStatus;
+}
+
+export interface QueueResponse {
+	items: QueueItem[];
+	stats: QueueStats;
 }
 
 export interface UpdateVideoSourceResponse {
 	ruleDisplay: string;
+	ruleDsl: string;
+	rescheduleError: string | null;
+}
+
+export interface TemplatePreviewRequest {
+	video_name?: string;
+	page_name?: string;
+	bangumi_name?: string;
+	limit?: number;
+}
+
+export interface TemplatePreviewSample {
+	video_id: number;
+	bvid: string;
+	name: string;
+	paths: string[];
+	error: string | null;
+}
+
+export interface TemplatePreviewResponse {
+	errors: string[];
+	samples: TemplatePreviewSample[];
+}
+
+export interface FileMove {
+	from: string;
+	to: string;
+}
+
+export interface VideoRelayout {
+	video_id: number;
+	bvid: string;
+	name: string;
+	old_path: string;
+	new_path: string;
+	moves: FileMove[];
+	conflicts: string[];
+	error: string | null;
+}
+
+export interface RelayoutReport {
+	dry_run: boolean;
+	planned: number;
+	relocated: number;
+	conflicted: number;
+	failed: number;
+	videos: VideoRelayout[];
+}
+
+export interface RuleExplanation {
+	rule: string;
+	matched: boolean;
+	children?: RuleExplanation[];
+}
+
+export interface RuleDryRunVideo {
+	id: number;
+	bvid: string;
+	name: string;
+	shouldDownload: boolean;
+	matched: boolean;
+	explanation: RuleExplanation | null;
+	globalDeny: RuleExplanation | null;
+	globalAllow: RuleExplanation | null;
+}
+
+export interface RuleDryRunRequest {
+	rule: Rule | null;
+	changedOnly?: boolean;
+	page?: number;
+	pageSize?: number;
+}
+
+export interface RuleDryRunResponse {
+	total: number;
+	matched: number;
+	newlyIncluded: number;
+	newlyExcluded: number;
+	totalCount: number;
+	videos: RuleDryRunVideo[];
 }
@@ -1,11 +1,12 @@
 import { toast } from 'svelte-sonner';
-import type { SysInfo, TaskStatus } from './types';
+import type { DownloadProgress, SysInfo, TaskStatus } from './types';
 
 // 支持的事件类型
 export enum EventType {
 	Logs = 'logs',
 	Tasks = 'tasks',
-	SysInfo = 'sysInfo'
+	SysInfo = 'sysInfo',
+	Progress = 'progress'
 }
 
 // 服务器事件响应格式
@@ -13,6 +14,7 @@ interface ServerEvent {
 	logs?: string;
 	tasks?: TaskStatus;
 	sysInfo?: SysInfo;
+	progress?: DownloadProgress[];
 }
 
 // 客户端事件请求格式
@@ -25,6 +27,7 @@ interface ClientEvent {
 type LogsCallback = (data: string) => void;
 type TasksCallback = (data: TaskStatus) => void;
 type SysInfoCallback = (data: SysInfo) => void;
+type ProgressCallback = (data: DownloadProgress[]) => void;
 type ErrorCallback = (error: Event) => void;
 
 export class WebSocketManager {
@@ -40,6 +43,7 @@ export class WebSocketManager {
 	private logsSubscribers: Set<LogsCallback> = new Set();
 	private tasksSubscribers: Set<TasksCallback> = new Set();
 	private sysInfoSubscribers: Set<SysInfoCallback> = new Set();
+	private progressSubscribers: Set<ProgressCallback> = new Set();
 	private errorSubscribers: Set<ErrorCallback> = new Set();
 
 	private subscribedEvents: Set<EventType> = new Set();
@@ -118,6 +122,8 @@ export class WebSocketManager {
 				this.notifyTasksSubscribers(data.tasks);
 			} else if (data.sysInfo !== undefined) {
 				this.notifySysInfoSubscribers(data.sysInfo);
+			} else if (data.progress !== undefined) {
+				this.notifyProgressSubscribers(data.progress);
 			}
 		} catch (error) {
 			console.error('Failed to parse WebSocket message:', error, event.data);
@@ -230,6 +236,22 @@ export class WebSocketManager {
 		};
 	}
 
+	// 订阅下载进度
+	public subscribeToProgress(callback: ProgressCallback): () => void {
+		this.progressSubscribers.add(callback);
+
+		if (this.progressSubscribers.size === 1) {
+			this.subscribe(EventType.Progress);
+		}
+
+		return () => {
+			this.progressSubscribers.delete(callback);
+			if (this.progressSubscribers.size === 0) {
+				this.unsubscribe(EventType.Progress);
+			}
+		};
+	}
+
 	private notifyLogsSubscribers(data: string): void {
 		this.logsSubscribers.forEach((callback) => {
 			try {
@@ -260,6 +282,16 @@ export class WebSocketManager {
 		});
 	}
 
+	private notifyProgressSubscribers(data: DownloadProgress[]): void {
+		this.progressSubscribers.forEach((callback) => {
+			try {
+				callback(data);
+			} catch (error) {
+				console.error('Error in progress subscriber callback:', error);
+			}
+		});
+	}
+
 	public disconnect(): void {
 		if (this.socket) {
 			this.socket.close();